@@ -0,0 +1,21 @@
+CREATE TYPE snipe_kind AS ENUM ('deleted', 'edited');
+
+-- ring of recently deleted and edited messages, trimmed per channel on insert.
+CREATE TABLE snipes (
+    id BIGSERIAL PRIMARY KEY,
+    message_id BIGINT NOT NULL,
+    channel_id INT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    guild_id INT REFERENCES guilds(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    username VARCHAR(32) NOT NULL,
+    kind snipe_kind NOT NULL,
+    content TEXT NOT NULL,
+    -- only set for edits.
+    old_content TEXT,
+    attachment_urls TEXT[] NOT NULL,
+    -- the message in the archive channel holding the reuploaded attachments.
+    archive_message_id BIGINT,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_snipes_channel_id ON snipes(channel_id, id DESC);
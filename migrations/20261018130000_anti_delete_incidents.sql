@@ -0,0 +1,14 @@
+CREATE TABLE anti_delete_incidents (
+    id SERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    channel_id INT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    -- the author of the deleted messages.
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- deleted messages that had their content available.
+    deleted_count INT NOT NULL,
+    digest TEXT NOT NULL,
+    timed_out BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_anti_delete_incidents_guild_id ON anti_delete_incidents(guild_id);
@@ -0,0 +1,18 @@
+-- channels that are locked down, their overwrites from before are restored on unlock.
+CREATE TABLE lockdown_channels (
+    channel_id INT PRIMARY KEY REFERENCES channels(id) ON DELETE CASCADE,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE lockdown_overwrites (
+    channel_id INT NOT NULL REFERENCES lockdown_channels(channel_id) ON DELETE CASCADE,
+    -- the discord id of the role or member the overwrite is for.
+    target_id BIGINT NOT NULL,
+    is_role BOOLEAN NOT NULL,
+    allow BIGINT NOT NULL,
+    deny BIGINT NOT NULL
+);
+
+CREATE INDEX idx_lockdown_channels_guild_id ON lockdown_channels(guild_id);
+CREATE INDEX idx_lockdown_overwrites_channel_id ON lockdown_overwrites(channel_id);
@@ -0,0 +1,16 @@
+CREATE TYPE name_kind AS ENUM ('username', 'global_name', 'nickname');
+
+-- every observed username, display name and nickname.
+CREATE TABLE name_history (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- only set for nicknames.
+    guild_id INT REFERENCES guilds(id) ON DELETE CASCADE,
+    kind name_kind NOT NULL,
+    -- null when the display name or nickname was removed.
+    name TEXT,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_name_history_user_id ON name_history(user_id, id DESC);
+CREATE INDEX idx_name_history_name ON name_history(LOWER(name));
@@ -0,0 +1,19 @@
+CREATE TYPE appearance_kind AS ENUM ('avatar', 'guild_avatar', 'decoration', 'nameplate');
+
+-- every observed avatar, avatar decoration and nameplate.
+CREATE TABLE appearance_history (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- only set for guild avatars.
+    guild_id INT REFERENCES guilds(id) ON DELETE CASCADE,
+    kind appearance_kind NOT NULL,
+    -- the image hash or nameplate asset path, null when it was removed.
+    asset TEXT,
+    -- only set for decorations and nameplates.
+    sku_id BIGINT,
+    -- the local copy of the image, relative to the working directory.
+    file_path TEXT,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_appearance_history_user_id ON appearance_history(user_id, id DESC);
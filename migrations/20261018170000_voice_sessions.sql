@@ -0,0 +1,21 @@
+CREATE TABLE voice_sessions (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    channel_id INT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    started_at TIMESTAMPTZ NOT NULL,
+    -- null while the session is open.
+    ended_at TIMESTAMPTZ,
+    -- time spent muted or deafened, by themselves or a moderator.
+    muted_seconds INT NOT NULL DEFAULT 0,
+    deafened_seconds INT NOT NULL DEFAULT 0,
+    -- when the current mute or deafen started, only set while the session is open.
+    muted_since TIMESTAMPTZ,
+    deafened_since TIMESTAMPTZ,
+    -- the last time the bot saw the session open, sessions that ended while it was offline are closed here.
+    last_seen_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_voice_sessions_guild_id ON voice_sessions(guild_id, channel_id);
+CREATE INDEX idx_voice_sessions_user_id ON voice_sessions(user_id);
+CREATE INDEX idx_voice_sessions_open ON voice_sessions(guild_id, user_id) WHERE ended_at IS NULL;
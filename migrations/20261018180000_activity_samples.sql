@@ -0,0 +1,11 @@
+-- periodic counts of members playing each game per guild.
+CREATE TABLE activity_samples (
+    id BIGSERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    players INT NOT NULL,
+    sampled_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_activity_samples_guild_id ON activity_samples(guild_id, sampled_at);
+CREATE INDEX idx_activity_samples_name ON activity_samples(guild_id, LOWER(name), sampled_at);
@@ -0,0 +1,31 @@
+CREATE TABLE nuke_incidents (
+    id SERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    -- the executor of the destructive actions.
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- the channels, roles and members targeted by the destructive actions.
+    target_ids BIGINT[] NOT NULL,
+    roles_stripped BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_nuke_incidents_guild_id ON nuke_incidents(guild_id);
+
+CREATE TYPE snapshot_kind AS ENUM ('channel', 'role');
+
+-- deleted channels and roles, kept so they can be recreated.
+CREATE TABLE deleted_snapshots (
+    id SERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    kind snapshot_kind NOT NULL,
+    target_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    -- the serialized channel or role.
+    data TEXT NOT NULL,
+    incident_id INT REFERENCES nuke_incidents(id) ON DELETE SET NULL,
+    restored BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_deleted_snapshots_guild_id ON deleted_snapshots(guild_id, target_id);
+CREATE INDEX idx_deleted_snapshots_incident_id ON deleted_snapshots(incident_id);
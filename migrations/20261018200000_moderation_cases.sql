@@ -0,0 +1,18 @@
+CREATE TYPE case_action AS ENUM ('kick', 'ban', 'unban', 'prune');
+
+CREATE TABLE moderation_cases (
+    id SERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    -- the audit log entry the case was created from, so it is never recorded twice.
+    audit_log_id BIGINT NOT NULL UNIQUE,
+    action case_action NOT NULL,
+    moderator_id INT REFERENCES users(id) ON DELETE SET NULL,
+    -- NULL for prunes, which affect many members at once.
+    target_id INT REFERENCES users(id) ON DELETE CASCADE,
+    reason TEXT,
+    prune_days INT,
+    pruned_count INT,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_moderation_cases_target_id ON moderation_cases(guild_id, target_id);
@@ -0,0 +1,12 @@
+CREATE TABLE member_joins (
+    id SERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- NULL when the invite used couldn't be worked out.
+    invite_code TEXT,
+    inviter_id INT REFERENCES users(id) ON DELETE SET NULL,
+    joined_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_member_joins_invite_code ON member_joins(guild_id, invite_code);
+CREATE INDEX idx_member_joins_inviter_id ON member_joins(guild_id, inviter_id);
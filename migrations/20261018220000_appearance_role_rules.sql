@@ -0,0 +1,28 @@
+CREATE TABLE appearance_role_rules (
+    id SERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    -- a member matches the rule if any of these match their appearance.
+    decoration_skus BIGINT[] NOT NULL DEFAULT '{}',
+    nameplate_assets TEXT[] NOT NULL DEFAULT '{}',
+    guild_tags TEXT[] NOT NULL DEFAULT '{}',
+    avatar_hashes TEXT[] NOT NULL DEFAULT '{}',
+    add_roles BIGINT[] NOT NULL DEFAULT '{}',
+    remove_roles BIGINT[] NOT NULL DEFAULT '{}',
+    announce_channel BIGINT,
+    -- templates, {user}, {rule} and {link} are replaced when sent.
+    reply_message TEXT,
+    added_message TEXT,
+    restored_message TEXT,
+    removed_message TEXT,
+    UNIQUE (guild_id, name)
+);
+
+CREATE TABLE appearance_role_assignments (
+    rule_id INT NOT NULL REFERENCES appearance_role_rules(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    PRIMARY KEY (rule_id, user_id)
+);
+
+-- rules are set up with the appearance-rule-set command. auto_bad_role is kept so the members of
+-- the old hard-coded rule can be carried over into appearance_role_assignments once it exists.
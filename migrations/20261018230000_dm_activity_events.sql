@@ -0,0 +1,12 @@
+CREATE TABLE dm_activity_events (
+    id SERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    until TIMESTAMPTZ NOT NULL,
+    observed_at TIMESTAMPTZ NOT NULL,
+    -- whether the flag was announced, extensions of a recent flag aren't.
+    announced BOOLEAN NOT NULL,
+    escalated BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX idx_dm_activity_events_user_id ON dm_activity_events(guild_id, user_id, observed_at);
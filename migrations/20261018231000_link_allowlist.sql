@@ -0,0 +1,8 @@
+CREATE TABLE link_allowlist (
+    id SERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    domain TEXT NOT NULL,
+    added_by INT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (guild_id, domain)
+);
@@ -0,0 +1,24 @@
+CREATE TABLE reaction_blocks (
+    id SERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    -- exactly one of these is set, the block applies to their messages or to the channel.
+    target_user_id INT REFERENCES users(id) ON DELETE CASCADE,
+    channel_id BIGINT,
+    -- the name of a custom emoji, so every copy of it is caught, or the unicode emoji itself.
+    emoji TEXT NOT NULL,
+    added_by INT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    CHECK ((target_user_id IS NULL) <> (channel_id IS NULL))
+);
+
+CREATE UNIQUE INDEX reaction_blocks_unique
+    ON reaction_blocks (guild_id, COALESCE(target_user_id, 0), COALESCE(channel_id, 0), emoji);
+
+-- carry over the hard-coded giga_mpreg block.
+INSERT INTO guilds (guild_id) VALUES (98226572468690944) ON CONFLICT (guild_id) DO NOTHING;
+INSERT INTO users (user_id) VALUES (291089948709486593) ON CONFLICT (user_id) DO NOTHING;
+
+INSERT INTO reaction_blocks (guild_id, target_user_id, emoji, created_at)
+SELECT g.id, u.id, 'giga_mpreg', NOW()
+FROM guilds g, users u
+WHERE g.guild_id = 98226572468690944 AND u.user_id = 291089948709486593;
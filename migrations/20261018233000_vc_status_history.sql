@@ -0,0 +1,15 @@
+CREATE TABLE vc_status_history (
+    id SERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    channel_id INT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    -- null when the status was removed.
+    status TEXT,
+    -- null when the change couldn't be found in the audit log.
+    user_id INT REFERENCES users(id) ON DELETE SET NULL,
+    blacklisted BOOLEAN NOT NULL DEFAULT FALSE,
+    -- the status was blacklisted and removed by the bot.
+    cleared BOOLEAN NOT NULL DEFAULT FALSE,
+    changed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_vc_status_history_channel_id ON vc_status_history(channel_id, changed_at);
@@ -39,3 +39,26 @@ pub const fn g(num: u32) -> u8 {
 pub const fn b(num: u32) -> u8 {
     (num & 255) as u8
 }
+
+/// Removes any ANSI escape sequences from a string, used when sending console output to Discord.
+#[must_use]
+pub fn strip(content: &str) -> String {
+    let mut stripped = String::with_capacity(content.len());
+    let mut chars = content.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1B' {
+            // skip until the final byte of the sequence.
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+
+        stripped.push(c);
+    }
+
+    stripped
+}
@@ -0,0 +1,156 @@
+use std::{collections::HashMap, fmt::Write};
+
+use crate::{Context, Error};
+use lumi::serenity_prelude::{
+    ChannelId, ChannelType, CreateAttachment, CreateChannel, EditRole, GuildChannel,
+    PermissionOverwriteType, Role, RoleColours, RoleId,
+};
+use moth_core::data::database::anti_nuke::{Snapshot, SnapshotKind};
+
+/// Recreate the channels and roles deleted during an anti nuke incident.
+#[lumi::command(
+    rename = "nuke-restore",
+    prefix_command,
+    slash_command,
+    category = "Moderation",
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    required_bot_permissions = "MANAGE_CHANNELS | MANAGE_ROLES"
+)]
+pub async fn nuke_restore(
+    ctx: Context<'_>,
+    #[description = "The incident id from the report."] incident: i32,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let data = ctx.data();
+
+    let snapshots = data
+        .database
+        .get_incident_snapshots(guild_id, incident)
+        .await?
+        .into_iter()
+        .filter(|s| !s.restored)
+        .collect::<Vec<_>>();
+
+    if snapshots.is_empty() {
+        ctx.say("There is nothing left to restore for that incident.")
+            .await?;
+        return Ok(());
+    }
+
+    ctx.defer().await?;
+
+    let reason = format!("Restored from anti nuke incident {incident}.");
+    let mut report = String::new();
+
+    // roles go first so channel overwrites can point at the recreated ones.
+    let mut roles = HashMap::new();
+    for snapshot in snapshots.iter().filter(|s| s.kind == SnapshotKind::Role) {
+        let Ok(role) = serde_json::from_str::<Role>(&snapshot.data) else {
+            writeln!(report, "Couldn't read the snapshot of @{}", snapshot.name).unwrap();
+            continue;
+        };
+
+        let builder = EditRole::new()
+            .name(role.name.as_str())
+            .colours(RoleColours {
+                primary_colour: role.colour,
+                secondary_colour: None,
+                tertiary_colour: None,
+            })
+            .hoist(role.hoist())
+            .mentionable(role.mentionable())
+            .permissions(role.permissions)
+            .audit_log_reason(&reason);
+
+        match guild_id.create_role(ctx.http(), builder).await {
+            Ok(new) => {
+                roles.insert(role.id, new.id);
+                mark_restored(ctx, snapshot).await;
+                writeln!(report, "Recreated <@&{}>", new.id).unwrap();
+            }
+            Err(e) => writeln!(report, "Failed to recreate @{}: {e}", role.name).unwrap(),
+        }
+    }
+
+    let mut channels = Vec::new();
+    for snapshot in snapshots.iter().filter(|s| s.kind == SnapshotKind::Channel) {
+        match serde_json::from_str::<GuildChannel>(&snapshot.data) {
+            Ok(channel) => channels.push((snapshot, channel)),
+            Err(_) => writeln!(report, "Couldn't read the snapshot of #{}", snapshot.name).unwrap(),
+        }
+    }
+
+    // categories first, so their children can be put back inside them.
+    channels.sort_by_key(|(_, c)| c.base.kind != ChannelType::Category);
+
+    let mut categories: HashMap<ChannelId, ChannelId> = HashMap::new();
+    for (snapshot, channel) in channels {
+        let overwrites = channel
+            .permission_overwrites
+            .iter()
+            .cloned()
+            .map(|mut o| {
+                if let PermissionOverwriteType::Role(id) = o.kind {
+                    o.kind = PermissionOverwriteType::Role(remap(&roles, id));
+                }
+                o
+            })
+            .collect::<Vec<_>>();
+
+        let mut builder = CreateChannel::new(channel.base.name.as_str())
+            .kind(channel.base.kind)
+            .position(channel.position)
+            .nsfw(channel.nsfw)
+            .permissions(overwrites)
+            .audit_log_reason(&reason);
+
+        if let Some(parent_id) = channel.parent_id {
+            builder = builder.category(categories.get(&parent_id).copied().unwrap_or(parent_id));
+        }
+        if let Some(topic) = &channel.topic {
+            builder = builder.topic(topic.as_str());
+        }
+        if let Some(user_limit) = channel.user_limit {
+            builder = builder.user_limit(user_limit);
+        }
+
+        match guild_id.create_channel(ctx.http(), builder).await {
+            Ok(new) => {
+                if channel.base.kind == ChannelType::Category {
+                    categories.insert(channel.id, new.id);
+                }
+                mark_restored(ctx, snapshot).await;
+                writeln!(report, "Recreated <#{}>", new.id).unwrap();
+            }
+            Err(e) => writeln!(report, "Failed to recreate #{}: {e}", channel.base.name).unwrap(),
+        }
+    }
+
+    let reply = if report.chars().count() > 2000 {
+        lumi::CreateReply::new()
+            .content("Restored, the results are attached.")
+            .attachment(CreateAttachment::bytes(report.into_bytes(), "restore.txt"))
+    } else {
+        lumi::CreateReply::new().content(report)
+    };
+
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+fn remap(roles: &HashMap<RoleId, RoleId>, id: RoleId) -> RoleId {
+    roles.get(&id).copied().unwrap_or(id)
+}
+
+async fn mark_restored(ctx: Context<'_>, snapshot: &Snapshot) {
+    if let Err(e) = ctx.data().database.set_snapshot_restored(snapshot.id).await {
+        println!("Failed to mark snapshot as restored: {e}");
+    }
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [nuke_restore()]
+}
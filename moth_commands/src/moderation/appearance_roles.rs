@@ -0,0 +1,220 @@
+use std::fmt::Write;
+
+use crate::{Context, Error};
+use lumi::serenity_prelude::{GuildChannel, Role, RoleId, SkuId};
+use moth_core::data::database::appearance_roles::AppearanceRoleRule;
+
+/// Splits a list given as one argument on commas and whitespace.
+fn split_list(input: Option<&str>) -> Vec<String> {
+    input
+        .unwrap_or_default()
+        .split([',', ' ', '\n'])
+        .filter(|item| !item.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Takes the ids out of a list, so role mentions work as well as plain ids.
+fn parse_ids(input: Option<&str>) -> Result<Vec<u64>, String> {
+    split_list(input)
+        .iter()
+        .map(|item| {
+            item.trim_start_matches("<@&")
+                .trim_end_matches('>')
+                .parse::<u64>()
+                .ok()
+                .filter(|id| *id != 0)
+                .ok_or_else(|| format!("`{item}` isn't an id."))
+        })
+        .collect()
+}
+
+fn list(items: impl Iterator<Item = String>) -> String {
+    let items = items.collect::<Vec<_>>();
+    if items.is_empty() {
+        String::from("None")
+    } else {
+        items.join(", ")
+    }
+}
+
+/// Give or take away roles based on the avatar decoration, nameplate, guild tag or avatar of a
+/// member, replacing the rule of the same name.
+///
+/// Lists are separated by commas or spaces. Messages can use {user}, {rule} and {link}.
+#[allow(clippy::too_many_arguments)]
+#[lumi::command(
+    rename = "appearance-rule-set",
+    prefix_command,
+    slash_command,
+    category = "Moderation",
+    guild_only,
+    required_permissions = "MANAGE_ROLES"
+)]
+pub async fn appearance_rule_set(
+    ctx: Context<'_>,
+    #[description = "The name of the rule."] name: String,
+    #[description = "The role given to matching members."] role: Role,
+    #[description = "Avatar decoration SKU ids."] decorations: Option<String>,
+    #[description = "Nameplate asset paths."] nameplates: Option<String>,
+    #[description = "Guild tags."] tags: Option<String>,
+    #[description = "Avatar hashes."] avatars: Option<String>,
+    #[description = "Roles taken from matching members."] remove_roles: Option<String>,
+    #[description = "Where changes are announced."] announce_channel: Option<GuildChannel>,
+    #[description = "Replied to the message of a member that starts matching."]
+    reply_message: Option<String>,
+    #[description = "Announced when the role is given."] added_message: Option<String>,
+    #[description = "Announced when a member that lost the role is given it again."]
+    restored_message: Option<String>,
+    #[description = "Announced when the role is taken away."] removed_message: Option<String>,
+) -> Result<(), Error> {
+    let (decoration_skus, remove_roles) = match (
+        parse_ids(decorations.as_deref()),
+        parse_ids(remove_roles.as_deref()),
+    ) {
+        (Ok(decorations), Ok(remove_roles)) => (decorations, remove_roles),
+        (Err(e), _) | (_, Err(e)) => {
+            ctx.say(e).await?;
+            return Ok(());
+        }
+    };
+
+    let rule = AppearanceRoleRule {
+        id: 0,
+        name: name.clone(),
+        decoration_skus: decoration_skus.into_iter().map(SkuId::new).collect(),
+        nameplate_assets: split_list(nameplates.as_deref()),
+        guild_tags: split_list(tags.as_deref()),
+        avatar_hashes: split_list(avatars.as_deref()),
+        add_roles: vec![role.id],
+        remove_roles: remove_roles.into_iter().map(RoleId::new).collect(),
+        announce_channel: announce_channel.map(|c| c.id.widen()),
+        reply_message,
+        added_message,
+        restored_message,
+        removed_message,
+    };
+
+    if rule.decoration_skus.is_empty()
+        && rule.nameplate_assets.is_empty()
+        && rule.guild_tags.is_empty()
+        && rule.avatar_hashes.is_empty()
+    {
+        ctx.say("A rule needs at least one decoration, nameplate, tag or avatar to match.")
+            .await?;
+        return Ok(());
+    }
+
+    let created = ctx
+        .data()
+        .database
+        .set_appearance_role_rule(ctx.guild_id().unwrap(), &rule)
+        .await?;
+
+    if created {
+        ctx.say(format!("Created the `{name}` appearance rule."))
+            .await?;
+    } else {
+        ctx.say(format!("Replaced the `{name}` appearance rule."))
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Delete an appearance rule, the roles it gave out are left alone.
+#[lumi::command(
+    rename = "appearance-rule-remove",
+    prefix_command,
+    slash_command,
+    category = "Moderation",
+    guild_only,
+    required_permissions = "MANAGE_ROLES"
+)]
+pub async fn appearance_rule_remove(
+    ctx: Context<'_>,
+    #[description = "The name of the rule."] name: String,
+) -> Result<(), Error> {
+    let data = ctx.data();
+    let removed = data
+        .database
+        .delete_appearance_role_rule(ctx.guild_id().unwrap(), &name)
+        .await?;
+
+    if let Some(rule_id) = removed {
+        data.appearance_roles.retain(|(id, _)| *id != rule_id);
+        ctx.say(format!("Removed the `{name}` appearance rule."))
+            .await?;
+    } else {
+        ctx.say(format!("There is no appearance rule called `{name}`."))
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// See the appearance rules of this server.
+#[lumi::command(
+    rename = "appearance-rules",
+    prefix_command,
+    slash_command,
+    category = "Moderation",
+    guild_only,
+    required_permissions = "MANAGE_ROLES"
+)]
+pub async fn appearance_rules(ctx: Context<'_>) -> Result<(), Error> {
+    let rules = ctx
+        .data()
+        .database
+        .get_appearance_role_rules(ctx.guild_id().unwrap())
+        .await?;
+
+    if rules.is_empty() {
+        ctx.say("There are no appearance rules.").await?;
+        return Ok(());
+    }
+
+    let pages = rules
+        .iter()
+        .map(|rule| {
+            let mut page = format!("**{}**\n", rule.name);
+            let roles = |roles: &[RoleId]| list(roles.iter().map(|id| format!("<@&{id}>")));
+            let code = |items: &[String]| list(items.iter().map(|i| format!("`{i}`")));
+
+            writeln!(page, "Gives: {}", roles(&rule.add_roles)).unwrap();
+            writeln!(page, "Takes: {}", roles(&rule.remove_roles)).unwrap();
+            writeln!(
+                page,
+                "Decorations: {}",
+                list(rule.decoration_skus.iter().map(|id| format!("`{id}`")))
+            )
+            .unwrap();
+            writeln!(page, "Nameplates: {}", code(&rule.nameplate_assets)).unwrap();
+            writeln!(page, "Tags: {}", code(&rule.guild_tags)).unwrap();
+            writeln!(page, "Avatars: {}", code(&rule.avatar_hashes)).unwrap();
+            if let Some(channel) = rule.announce_channel {
+                writeln!(page, "Announced in <#{channel}>").unwrap();
+            }
+            page
+        })
+        .collect::<Vec<_>>();
+
+    crate::utils::embed_pages(
+        ctx,
+        "Appearance rules",
+        &pages,
+        &format!("{} rules", rules.len()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 3] {
+    [
+        appearance_rule_set(),
+        appearance_rule_remove(),
+        appearance_rules(),
+    ]
+}
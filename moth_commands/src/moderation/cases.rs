@@ -0,0 +1,73 @@
+use std::fmt::Write;
+
+use crate::{Context, Error};
+use lumi::serenity_prelude::User;
+
+const CASES_PER_PAGE: usize = 10;
+
+/// See the kicks, bans and unbans of a user, and the prunes they carried out.
+#[lumi::command(
+    aliases("modlogs"),
+    prefix_command,
+    slash_command,
+    category = "Moderation",
+    guild_only,
+    required_permissions = "KICK_MEMBERS"
+)]
+pub async fn cases(
+    ctx: Context<'_>,
+    #[description = "The user to look up."] user: User,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let cases = ctx
+        .data()
+        .database
+        .get_moderation_cases(guild_id, user.id)
+        .await?;
+
+    if cases.is_empty() {
+        ctx.say(format!("{} has no moderation cases.", user.tag()))
+            .await?;
+        return Ok(());
+    }
+
+    let pages = cases
+        .chunks(CASES_PER_PAGE)
+        .map(|chunk| {
+            let mut page = String::new();
+            for case in chunk {
+                let moderator = case
+                    .moderator_id
+                    .map_or_else(|| String::from("Unknown"), |id| format!("<@{id}>"));
+                let prune = case.prune.map_or_else(String::new, |(days, count)| {
+                    format!(" of {count} members inactive for {days} days")
+                });
+                writeln!(
+                    page,
+                    "`#{}` **{}**{prune} <t:{}:d> by {moderator}\n{}",
+                    case.id,
+                    case.action.as_str(),
+                    case.created_at.timestamp(),
+                    case.reason.as_deref().unwrap_or("No reason given.")
+                )
+                .unwrap();
+            }
+            page
+        })
+        .collect::<Vec<_>>();
+
+    crate::utils::embed_pages(
+        ctx,
+        &format!("Cases for {}", user.tag()),
+        &pages,
+        &format!("{} cases • User ID: {}", cases.len(), user.id),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [cases()]
+}
@@ -0,0 +1,109 @@
+use std::fmt::Write;
+
+use crate::{Context, Error};
+
+const LEADERBOARD_SIZE: i64 = 100;
+const ENTRIES_PER_PAGE: usize = 15;
+
+/// See whose invites brought in the most members.
+#[lumi::command(
+    rename = "top-inviters",
+    prefix_command,
+    slash_command,
+    category = "Moderation",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn top_inviters(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+
+    let inviters = ctx
+        .data()
+        .database
+        .get_top_inviters(guild_id, LEADERBOARD_SIZE)
+        .await?;
+
+    if inviters.is_empty() {
+        ctx.say("I haven't seen anyone join through an invite yet.")
+            .await?;
+        return Ok(());
+    }
+
+    let pages = inviters
+        .chunks(ENTRIES_PER_PAGE)
+        .enumerate()
+        .map(|(page_index, chunk)| {
+            let mut page = String::new();
+            for (i, (user_id, joins)) in chunk.iter().enumerate() {
+                writeln!(
+                    page,
+                    "`{}.` <@{user_id}>: {joins}",
+                    page_index * ENTRIES_PER_PAGE + i + 1
+                )
+                .unwrap();
+            }
+            page
+        })
+        .collect::<Vec<_>>();
+
+    crate::utils::embed_pages(ctx, "Top inviters", &pages, "Members are counted once").await?;
+
+    Ok(())
+}
+
+/// See the members that joined through an invite.
+#[lumi::command(
+    rename = "invite-joins",
+    prefix_command,
+    slash_command,
+    category = "Moderation",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn invite_joins(
+    ctx: Context<'_>,
+    #[description = "The invite code or link."] invite: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let code = invite
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(&invite);
+
+    let joins = ctx.data().database.get_invite_joins(guild_id, code).await?;
+
+    if joins.is_empty() {
+        ctx.say(format!(
+            "Nobody has joined through `{code}` that I know of."
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let pages = joins
+        .chunks(ENTRIES_PER_PAGE)
+        .map(|chunk| {
+            let mut page = String::new();
+            for (user_id, joined_at) in chunk {
+                writeln!(page, "<@{user_id}> <t:{}:R>", joined_at.timestamp()).unwrap();
+            }
+            page
+        })
+        .collect::<Vec<_>>();
+
+    crate::utils::embed_pages(
+        ctx,
+        &format!("Joins through {code}"),
+        &pages,
+        &format!("{} joins", joins.len()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 2] {
+    [top_inviters(), invite_joins()]
+}
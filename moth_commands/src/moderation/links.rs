@@ -0,0 +1,134 @@
+use crate::{Context, Error};
+
+/// Takes the domain out of a link, or a domain on its own.
+fn parse_domain(input: &str) -> Option<String> {
+    let input = input.trim().to_lowercase();
+    let rest = input
+        .split_once("://")
+        .map_or(input.as_str(), |(_, rest)| rest);
+    let domain = rest.split(['/', '?', '#']).next()?;
+    let domain = domain.strip_prefix("www.").unwrap_or(domain);
+    let domain = domain.trim_end_matches('.');
+
+    domain.contains('.').then(|| domain.to_string())
+}
+
+/// Allow a domain that was wrongly treated as a scam link, along with its subdomains.
+#[lumi::command(
+    rename = "link-allow",
+    prefix_command,
+    slash_command,
+    category = "Moderation",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn link_allow(
+    ctx: Context<'_>,
+    #[description = "The domain or a link to it."] domain: String,
+) -> Result<(), Error> {
+    let Some(domain) = parse_domain(&domain) else {
+        ctx.say("That doesn't look like a domain.").await?;
+        return Ok(());
+    };
+
+    let added = ctx
+        .data()
+        .database
+        .insert_link_allowlist(ctx.guild_id().unwrap(), &domain, ctx.author().id)
+        .await?;
+
+    if added {
+        ctx.say(format!("Links to `{domain}` are now allowed."))
+            .await?;
+    } else {
+        ctx.say(format!("`{domain}` is already allowed.")).await?;
+    }
+
+    Ok(())
+}
+
+/// Stop allowing a domain, so links to it are checked again.
+#[lumi::command(
+    rename = "link-unallow",
+    aliases("link-disallow"),
+    prefix_command,
+    slash_command,
+    category = "Moderation",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn link_unallow(
+    ctx: Context<'_>,
+    #[description = "The domain or a link to it."] domain: String,
+) -> Result<(), Error> {
+    let Some(domain) = parse_domain(&domain) else {
+        ctx.say("That doesn't look like a domain.").await?;
+        return Ok(());
+    };
+
+    let removed = ctx
+        .data()
+        .database
+        .delete_link_allowlist(ctx.guild_id().unwrap(), &domain)
+        .await?;
+
+    if removed {
+        ctx.say(format!("Links to `{domain}` are checked again."))
+            .await?;
+    } else {
+        ctx.say(format!("`{domain}` wasn't allowed.")).await?;
+    }
+
+    Ok(())
+}
+
+/// See the domains that are allowed past the scam link checks.
+#[lumi::command(
+    rename = "link-allowlist",
+    prefix_command,
+    slash_command,
+    category = "Moderation",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn link_allowlist(ctx: Context<'_>) -> Result<(), Error> {
+    let allowlist = ctx
+        .data()
+        .database
+        .get_link_allowlist(ctx.guild_id().unwrap())
+        .await?;
+
+    if allowlist.is_empty() {
+        ctx.say("No domains are allowed.").await?;
+        return Ok(());
+    }
+
+    let mut domains = allowlist.iter().map(String::as_str).collect::<Vec<_>>();
+    domains.sort_unstable();
+
+    let pages = domains
+        .chunks(25)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|domain| format!("`{domain}`"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>();
+
+    crate::utils::embed_pages(
+        ctx,
+        "Allowed domains",
+        &pages,
+        &format!("{} domains", domains.len()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 3] {
+    [link_allow(), link_unallow(), link_allowlist()]
+}
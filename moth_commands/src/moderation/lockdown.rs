@@ -0,0 +1,384 @@
+use std::{collections::HashMap, fmt::Write, sync::LazyLock};
+
+use crate::{Context, Error};
+
+use ::serenity::http::HttpError;
+use lumi::serenity_prelude::{
+    Channel, ChannelId, ChannelType, CreateAttachment, EditChannel, GuildId, PermissionOverwrite,
+    PermissionOverwriteType, Permissions, RoleId,
+};
+use moth_events::helper::{get_permission_changes, overwrite_removal};
+use regex::Regex;
+use reqwest::StatusCode;
+
+/// The permissions denied to every locked role.
+const LOCKED: Permissions = Permissions::SEND_MESSAGES.union(Permissions::CONNECT);
+
+static TARGET_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<#(\d+)>|<@&(\d+)>|(\d{16,20})").unwrap());
+
+/// A channel targeted by a lockdown and its current overwrites.
+struct Target {
+    id: ChannelId,
+    name: String,
+    overwrites: Vec<PermissionOverwrite>,
+}
+
+/// Lock channels down, stopping @everyone and the given roles from talking.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    category = "Moderation - Lockdown",
+    required_permissions = "MANAGE_CHANNELS",
+    required_bot_permissions = "MANAGE_CHANNELS | MANAGE_ROLES",
+    guild_only
+)]
+pub async fn lockdown(
+    ctx: Context<'_>,
+    #[description = "Channels, categories and extra roles to lock, or \"all\" for every channel."]
+    #[rest]
+    targets: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+
+    let (channels, mut roles) = parse_targets(ctx, guild_id, &targets);
+    if channels.is_empty() {
+        ctx.say("I couldn't find any channels to lock down.")
+            .await?;
+        return Ok(());
+    }
+    // @everyone is always locked.
+    roles.insert(0, RoleId::new(guild_id.get()));
+
+    ctx.defer().await?;
+
+    let data = ctx.data();
+    let reason = format!("Lockdown by {}", ctx.author().tag());
+
+    let mut report = String::new();
+    let (mut locked, mut skipped) = (0, 0);
+    let mut failed = Vec::new();
+    for target in channels {
+        // don't snapshot a channel that's already locked, it'd restore to the locked state.
+        match data
+            .database
+            .insert_lockdown(guild_id, target.id, &target.overwrites)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                skipped += 1;
+                continue;
+            }
+            // without a snapshot it couldn't be unlocked, so it's left alone.
+            Err(e) => {
+                println!("Failed to snapshot {} for a lockdown: {e}", target.id);
+                failed.push(format!("#{}", target.name));
+                continue;
+            }
+        }
+
+        let new = locked_overwrites(&target.overwrites, &roles);
+        if target
+            .id
+            .edit(
+                ctx.http(),
+                EditChannel::new()
+                    .permissions(new.clone())
+                    .audit_log_reason(&reason),
+            )
+            .await
+            .is_err()
+        {
+            if let Err(e) = data.database.delete_lockdown(target.id).await {
+                println!(
+                    "Failed to delete the lockdown snapshot of {}: {e}",
+                    target.id
+                );
+            }
+            failed.push(format!("#{}", target.name));
+            continue;
+        }
+
+        locked += 1;
+        writeln!(report, "#{}:", target.name).unwrap();
+        report.push_str(&overwrite_changes(ctx, guild_id, &target.overwrites, &new).await);
+    }
+
+    let mut summary = format!("Locked down {locked} channels.");
+    if skipped > 0 {
+        write!(summary, " {skipped} were already locked down.").unwrap();
+    }
+    if !failed.is_empty() {
+        write!(summary, " Failed to lock {}.", failed.join(", ")).unwrap();
+    }
+
+    send_report(ctx, summary, &report).await
+}
+
+/// Restore channels to how they were before they were locked down.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    category = "Moderation - Lockdown",
+    required_permissions = "MANAGE_CHANNELS",
+    required_bot_permissions = "MANAGE_CHANNELS | MANAGE_ROLES",
+    guild_only
+)]
+pub async fn unlock(
+    ctx: Context<'_>,
+    #[description = "Channels or categories to unlock, every locked channel if not given."]
+    #[rest]
+    targets: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let data = ctx.data();
+
+    let mut lockdowns = data.database.get_lockdowns(guild_id).await?;
+    if let Some(targets) = &targets {
+        let (channels, _) = parse_targets(ctx, guild_id, targets);
+        lockdowns.retain(|id, _| channels.iter().any(|c| c.id == *id));
+    }
+
+    if lockdowns.is_empty() {
+        ctx.say("There is nothing locked down to unlock.").await?;
+        return Ok(());
+    }
+
+    ctx.defer().await?;
+
+    let current = {
+        let guild = ctx.guild().unwrap();
+        lockdowns
+            .keys()
+            .filter_map(|id| {
+                let channel = guild.channels.get(id)?;
+                Some((
+                    *id,
+                    (
+                        channel.base.name.to_string(),
+                        channel.permission_overwrites.to_vec(),
+                    ),
+                ))
+            })
+            .collect::<HashMap<_, _>>()
+    };
+
+    let reason = format!("Lockdown lifted by {}", ctx.author().tag());
+
+    let mut report = String::new();
+    let (mut unlocked, mut failed) = (0, 0);
+    for (channel_id, snapshot) in lockdowns {
+        let (name, overwrites) = match current.get(&channel_id) {
+            Some(current) => current.clone(),
+            // not being cached doesn't mean the channel is gone, so ask discord.
+            None => match ctx.http().get_channel(channel_id.widen()).await {
+                Ok(Channel::Guild(channel)) => (
+                    channel.base.name.to_string(),
+                    channel.permission_overwrites.to_vec(),
+                ),
+                // the channel was deleted while locked, so there is nothing to restore.
+                Err(::serenity::Error::Http(HttpError::UnsuccessfulRequest(response)))
+                    if response.status_code == StatusCode::NOT_FOUND =>
+                {
+                    if let Err(e) = data.database.delete_lockdown(channel_id).await {
+                        println!("Failed to remove the lockdown of {channel_id}: {e}");
+                    }
+                    continue;
+                }
+                _ => {
+                    failed += 1;
+                    continue;
+                }
+            },
+        };
+
+        if channel_id
+            .edit(
+                ctx.http(),
+                EditChannel::new()
+                    .permissions(snapshot.clone())
+                    .audit_log_reason(&reason),
+            )
+            .await
+            .is_err()
+        {
+            failed += 1;
+            continue;
+        }
+
+        // the channel is already restored, so carry on with the rest.
+        if let Err(e) = data.database.delete_lockdown(channel_id).await {
+            println!("Failed to remove the lockdown of {channel_id}: {e}");
+        }
+
+        unlocked += 1;
+        writeln!(report, "#{name}:").unwrap();
+        report.push_str(&overwrite_changes(ctx, guild_id, &overwrites, &snapshot).await);
+    }
+
+    let mut summary = format!("Unlocked {unlocked} channels.");
+    if failed > 0 {
+        write!(
+            summary,
+            " Failed to unlock {failed}, they are still locked down."
+        )
+        .unwrap();
+    }
+
+    send_report(ctx, summary, &report).await
+}
+
+/// Parses channel, category and role mentions or ids, categories include the channels under them.
+fn parse_targets(ctx: Context<'_>, guild_id: GuildId, targets: &str) -> (Vec<Target>, Vec<RoleId>) {
+    let Some(guild) = ctx.guild() else {
+        return (vec![], vec![]);
+    };
+
+    let target = |channel: &lumi::serenity_prelude::GuildChannel| Target {
+        id: channel.id,
+        name: channel.base.name.to_string(),
+        overwrites: channel.permission_overwrites.to_vec(),
+    };
+
+    let mut channel_ids = Vec::new();
+    let mut roles = Vec::new();
+    for captures in TARGET_REGEX.captures_iter(targets) {
+        if let Some(id) = captures.get(1).and_then(|m| m.as_str().parse().ok()) {
+            channel_ids.push(ChannelId::new(id));
+        } else if let Some(id) = captures.get(2).and_then(|m| m.as_str().parse().ok()) {
+            roles.push(RoleId::new(id));
+        } else if let Some(id) = captures.get(3).and_then(|m| m.as_str().parse::<u64>().ok()) {
+            // raw ids could be either.
+            if guild.roles.contains_key(&RoleId::new(id)) {
+                roles.push(RoleId::new(id));
+            } else {
+                channel_ids.push(ChannelId::new(id));
+            }
+        }
+    }
+
+    roles.retain(|r| guild.roles.contains_key(r) && r.get() != guild_id.get());
+    roles.sort_unstable();
+    roles.dedup();
+
+    if targets
+        .split_whitespace()
+        .any(|word| word.eq_ignore_ascii_case("all"))
+    {
+        return (guild.channels.iter().map(target).collect(), roles);
+    }
+
+    let mut channels: Vec<Target> = Vec::new();
+    for id in channel_ids {
+        let Some(channel) = guild.channels.get(&id) else {
+            continue;
+        };
+
+        let children = guild.channels.iter().filter(|c| {
+            channel.base.kind == ChannelType::Category && c.parent_id == Some(channel.id)
+        });
+
+        for channel in std::iter::once(channel).chain(children) {
+            if !channels.iter().any(|c| c.id == channel.id) {
+                channels.push(target(channel));
+            }
+        }
+    }
+
+    (channels, roles)
+}
+
+/// Denies [`LOCKED`] for each role, keeping every other overwrite as it is.
+fn locked_overwrites(
+    overwrites: &[PermissionOverwrite],
+    roles: &[RoleId],
+) -> Vec<PermissionOverwrite> {
+    let mut overwrites = overwrites.to_vec();
+
+    for role_id in roles {
+        let kind = PermissionOverwriteType::Role(*role_id);
+        if let Some(overwrite) = overwrites.iter_mut().find(|o| o.kind == kind) {
+            overwrite.allow.remove(LOCKED);
+            overwrite.deny.insert(LOCKED);
+        } else {
+            overwrites.push(PermissionOverwrite {
+                allow: Permissions::empty(),
+                deny: LOCKED,
+                kind,
+            });
+        }
+    }
+
+    overwrites
+}
+
+/// Describes how the overwrites of a channel changed using the same format as the channel logs.
+async fn overwrite_changes(
+    ctx: Context<'_>,
+    guild_id: GuildId,
+    old: &[PermissionOverwrite],
+    new: &[PermissionOverwrite],
+) -> String {
+    let mut changes = String::new();
+
+    for new_overwrite in new {
+        let (old_allow, old_deny) = old
+            .iter()
+            .find(|o| o.kind == new_overwrite.kind)
+            .map_or((Permissions::empty(), Permissions::empty()), |o| {
+                (o.allow, o.deny)
+            });
+
+        changes.push_str(
+            &get_permission_changes(
+                ctx.serenity_context(),
+                guild_id,
+                old_allow,
+                new_overwrite.allow,
+                old_deny,
+                new_overwrite.deny,
+                new_overwrite.kind,
+            )
+            .await,
+        );
+    }
+
+    for old_overwrite in old {
+        if !new.iter().any(|o| o.kind == old_overwrite.kind) {
+            changes.push_str(
+                &overwrite_removal(ctx.serenity_context(), guild_id, old_overwrite).await,
+            );
+        }
+    }
+
+    changes
+}
+
+async fn send_report(ctx: Context<'_>, summary: String, report: &str) -> Result<(), Error> {
+    if report.is_empty() {
+        ctx.say(summary).await?;
+        return Ok(());
+    }
+
+    let content = format!("{summary}\n```ansi\n{report}\n```");
+    let reply = if content.chars().count() > 2000 {
+        lumi::CreateReply::new()
+            .content(summary)
+            .attachment(CreateAttachment::bytes(
+                moth_ansi::strip(report).into_bytes(),
+                "changes.txt",
+            ))
+    } else {
+        lumi::CreateReply::new().content(content)
+    };
+
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 2] {
+    [lockdown(), unlock()]
+}
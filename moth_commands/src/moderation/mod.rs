@@ -8,6 +8,14 @@ use moth_events::handlers::messages::invites::INVITE;
 use serenity::all::MessageId;
 use small_fixed_array::FixedString;
 
+pub mod anti_nuke;
+pub mod appearance_roles;
+pub mod cases;
+pub mod invites;
+pub mod links;
+pub mod lockdown;
+pub mod snipe;
+
 /// Purge messages in a channel.
 #[lumi::command(
     rename = "purge-in",
@@ -432,6 +440,15 @@ fn has_permissions(ctx: &PrefixContext) -> (bool, bool) {
 }
 
 #[must_use]
-pub fn commands() -> [crate::Command; 2] {
+pub fn commands() -> Vec<crate::Command> {
     [purge(), purge_in()]
+        .into_iter()
+        .chain(anti_nuke::commands())
+        .chain(appearance_roles::commands())
+        .chain(cases::commands())
+        .chain(invites::commands())
+        .chain(links::commands())
+        .chain(lockdown::commands())
+        .chain(snipe::commands())
+        .collect()
 }
@@ -0,0 +1,107 @@
+use crate::{Context, Error};
+
+use lumi::serenity_prelude::{
+    self as serenity, Colour, CreateAllowedMentions, CreateEmbed, CreateEmbedAuthor,
+    CreateEmbedFooter, User,
+};
+use moth_core::data::database::snipes::SnipeKind;
+use moth_events::{
+    diff::{diff_words, render_markdown},
+    handlers::messages::snipe::{fresh_urls, get_snipes},
+};
+
+/// View recently deleted and edited messages in this channel.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    category = "Moderation - Snipe",
+    required_permissions = "MANAGE_MESSAGES",
+    guild_only
+)]
+pub async fn snipe(
+    ctx: Context<'_>,
+    #[description = "Only show messages from this user."] user: Option<User>,
+    #[description = "How many messages to step back, starting at 1."] index: Option<usize>,
+) -> Result<(), Error> {
+    let data = ctx.data();
+    let index = index.unwrap_or(1).max(1);
+
+    let snipes = get_snipes(&data, ctx.channel_id())
+        .await?
+        .into_iter()
+        .filter(|s| user.as_ref().is_none_or(|u| u.id == s.user_id))
+        .collect::<Vec<_>>();
+
+    let Some(snipe) = snipes.get(index - 1) else {
+        let msg = if snipes.is_empty() {
+            "There is nothing to snipe here.".to_string()
+        } else {
+            format!("There are only {} snipes to step through.", snipes.len())
+        };
+        ctx.say(msg).await?;
+        return Ok(());
+    };
+
+    let (title, colour, description) = match snipe.kind {
+        SnipeKind::Deleted => ("Deleted message", Colour::RED, snipe.content.clone()),
+        SnipeKind::Edited => {
+            let old = snipe.old_content.as_deref().unwrap_or("");
+            let changes = diff_words(old, &snipe.content);
+            ("Edited message", Colour::GOLD, render_markdown(&changes))
+        }
+    };
+
+    let mut embed = CreateEmbed::new()
+        .title(title)
+        .colour(colour)
+        .author(CreateEmbedAuthor::new(&snipe.username))
+        .description(moth_events::logging::truncate(&description, 4096))
+        .footer(CreateEmbedFooter::new(format!(
+            "{index}/{} • User ID: {}",
+            snipes.len(),
+            snipe.user_id
+        )));
+
+    if let Ok(timestamp) = serenity::Timestamp::from_unix_timestamp(snipe.created_at.timestamp()) {
+        embed = embed.timestamp(timestamp);
+    }
+
+    let urls = fresh_urls(ctx.serenity_context(), &data, snipe).await;
+    if !urls.is_empty() {
+        if let Some(image) = urls.iter().find(|u| is_image(u)) {
+            embed = embed.image(image);
+        }
+
+        embed = embed.field(
+            "Attachments",
+            moth_events::logging::field_value(&urls.join("\n")),
+            false,
+        );
+    }
+
+    let mentions = CreateAllowedMentions::new()
+        .all_users(false)
+        .everyone(false)
+        .all_roles(false);
+
+    ctx.send(
+        lumi::CreateReply::default()
+            .embed(embed)
+            .allowed_mentions(mentions),
+    )
+    .await?;
+
+    Ok(())
+}
+
+fn is_image(url: &str) -> bool {
+    let path = url.split('?').next().unwrap_or(url).to_lowercase();
+    [".png", ".jpg", ".jpeg", ".gif", ".webp"]
+        .iter()
+        .any(|ext| path.ends_with(ext))
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [snipe()]
+}
@@ -0,0 +1,141 @@
+use std::{fmt::Write, path::Path};
+
+use crate::{Context, Error};
+use lumi::serenity_prelude::{self as serenity, CreateAttachment, CreateEmbedFooter, User};
+use moth_core::data::database::appearances::Appearance;
+use moth_events::handlers::appearances::cdn_url;
+
+const APPEARANCES_PER_PAGE: usize = 15;
+
+/// Browse the past avatars, avatar decorations and nameplates of a user.
+#[lumi::command(
+    aliases("avatars"),
+    prefix_command,
+    slash_command,
+    category = "Utility",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn appearance(
+    ctx: Context<'_>,
+    #[description = "The user to look up."] user: User,
+    #[description = "The entry to view the image of, starting at 1."] index: Option<usize>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let appearances = ctx
+        .data()
+        .database
+        .get_appearances(user.id, guild_id)
+        .await?;
+
+    if appearances.is_empty() {
+        ctx.say(format!("I haven't seen any avatars for {}.", user.tag()))
+            .await?;
+        return Ok(());
+    }
+
+    let Some(index) = index else {
+        let pages = appearances
+            .chunks(APPEARANCES_PER_PAGE)
+            .enumerate()
+            .map(|(page_index, chunk)| {
+                let mut page = String::new();
+                for (i, entry) in chunk.iter().enumerate() {
+                    let removed = if entry.asset.is_none() {
+                        " removed"
+                    } else {
+                        ""
+                    };
+                    writeln!(
+                        page,
+                        "`{}.` <t:{}:d> **{}**{}",
+                        page_index * APPEARANCES_PER_PAGE + i + 1,
+                        entry.created_at.timestamp(),
+                        entry.kind.as_str(),
+                        removed
+                    )
+                    .unwrap();
+                }
+                page
+            })
+            .collect::<Vec<_>>();
+
+        crate::utils::embed_pages(
+            ctx,
+            &format!("{}'s appearance history", user.tag()),
+            &pages,
+            "Pass an index to view the image",
+        )
+        .await?;
+
+        return Ok(());
+    };
+
+    let Some(entry) = index.checked_sub(1).and_then(|i| appearances.get(i)) else {
+        ctx.say(format!(
+            "There are only {} entries to view.",
+            appearances.len()
+        ))
+        .await?;
+        return Ok(());
+    };
+
+    let mut embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{}'s {}",
+            user.tag(),
+            entry.kind.as_str().to_lowercase()
+        ))
+        .footer(CreateEmbedFooter::new(format!(
+            "{index}/{} • User ID: {}",
+            appearances.len(),
+            user.id
+        )));
+
+    if let Ok(timestamp) = serenity::Timestamp::from_unix_timestamp(entry.created_at.timestamp()) {
+        embed = embed.timestamp(timestamp);
+    }
+
+    if let Some(sku_id) = entry.sku_id {
+        embed = embed.field("SKU", sku_id.to_string(), true);
+    }
+
+    let Some(asset) = &entry.asset else {
+        embed = embed.description("Removed.");
+        ctx.send(lumi::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    embed = embed.field("Asset", format!("`{asset}`"), true);
+
+    let mut reply = lumi::CreateReply::default();
+    match local_copy(entry).await {
+        Some((filename, attachment)) => {
+            embed = embed.image(format!("attachment://{filename}"));
+            reply = reply.attachment(attachment);
+        }
+        // the cdn may no longer have it, but it's better than nothing.
+        None => {
+            embed = embed
+                .image(cdn_url(user.id, entry.guild_id, entry.kind, asset))
+                .description("No local copy was saved, this may no longer load.");
+        }
+    }
+
+    ctx.send(reply.embed(embed)).await?;
+
+    Ok(())
+}
+
+async fn local_copy(entry: &Appearance) -> Option<(String, CreateAttachment<'static>)> {
+    let path = Path::new(entry.file_path.as_ref()?);
+    let filename = path.file_name()?.to_string_lossy().into_owned();
+    let bytes = tokio::fs::read(path).await.ok()?;
+
+    Some((filename.clone(), CreateAttachment::bytes(bytes, filename)))
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [appearance()]
+}
@@ -1,9 +1,13 @@
+pub mod appearances;
 pub mod charinfo;
 pub mod expressions;
 pub mod guild;
+pub mod names;
 pub mod random;
+pub mod reaction_blocks;
 pub mod roles;
 pub mod users;
+pub mod voice;
 
 #[must_use]
 pub fn commands() -> Vec<crate::Command> {
@@ -12,9 +16,13 @@ pub fn commands() -> Vec<crate::Command> {
             .into_iter()
             .chain(random::commands())
             .chain(users::commands())
+            .chain(names::commands())
+            .chain(appearances::commands())
+            .chain(voice::commands())
             .chain(guild::commands())
             .chain(charinfo::commands())
             .chain(roles::commands())
+            .chain(reaction_blocks::commands())
             .collect()
     }
 }
@@ -0,0 +1,126 @@
+use std::{collections::HashSet, fmt::Write};
+
+use crate::{Context, Error};
+use lumi::serenity_prelude::User;
+use moth_core::data::database::names::NameEntry;
+
+const NAMES_PER_PAGE: usize = 15;
+
+/// See the past usernames, display names and nicknames of a user.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    category = "Utility",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn names(
+    ctx: Context<'_>,
+    #[description = "The user to look up."] user: User,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let entries = ctx.data().database.get_names(user.id, guild_id).await?;
+
+    if entries.is_empty() {
+        ctx.say(format!("I haven't seen any names for {}.", user.tag()))
+            .await?;
+        return Ok(());
+    }
+
+    let pages = entries
+        .chunks(NAMES_PER_PAGE)
+        .map(|chunk| {
+            let mut page = String::new();
+            for entry in chunk {
+                writeln!(
+                    page,
+                    "<t:{}:d> **{}**: {}",
+                    entry.created_at.timestamp(),
+                    entry.kind.as_str(),
+                    format_name(entry)
+                )
+                .unwrap();
+            }
+            page
+        })
+        .collect::<Vec<_>>();
+
+    crate::utils::embed_pages(
+        ctx,
+        &format!("{}'s name history", user.tag()),
+        &pages,
+        &format!("User ID: {}", user.id),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Find everyone that has ever used a name.
+#[lumi::command(
+    rename = "names-search",
+    aliases("name-search"),
+    prefix_command,
+    slash_command,
+    category = "Utility",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn names_search(
+    ctx: Context<'_>,
+    #[description = "The name to search for, ignoring case."]
+    #[rest]
+    name: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let entries = ctx.data().database.search_names(&name, guild_id).await?;
+
+    if entries.is_empty() {
+        ctx.say("Nobody has used that name as far as I know.")
+            .await?;
+        return Ok(());
+    }
+
+    let users = entries.iter().map(|e| e.user_id).collect::<HashSet<_>>();
+
+    let pages = entries
+        .chunks(NAMES_PER_PAGE)
+        .map(|chunk| {
+            let mut page = String::new();
+            for entry in chunk {
+                writeln!(
+                    page,
+                    "<t:{}:d> <@{}> ({}) as a {}",
+                    entry.created_at.timestamp(),
+                    entry.user_id,
+                    entry.user_id,
+                    entry.kind.as_str().to_lowercase()
+                )
+                .unwrap();
+            }
+            page
+        })
+        .collect::<Vec<_>>();
+
+    crate::utils::embed_pages(
+        ctx,
+        &format!("Users that have been named {}", name.replace('`', "'")),
+        &pages,
+        &format!("{} users", users.len()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+fn format_name(entry: &NameEntry) -> String {
+    match &entry.name {
+        Some(name) => format!("`{}`", name.replace('`', "'")),
+        None => String::from("*removed*"),
+    }
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 2] {
+    [names(), names_search()]
+}
@@ -0,0 +1,236 @@
+use std::fmt::Write;
+
+use crate::{Context, Error};
+use lumi::serenity_prelude::{GuildChannel, User};
+use moth_core::data::database::reaction_blocks::ReactionBlockTarget;
+
+/// Takes the name out of a custom emoji, or the unicode emoji as it is.
+fn parse_emoji(input: &str) -> Option<String> {
+    let input = input.trim();
+    let emoji = match input.strip_prefix('<').and_then(|e| e.strip_suffix('>')) {
+        // <:name:id> or <a:name:id>
+        Some(custom) => custom.split(':').nth(1)?,
+        None => input.trim_matches(':'),
+    };
+
+    (!emoji.is_empty() && !emoji.contains(char::is_whitespace)).then(|| emoji.to_string())
+}
+
+fn can_manage_messages(ctx: Context<'_>) -> bool {
+    let Some(guild) = ctx.guild() else {
+        return false;
+    };
+
+    guild
+        .members
+        .get(&ctx.author().id)
+        .is_some_and(|member| guild.member_permissions(member).manage_messages())
+}
+
+/// Stop an emoji from being reacted onto your messages, mods can do this for anyone.
+#[lumi::command(
+    rename = "reaction-block",
+    prefix_command,
+    slash_command,
+    category = "Utility",
+    guild_only
+)]
+pub async fn reaction_block(
+    ctx: Context<'_>,
+    #[description = "The emoji to block."] emoji: String,
+    #[description = "Whose messages to block it on, yourself if not given."] user: Option<User>,
+) -> Result<(), Error> {
+    let user = user.as_ref().unwrap_or_else(|| ctx.author());
+
+    if user.id != ctx.author().id && !can_manage_messages(ctx) {
+        ctx.say("You can only block reactions on your own messages.")
+            .await?;
+        return Ok(());
+    }
+
+    let Some(emoji) = parse_emoji(&emoji) else {
+        ctx.say("That doesn't look like an emoji.").await?;
+        return Ok(());
+    };
+
+    let added = ctx
+        .data()
+        .database
+        .insert_reaction_block(
+            ctx.guild_id().unwrap(),
+            ReactionBlockTarget::User(user.id),
+            &emoji,
+            ctx.author().id,
+        )
+        .await?;
+
+    if added {
+        ctx.say(format!(
+            "{emoji} will be removed from messages by {}.",
+            user.tag()
+        ))
+        .await?;
+    } else {
+        ctx.say(format!("{emoji} is already blocked for {}.", user.tag()))
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Stop an emoji from being reacted onto any message in a channel.
+#[lumi::command(
+    rename = "reaction-block-channel",
+    prefix_command,
+    slash_command,
+    category = "Utility",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn reaction_block_channel(
+    ctx: Context<'_>,
+    #[description = "The emoji to block."] emoji: String,
+    #[description = "The channel to block it in, this one if not given."] channel: Option<
+        GuildChannel,
+    >,
+) -> Result<(), Error> {
+    let Some(emoji) = parse_emoji(&emoji) else {
+        ctx.say("That doesn't look like an emoji.").await?;
+        return Ok(());
+    };
+
+    let channel_id = channel.map_or_else(|| ctx.channel_id(), |c| c.id.widen());
+
+    let added = ctx
+        .data()
+        .database
+        .insert_reaction_block(
+            ctx.guild_id().unwrap(),
+            ReactionBlockTarget::Channel(channel_id),
+            &emoji,
+            ctx.author().id,
+        )
+        .await?;
+
+    if added {
+        ctx.say(format!("{emoji} will be removed in <#{channel_id}>."))
+            .await?;
+    } else {
+        ctx.say(format!("{emoji} is already blocked in <#{channel_id}>."))
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Remove a reaction block by its id, see reaction-blocks for the ids.
+#[lumi::command(
+    rename = "reaction-unblock",
+    prefix_command,
+    slash_command,
+    category = "Utility",
+    guild_only
+)]
+pub async fn reaction_unblock(
+    ctx: Context<'_>,
+    #[description = "The id of the block."] id: i32,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let blocks = ctx.data().database.get_reaction_blocks(guild_id).await?;
+
+    let Some(block) = blocks.iter().find(|b| b.id == id) else {
+        ctx.say("There is no reaction block with that id.").await?;
+        return Ok(());
+    };
+
+    if block.target != ReactionBlockTarget::User(ctx.author().id) && !can_manage_messages(ctx) {
+        ctx.say("You can only remove reaction blocks on your own messages.")
+            .await?;
+        return Ok(());
+    }
+
+    let removed = ctx
+        .data()
+        .database
+        .delete_reaction_block(guild_id, id)
+        .await?;
+
+    if removed {
+        ctx.say(format!("{} can be reacted again.", block.emoji))
+            .await?;
+    } else {
+        ctx.say("There is no reaction block with that id.").await?;
+    }
+
+    Ok(())
+}
+
+/// See the emoji that are removed from a member's messages or in channels.
+#[lumi::command(
+    rename = "reaction-blocks",
+    prefix_command,
+    slash_command,
+    category = "Utility",
+    guild_only
+)]
+pub async fn reaction_blocks(
+    ctx: Context<'_>,
+    #[description = "Only show the blocks on this user's messages."] user: Option<User>,
+) -> Result<(), Error> {
+    let blocks = ctx
+        .data()
+        .database
+        .get_reaction_blocks(ctx.guild_id().unwrap())
+        .await?;
+
+    let blocks = blocks
+        .iter()
+        .filter(|b| {
+            user.as_ref()
+                .is_none_or(|u| b.target == ReactionBlockTarget::User(u.id))
+        })
+        .collect::<Vec<_>>();
+
+    if blocks.is_empty() {
+        ctx.say("No reactions are blocked.").await?;
+        return Ok(());
+    }
+
+    let pages = blocks
+        .chunks(15)
+        .map(|chunk| {
+            let mut page = String::new();
+            for block in chunk {
+                let target = match block.target {
+                    ReactionBlockTarget::User(user_id) => format!("messages by <@{user_id}>"),
+                    ReactionBlockTarget::Channel(channel_id) => format!("in <#{channel_id}>"),
+                };
+                writeln!(page, "`{}` {} {target}", block.id, block.emoji).unwrap();
+            }
+            page
+        })
+        .collect::<Vec<_>>();
+
+    crate::utils::embed_pages(
+        ctx,
+        "Blocked reactions",
+        &pages,
+        &format!(
+            "{} blocks • Use reaction-unblock to remove one",
+            blocks.len()
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 4] {
+    [
+        reaction_block(),
+        reaction_block_channel(),
+        reaction_unblock(),
+        reaction_blocks(),
+    ]
+}
@@ -46,7 +46,47 @@ pub async fn statuses(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
-/// See what games people are playing!
+#[derive(Debug, Clone, Copy, Default, PartialEq, lumi::ChoiceParameter)]
+pub enum PlayingMode {
+    /// What is being played right now.
+    #[default]
+    Now,
+    /// The games with the highest peak player count in the window.
+    Top,
+    /// The peak player count of a single game over the window.
+    History,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, lumi::ChoiceParameter)]
+pub enum ActivityWindow {
+    Day,
+    #[default]
+    Week,
+    Month,
+    Year,
+}
+
+impl ActivityWindow {
+    fn duration(self) -> chrono::Duration {
+        match self {
+            ActivityWindow::Day => chrono::Duration::days(1),
+            ActivityWindow::Week => chrono::Duration::weeks(1),
+            ActivityWindow::Month => chrono::Duration::days(30),
+            ActivityWindow::Year => chrono::Duration::days(365),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            ActivityWindow::Day => "today",
+            ActivityWindow::Week => "this week",
+            ActivityWindow::Month => "this month",
+            ActivityWindow::Year => "this year",
+        }
+    }
+}
+
+/// See what games people are playing, or have been playing!
 #[lumi::command(
     slash_command,
     prefix_command,
@@ -54,7 +94,32 @@ pub async fn statuses(ctx: Context<'_>) -> Result<(), Error> {
     guild_only,
     user_cooldown = 15
 )]
-pub async fn playing(ctx: Context<'_>) -> Result<(), Error> {
+pub async fn playing(
+    ctx: Context<'_>,
+    #[description = "Games now, the top games or the history of one game."] mode: Option<
+        PlayingMode,
+    >,
+    #[description = "How far back to look, a week if not given."] window: Option<ActivityWindow>,
+    #[description = "The game to view the history of."]
+    #[rest]
+    game: Option<String>,
+) -> Result<(), Error> {
+    let window = window.unwrap_or_default();
+
+    match mode.unwrap_or_default() {
+        PlayingMode::Now => playing_now(ctx).await,
+        PlayingMode::Top => playing_top(ctx, window).await,
+        PlayingMode::History => {
+            let Some(game) = game else {
+                ctx.say("Give me a game to view the history of.").await?;
+                return Ok(());
+            };
+            playing_history(ctx, window, &game).await
+        }
+    }
+}
+
+async fn playing_now(ctx: Context<'_>) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap();
 
     let cache = &ctx.cache();
@@ -96,11 +161,117 @@ pub async fn playing(ctx: Context<'_>) -> Result<(), Error> {
         .map(<[(&str, u32)]>::to_vec)
         .collect();
 
-    crate::utils::presence_builder(ctx, pages, total_members, total_games).await?;
+    if pages.is_empty() {
+        ctx.say("Nobody is playing anything right now.").await?;
+        return Ok(());
+    }
+
+    let footer = format!("{total_members} members are playing {total_games} games right now.");
+    crate::utils::presence_builder(ctx, "Top games being played right now:", pages, &footer)
+        .await?;
+
+    Ok(())
+}
+
+async fn playing_top(ctx: Context<'_>, window: ActivityWindow) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let since = chrono::Utc::now() - window.duration();
+
+    let activities = ctx
+        .data()
+        .database
+        .get_top_activities(guild_id, since)
+        .await?;
+
+    if activities.is_empty() {
+        ctx.say(format!("Nobody has played anything {}.", window.describe()))
+            .await?;
+        return Ok(());
+    }
+
+    let entries = activities
+        .iter()
+        .map(|(name, peak)| (name.as_str(), *peak as u32))
+        .collect::<Vec<_>>();
+    let pages = entries.chunks(15).map(<[(&str, u32)]>::to_vec).collect();
+
+    let footer = format!(
+        "{} games were played {}, counts are the peak players.",
+        activities.len(),
+        window.describe()
+    );
+    crate::utils::presence_builder(
+        ctx,
+        &format!("Top games {}:", window.describe()),
+        pages,
+        &footer,
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn playing_history(
+    ctx: Context<'_>,
+    window: ActivityWindow,
+    game: &str,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let since = chrono::Utc::now() - window.duration();
+    // daily buckets would only leave one or two entries for a day.
+    let hourly = window == ActivityWindow::Day;
+
+    let history = ctx
+        .data()
+        .database
+        .get_activity_history(guild_id, game, since, hourly)
+        .await?;
+
+    if history.is_empty() {
+        ctx.say(format!("Nobody has played {game} {}.", window.describe()))
+            .await?;
+        return Ok(());
+    }
+
+    let format = if hourly { "%H:00" } else { "%Y-%m-%d" };
+    let labels = history
+        .iter()
+        .map(|(bucket, _)| bucket.format(format).to_string())
+        .collect::<Vec<_>>();
+    let entries = labels
+        .iter()
+        .zip(&history)
+        .map(|(label, (_, peak))| (label.as_str(), *peak as u32))
+        .collect::<Vec<_>>();
+    let pages = entries.chunks(15).map(<[(&str, u32)]>::to_vec).collect();
+
+    let peak = history.iter().map(|(_, peak)| *peak).max().unwrap_or(0);
+    let footer = format!(
+        "Peaked at {peak} players {}, times are in UTC.",
+        window.describe()
+    );
+    crate::utils::presence_builder(
+        ctx,
+        &format!("Players of {game} {}:", window.describe()),
+        pages,
+        &footer,
+    )
+    .await?;
 
     Ok(())
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, lumi::ChoiceParameter)]
+pub enum DmActivityView {
+    /// The current flag and how often it was announced.
+    #[default]
+    Summary,
+    /// Every flag that was observed.
+    Timeline,
+}
+
+const DM_ACTIVITY_EVENTS_PER_PAGE: usize = 10;
+
 /// See information about a users dm activity flag.
 #[lumi::command(
     rename = "dm-activity-check",
@@ -110,11 +281,27 @@ pub async fn playing(ctx: Context<'_>) -> Result<(), Error> {
     guild_only,
     required_permissions = "MANAGE_MESSAGES"
 )]
-pub async fn dm_activity_check(ctx: Context<'_>, user: User) -> Result<(), Error> {
-    if ctx.guild_id().unwrap() != 98226572468690944 {
+pub async fn dm_activity_check(
+    ctx: Context<'_>,
+    user: User,
+    view: Option<DmActivityView>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    if !ctx
+        .data()
+        .config
+        .read()
+        .dm_activity
+        .guilds
+        .contains_key(&guild_id)
+    {
         return Ok(());
     }
 
+    if view.unwrap_or_default() == DmActivityView::Timeline {
+        return dm_activity_timeline(ctx, &user).await;
+    }
+
     let author =
         serenity::CreateEmbedAuthor::new(format!("{}'s unusual dm activity info", user.tag()))
             .icon_url(user.avatar_url().unwrap_or_default());
@@ -140,7 +327,7 @@ pub async fn dm_activity_check(ctx: Context<'_>, user: User) -> Result<(), Error
             .field("Count", result.count.to_string(), true);
     }
 
-    if let Ok(member) = ctx.guild_id().unwrap().member(ctx, user.id).await {
+    if let Ok(member) = guild_id.member(ctx, user.id).await {
         let until = if let Some(activity) = member.unusual_dm_activity_until {
             format!("<t:{}>", activity.unix_timestamp())
         } else {
@@ -154,6 +341,62 @@ pub async fn dm_activity_check(ctx: Context<'_>, user: User) -> Result<(), Error
     Ok(())
 }
 
+async fn dm_activity_timeline(ctx: Context<'_>, user: &User) -> Result<(), Error> {
+    use std::fmt::Write;
+
+    let events = ctx
+        .data()
+        .database
+        .get_dm_activity_events(ctx.guild_id().unwrap(), user.id)
+        .await?;
+
+    if events.is_empty() {
+        ctx.say(format!(
+            "{} has never been flagged for unusual dm activity.",
+            user.tag()
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let pages = events
+        .rchunks(DM_ACTIVITY_EVENTS_PER_PAGE)
+        .map(|chunk| {
+            let mut page = String::new();
+            for event in chunk.iter().rev() {
+                let kind = if event.announced {
+                    "Flagged"
+                } else {
+                    "Extended"
+                };
+                write!(
+                    page,
+                    "<t:{}:f> **{kind}** until <t:{}:f>",
+                    event.observed_at.timestamp(),
+                    event.until.timestamp()
+                )
+                .unwrap();
+                if event.escalated {
+                    page.push_str(" (escalated)");
+                }
+                page.push('\n');
+            }
+            page
+        })
+        .collect::<Vec<_>>();
+
+    let flags = events.iter().filter(|e| e.announced).count();
+    crate::utils::embed_pages(
+        ctx,
+        &format!("{}'s unusual dm activity timeline", user.tag()),
+        &pages,
+        &format!("{flags} flags • User ID: {}", user.id),
+    )
+    .await?;
+
+    Ok(())
+}
+
 /// Display some details from the member object.
 #[lumi::command(
     prefix_command,
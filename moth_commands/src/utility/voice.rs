@@ -0,0 +1,277 @@
+use std::fmt::Write;
+
+use crate::{Context, Error};
+use lumi::serenity_prelude::{self as serenity, GuildChannel, User};
+
+const LEADERBOARD_SIZE: i64 = 100;
+const ENTRIES_PER_PAGE: usize = 15;
+const STATUS_HISTORY_SIZE: i64 = 100;
+
+/// See how long a member has spent in voice channels.
+#[lumi::command(
+    rename = "voice-time",
+    aliases("vc-time"),
+    prefix_command,
+    slash_command,
+    category = "Utility",
+    guild_only
+)]
+pub async fn voice_time(
+    ctx: Context<'_>,
+    #[description = "The user to look up, yourself if not given."] user: Option<User>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let user = user.as_ref().unwrap_or_else(|| ctx.author());
+
+    let channels = ctx
+        .data()
+        .database
+        .get_voice_time(guild_id, user.id)
+        .await?;
+
+    if channels.is_empty() {
+        ctx.say(format!("{} hasn't been in voice yet.", user.tag()))
+            .await?;
+        return Ok(());
+    }
+
+    let seconds = channels.iter().map(|c| c.seconds).sum::<i64>();
+    let muted = channels.iter().map(|c| c.muted_seconds).sum::<i64>();
+    let deafened = channels.iter().map(|c| c.deafened_seconds).sum::<i64>();
+    let sessions = channels.iter().map(|c| c.sessions).sum::<i64>();
+
+    let mut top_channels = String::new();
+    for channel in channels.iter().take(10) {
+        writeln!(
+            top_channels,
+            "<#{}>: {}",
+            channel.channel_id,
+            format_seconds(channel.seconds)
+        )
+        .unwrap();
+    }
+
+    let embed = serenity::CreateEmbed::default()
+        .author(
+            serenity::CreateEmbedAuthor::new(format!("{}'s voice time", user.tag()))
+                .icon_url(user.face()),
+        )
+        .field("Total", format_seconds(seconds), true)
+        .field("Muted", format_seconds(muted), true)
+        .field("Deafened", format_seconds(deafened), true)
+        .field("Sessions", sessions.to_string(), true)
+        .field("Top channels", top_channels, false);
+
+    ctx.send(lumi::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// See who has spent the most time in voice, in the whole server or one channel.
+#[lumi::command(
+    rename = "voice-leaderboard",
+    aliases("vc-leaderboard", "vc-lb"),
+    prefix_command,
+    slash_command,
+    category = "Utility",
+    guild_only,
+    user_cooldown = 15
+)]
+pub async fn voice_leaderboard(
+    ctx: Context<'_>,
+    #[description = "Only count time in this channel."] channel: Option<GuildChannel>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+
+    let leaderboard = ctx
+        .data()
+        .database
+        .get_voice_leaderboard(guild_id, channel.as_ref().map(|c| c.id), LEADERBOARD_SIZE)
+        .await?;
+
+    if leaderboard.is_empty() {
+        ctx.say("Nobody has been in voice yet.").await?;
+        return Ok(());
+    }
+
+    let pages = leaderboard
+        .chunks(ENTRIES_PER_PAGE)
+        .enumerate()
+        .map(|(page_index, chunk)| {
+            let mut page = String::new();
+            for (i, (user_id, seconds)) in chunk.iter().enumerate() {
+                writeln!(
+                    page,
+                    "`{}.` <@{user_id}>: {}",
+                    page_index * ENTRIES_PER_PAGE + i + 1,
+                    format_seconds(*seconds)
+                )
+                .unwrap();
+            }
+            page
+        })
+        .collect::<Vec<_>>();
+
+    let title = match &channel {
+        Some(channel) => format!("Voice leaderboard for #{}", channel.base.name),
+        None => String::from("Voice leaderboard"),
+    };
+
+    crate::utils::embed_pages(ctx, &title, &pages, "Open sessions count up to now").await?;
+
+    Ok(())
+}
+
+/// See who is currently in voice and since when.
+#[lumi::command(
+    rename = "voice-sessions",
+    aliases("vc-sessions"),
+    prefix_command,
+    slash_command,
+    category = "Utility",
+    guild_only
+)]
+pub async fn voice_sessions(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+
+    let sessions = ctx
+        .data()
+        .database
+        .get_open_voice_sessions(guild_id)
+        .await?;
+
+    if sessions.is_empty() {
+        ctx.say("Nobody is in voice right now.").await?;
+        return Ok(());
+    }
+
+    let pages = sessions
+        .chunks(ENTRIES_PER_PAGE)
+        .map(|chunk| {
+            let mut page = String::new();
+            for session in chunk {
+                writeln!(
+                    page,
+                    "<@{}> in <#{}> since <t:{}:R>",
+                    session.user_id,
+                    session.channel_id,
+                    session.started_at.timestamp()
+                )
+                .unwrap();
+            }
+            page
+        })
+        .collect::<Vec<_>>();
+
+    crate::utils::embed_pages(
+        ctx,
+        "Open voice sessions",
+        &pages,
+        &format!("{} members in voice", sessions.len()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// See who set the statuses of a voice channel.
+#[lumi::command(
+    rename = "vc-status-history",
+    aliases("voice-status-history"),
+    prefix_command,
+    slash_command,
+    category = "Utility",
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn vc_status_history(
+    ctx: Context<'_>,
+    #[description = "The voice channel, this one if not given."] channel: Option<GuildChannel>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let (channel_id, name) = match channel {
+        Some(channel) => (channel.id, channel.base.name.to_string()),
+        None => {
+            let channel_id = ctx.channel_id().expect_channel();
+            let name = ctx
+                .guild()
+                .and_then(|g| g.channels.get(&channel_id).map(|c| c.base.name.to_string()))
+                .unwrap_or_else(|| channel_id.to_string());
+            (channel_id, name)
+        }
+    };
+
+    let history = ctx
+        .data()
+        .database
+        .get_vc_status_history(guild_id, channel_id, STATUS_HISTORY_SIZE)
+        .await?;
+
+    if history.is_empty() {
+        ctx.say(format!("<#{channel_id}> has no recorded statuses."))
+            .await?;
+        return Ok(());
+    }
+
+    let pages = history
+        .chunks(ENTRIES_PER_PAGE)
+        .map(|chunk| {
+            let mut page = String::new();
+            for change in chunk {
+                let user = change
+                    .user_id
+                    .map_or_else(|| String::from("Unknown"), |id| format!("<@{id}>"));
+                let status = change.status.as_deref().map_or_else(
+                    || String::from("*removed*"),
+                    |s| format!("`{}`", s.replace('`', "'")),
+                );
+                let flag = if change.cleared {
+                    " (blacklisted, removed)"
+                } else if change.blacklisted {
+                    " (blacklisted)"
+                } else {
+                    ""
+                };
+
+                writeln!(
+                    page,
+                    "<t:{}:f> {user}: {status}{flag}",
+                    change.changed_at.timestamp()
+                )
+                .unwrap();
+            }
+            page
+        })
+        .collect::<Vec<_>>();
+
+    crate::utils::embed_pages(
+        ctx,
+        &format!("Status history of #{name}"),
+        &pages,
+        &format!("Last {} status changes", history.len()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+fn format_seconds(seconds: i64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m {}s", seconds % 60)
+    }
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 4] {
+    [
+        voice_time(),
+        voice_leaderboard(),
+        voice_sessions(),
+        vc_status_history(),
+    ]
+}
@@ -9,9 +9,32 @@ use lumi::{Context, CreateReply};
 
 pub async fn presence_builder<U: Send + Sync + 'static, E>(
     ctx: Context<'_, U, E>,
+    title: &str,
     pages: Vec<Vec<(&str, u32)>>,
-    total_members: usize,
-    total_games: usize,
+    footer: &str,
+) -> Result<(), serenity::Error> {
+    let pages = pages
+        .iter()
+        .map(|page| format_pages(page))
+        .collect::<Vec<_>>();
+
+    embed_pages(ctx, title, &pages, footer).await
+}
+
+fn format_pages(pages: &[(&str, u32)]) -> String {
+    pages
+        .iter()
+        .map(|(name, count)| format!("{name}: {count}"))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Sends pre-rendered embed descriptions with buttons to step through them.
+pub async fn embed_pages<U: Send + Sync + 'static, E>(
+    ctx: Context<'_, U, E>,
+    title: &str,
+    pages: &[String],
+    footer: &str,
 ) -> Result<(), serenity::Error> {
     let ctx_id = ctx.id();
     let prev_button_id = format!("{ctx_id}prev");
@@ -20,21 +43,31 @@ pub async fn presence_builder<U: Send + Sync + 'static, E>(
     let sctx = ctx.serenity_context();
 
     let mut current_page = 0;
-    let footer = format!("{total_members} members are playing {total_games} games right now.");
+
+    // there is nothing to step through with a single page.
+    let components = if pages.len() > 1 {
+        vec![CreateComponent::ActionRow(CreateActionRow::Buttons(
+            Cow::Owned(vec![
+                serenity::CreateButton::new(&prev_button_id).emoji('◀'),
+                serenity::CreateButton::new(&next_button_id).emoji('▶'),
+            ]),
+        ))]
+    } else {
+        vec![]
+    };
 
     let msg = ctx
         .send(
             lumi::CreateReply::default()
-                .embed(create_presence_embed(current_page, &footer, &pages))
-                .components(vec![CreateComponent::ActionRow(CreateActionRow::Buttons(
-                    Cow::Owned(vec![
-                        serenity::CreateButton::new(&prev_button_id).emoji('◀'),
-                        serenity::CreateButton::new(&next_button_id).emoji('▶'),
-                    ]),
-                ))]),
+                .embed(create_page_embed(current_page, title, footer, pages))
+                .components(components),
         )
         .await?;
 
+    if pages.len() <= 1 {
+        return Ok(());
+    }
+
     while let Some(press) = ComponentInteractionCollector::new(ctx.serenity_context())
         .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
         .timeout(std::time::Duration::from_secs(180))
@@ -55,8 +88,12 @@ pub async fn presence_builder<U: Send + Sync + 'static, E>(
             .create_response(
                 &sctx.http,
                 CreateInteractionResponse::UpdateMessage(
-                    serenity::CreateInteractionResponseMessage::default()
-                        .embed(create_presence_embed(current_page, &footer, &pages)),
+                    serenity::CreateInteractionResponseMessage::default().embed(create_page_embed(
+                        current_page,
+                        title,
+                        footer,
+                        pages,
+                    )),
                 ),
             )
             .await?;
@@ -64,7 +101,7 @@ pub async fn presence_builder<U: Send + Sync + 'static, E>(
     msg.edit(
         ctx,
         CreateReply::default()
-            .embed(create_presence_embed(current_page, &footer, &pages))
+            .embed(create_page_embed(current_page, title, footer, pages))
             .components(vec![]),
     )
     .await?;
@@ -72,22 +109,18 @@ pub async fn presence_builder<U: Send + Sync + 'static, E>(
     Ok(())
 }
 
-// This is split to make the code more pleasant
-fn create_presence_embed<'a>(
+fn create_page_embed<'a>(
     current_page: usize,
+    title: &str,
     footer_text: &str,
-    pages: &[Vec<(&str, u32)>],
+    pages: &[String],
 ) -> serenity::CreateEmbed<'a> {
     serenity::CreateEmbed::default()
-        .title("Top games being played right now:")
-        .description(format_pages(&pages[current_page]))
-        .footer(CreateEmbedFooter::new(footer_text.to_string()))
-}
-
-fn format_pages(pages: &[(&str, u32)]) -> String {
-    pages
-        .iter()
-        .map(|(name, count)| format!("{name}: {count}"))
-        .collect::<Vec<String>>()
-        .join("\n")
+        .title(title.to_string())
+        .description(pages.get(current_page).cloned().unwrap_or_default())
+        .footer(CreateEmbedFooter::new(format!(
+            "Page {}/{} • {footer_text}",
+            current_page + 1,
+            pages.len().max(1)
+        )))
 }
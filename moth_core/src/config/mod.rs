@@ -1,13 +1,19 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-use lumi::serenity_prelude::{ChannelId, GuildId};
+use lumi::serenity_prelude::{ChannelId, GenericChannelId, GuildId, RoleId, UserId};
 
 mod serialize;
 use serenity::all::{ThreadId, WebhookId};
-use serialize::{read_words_from_file, regex_patterns};
+use serialize::{read_domains_from_file, read_words_from_file, regex_patterns};
+
+/// The longest timeout discord allows, 28 days.
+pub const MAX_TIMEOUT_SECONDS: u64 = 28 * 24 * 60 * 60;
 
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct MothConfig {
@@ -15,6 +21,39 @@ pub struct MothConfig {
     pub events: Events,
     // Tracking for osu!game, harshly hardcoded.
     pub vcstatus: VCStatus,
+    // Mirroring of events to discord channels.
+    #[serde(default)]
+    pub logging: Logging,
+    // Thresholds and actions for mass message deletion.
+    #[serde(default)]
+    pub anti_delete: AntiDelete,
+    // Raid detection signals and the lockdown it can start.
+    #[serde(default)]
+    pub raid: Raid,
+    // Sampling of presences for activity history.
+    #[serde(default)]
+    pub activity: Activity,
+    // Alerts for risky role and overwrite changes.
+    #[serde(default)]
+    pub permission_alerts: PermissionAlerts,
+    // Role stripping for staff performing destructive actions in bulk.
+    #[serde(default)]
+    pub anti_nuke: AntiNuke,
+    // Moderation of invites to other servers.
+    #[serde(default)]
+    pub invites: Invites,
+    // Escalation of members repeatedly flagged for unusual dm activity.
+    #[serde(default = "DmActivityEscalation::legacy")]
+    pub dm_activity: DmActivityEscalation,
+    // Detection of scam and phishing links.
+    #[serde(default)]
+    pub scam_links: ScamLinks,
+    // Detection of the same message being posted across many channels.
+    #[serde(default)]
+    pub cross_channel_spam: CrossChannelSpam,
+    // Limits on how many members and roles can be mentioned at once.
+    #[serde(default)]
+    pub mention_flood: MentionFlood,
 }
 
 impl MothConfig {
@@ -23,6 +62,17 @@ impl MothConfig {
         MothConfig {
             events: Events::default(),
             vcstatus: VCStatus::default(),
+            logging: Logging::default(),
+            anti_delete: AntiDelete::default(),
+            raid: Raid::default(),
+            activity: Activity::default(),
+            permission_alerts: PermissionAlerts::default(),
+            anti_nuke: AntiNuke::default(),
+            invites: Invites::default(),
+            dm_activity: DmActivityEscalation::default(),
+            scam_links: ScamLinks::default(),
+            cross_channel_spam: CrossChannelSpam::default(),
+            mention_flood: MentionFlood::default(),
         }
     }
 
@@ -56,6 +106,9 @@ impl MothConfig {
                 // Set value of unconfigurable properties.
                 config.events.badlist = read_words_from_file("config/lists/badwords.txt");
                 config.events.fixlist = read_words_from_file("config/lists/fixwords.txt");
+                config.scam_links.blocklist =
+                    Arc::new(read_domains_from_file("config/lists/scam_domains.txt"));
+                config.anti_delete.validate();
 
                 config
             } else {
@@ -81,6 +134,12 @@ pub struct VCStatus {
     #[serde(with = "regex_patterns")]
     pub regex: Option<Vec<Regex>>,
     pub guilds: Option<Vec<GuildId>>,
+    /// Removes statuses that match the blacklist.
+    #[serde(default)]
+    pub clear_blacklisted: bool,
+    /// Sent to whoever set a blacklisted status, `{channel}` is replaced with the channel.
+    #[serde(default)]
+    pub warn_message: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
@@ -94,4 +153,443 @@ pub struct Events {
     #[serde(skip)]
     pub fixlist: HashSet<String>,
     pub guild_name_override: Option<HashMap<GuildId, String>>,
+    /// Channel that attachments of sniped messages are reuploaded to so they don't expire.
+    pub snipe_archive_channel: Option<GenericChannelId>,
+}
+
+/// Where a logged event should be sent.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum LogTarget {
+    Channel(GenericChannelId),
+    /// Webhook id, optional thread and the webhook token.
+    Webhook(WebhookId, Option<ThreadId>, String),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LogEvent {
+    MessageEdit,
+    MessageDelete,
+    MemberJoin,
+    MemberLeave,
+    MemberUpdate,
+    MemberRoles,
+    RoleCreate,
+    RoleUpdate,
+    RoleDelete,
+    ChannelCreate,
+    ChannelUpdate,
+    ChannelDelete,
+    Voice,
+    AntiDelete,
+    Raid,
+    AntiNuke,
+    Spam,
+    ReactionClear,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct Logging {
+    /// Per guild routing of each event type.
+    pub guilds: HashMap<GuildId, HashMap<LogEvent, LogTarget>>,
+}
+
+impl Logging {
+    #[must_use]
+    pub fn target(&self, guild_id: GuildId, event: LogEvent) -> Option<&LogTarget> {
+        self.guilds.get(&guild_id)?.get(&event)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AntiDelete {
+    /// Deletes across all guilds per cycle before checks are skipped, protects the http fetches.
+    pub max_deletes_per_cycle: usize,
+    pub guilds: HashMap<GuildId, AntiDeleteGuild>,
+}
+
+impl Default for AntiDelete {
+    fn default() -> Self {
+        Self {
+            max_deletes_per_cycle: 250,
+            guilds: HashMap::new(),
+        }
+    }
+}
+
+impl AntiDelete {
+    /// The settings for a guild, falling back to the defaults.
+    #[must_use]
+    pub fn guild(&self, guild_id: GuildId) -> AntiDeleteGuild {
+        self.guilds.get(&guild_id).cloned().unwrap_or_default()
+    }
+
+    /// Resets the heat of guilds whose `min_heat` is above their `max_heat`, as deletions would
+    /// never be checked, and caps timeouts at what discord allows.
+    pub fn validate(&mut self) {
+        for (guild_id, settings) in &mut self.guilds {
+            if settings.min_heat > settings.max_heat {
+                eprintln!(
+                    "Error: anti_delete min_heat ({}) is above max_heat ({}) for {guild_id}. \
+                     Using the default heat.",
+                    settings.min_heat, settings.max_heat
+                );
+
+                let default = AntiDeleteGuild::default();
+                settings.min_heat = default.min_heat;
+                settings.max_heat = default.max_heat;
+            }
+
+            if let Some(timeout) = settings.timeout
+                && timeout > MAX_TIMEOUT_SECONDS
+            {
+                eprintln!(
+                    "Error: anti_delete timeout ({timeout}) for {guild_id} is longer than discord \
+                     allows. Using 28 days."
+                );
+                settings.timeout = Some(MAX_TIMEOUT_SECONDS);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AntiDeleteGuild {
+    /// The heat at which deletions start being checked.
+    pub min_heat: u16,
+    /// The heat ceiling, higher values take longer to decay.
+    pub max_heat: u16,
+    /// Seconds between incidents for the same user.
+    pub incident_cooldown: u64,
+    /// Timeout the user for this many seconds when an incident is recorded, at most 28 days.
+    pub timeout: Option<u64>,
+    /// Messages the user needs to have lost in the incident before they are timed out, as heat
+    /// is shared by the whole guild.
+    pub timeout_threshold: usize,
+}
+
+impl Default for AntiDeleteGuild {
+    fn default() -> Self {
+        Self {
+            min_heat: 3,
+            max_heat: 5,
+            incident_cooldown: 300,
+            timeout: None,
+            timeout_threshold: 3,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct Raid {
+    /// Raid detection only runs in guilds present here.
+    pub guilds: HashMap<GuildId, RaidGuild>,
+}
+
+/// Each join is scored by the signals it matches, a raid is detected when the combined score of
+/// the joins inside the window crosses the threshold.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RaidGuild {
+    /// Seconds that joins are considered together.
+    pub window: u64,
+    pub threshold: u32,
+    /// Accounts younger than this many seconds are considered new.
+    pub new_account_age: u64,
+    pub new_account_score: u32,
+    /// Joins inside the window before velocity counts towards the score.
+    pub velocity_joins: usize,
+    pub velocity_score: u32,
+    pub default_avatar_score: u32,
+    /// Usernames within this edit distance of another recent joiner are similar.
+    pub similar_name_distance: usize,
+    pub similar_name_score: u32,
+    /// Joins inside the window using the same invite before it counts towards the score.
+    pub shared_invite_joins: usize,
+    pub shared_invite_score: u32,
+    /// Works out the invite each member joined through, costing a request per join.
+    pub track_invites: bool,
+    pub lockdown: Option<RaidLockdown>,
+}
+
+impl Default for RaidGuild {
+    fn default() -> Self {
+        Self {
+            window: 60,
+            threshold: 15,
+            new_account_age: 60 * 60 * 24 * 7,
+            new_account_score: 2,
+            velocity_joins: 5,
+            velocity_score: 2,
+            default_avatar_score: 1,
+            similar_name_distance: 2,
+            similar_name_score: 2,
+            shared_invite_joins: 3,
+            shared_invite_score: 2,
+            track_invites: true,
+            lockdown: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RaidLockdown {
+    /// Seconds until the lockdown is lifted.
+    pub duration: u64,
+    /// The verification level to raise the guild to.
+    pub verification_level: Option<u8>,
+    /// Slowmode in seconds to apply to `slowmode_channels`.
+    pub slowmode: Option<u16>,
+    pub slowmode_channels: Vec<ChannelId>,
+    pub pause_invites: bool,
+}
+
+impl Default for RaidLockdown {
+    fn default() -> Self {
+        Self {
+            duration: 30 * 60,
+            verification_level: None,
+            slowmode: None,
+            slowmode_channels: Vec::new(),
+            pause_invites: false,
+        }
+    }
+}
+
+/// Sampled from the cached presences, so this needs the presence intent.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Activity {
+    /// Requests the privileged presence intent and samples activities.
+    ///
+    /// The intent must be approved for the bot or the gateway closes with 4014, and caching
+    /// every presence costs a fair bit of memory, so this is off by default.
+    pub enabled: bool,
+    /// Seconds between samples of the presences in each guild.
+    pub sample_interval: u64,
+    /// Days that samples are kept for.
+    pub retention_days: u32,
+}
+
+impl Default for Activity {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_interval: 600,
+            retention_days: 365,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct PermissionAlerts {
+    /// Alerts are only sent for guilds present here.
+    pub guilds: HashMap<GuildId, PermissionAlertGuild>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PermissionAlertGuild {
+    /// The mod channel alerts are sent to.
+    pub channel: GenericChannelId,
+    /// Role pinged with every alert.
+    #[serde(default)]
+    pub ping_role: Option<RoleId>,
+    /// Roles that no other role should be moved above.
+    #[serde(default)]
+    pub protected_roles: Vec<RoleId>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct AntiNuke {
+    /// Destructive actions are only tracked in guilds present here.
+    pub guilds: HashMap<GuildId, AntiNukeGuild>,
+}
+
+/// Channel deletions, role deletions and bans count as destructive actions.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AntiNukeGuild {
+    /// Seconds that actions by the same executor are considered together.
+    pub window: u64,
+    /// Actions inside the window before the executor has their roles stripped.
+    pub threshold: usize,
+    /// Executors that are never acted on, such as the owner or other bots.
+    pub trusted: Vec<UserId>,
+}
+
+impl Default for AntiNukeGuild {
+    fn default() -> Self {
+        Self {
+            window: 30,
+            threshold: 5,
+            trusted: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct Invites {
+    /// Invites are only moderated in guilds present here.
+    pub guilds: HashMap<GuildId, InviteGuild>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InviteGuild {
+    /// Channel that invites to servers that aren't allowed are reported to.
+    pub report_channel: GenericChannelId,
+    /// Partner servers that can be invited to, invites to the guild itself are always allowed.
+    #[serde(default)]
+    pub allowed_guilds: Vec<GuildId>,
+    /// Delete messages with invites that aren't allowed, instead of only reporting them.
+    #[serde(default)]
+    pub delete: bool,
+    /// Members with any of these roles can post any invite.
+    #[serde(default)]
+    pub exempt_roles: Vec<RoleId>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct DmActivityEscalation {
+    /// Flags are only announced and escalated in guilds present here.
+    pub guilds: HashMap<GuildId, DmActivityGuild>,
+}
+
+impl DmActivityEscalation {
+    /// Configs from before this was configurable keep announcing to the channel that used to be
+    /// hard-coded.
+    fn legacy() -> Self {
+        let guild = DmActivityGuild {
+            channel: GenericChannelId::new(158484765136125952),
+            window: default_dm_activity_window(),
+            threshold: default_dm_activity_threshold(),
+            ping_role: None,
+            timeout: None,
+        };
+
+        Self {
+            guilds: HashMap::from([(GuildId::new(98226572468690944), guild)]),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DmActivityGuild {
+    /// Where flags and escalations are sent.
+    pub channel: GenericChannelId,
+    /// Seconds that flags on the same member are considered together.
+    #[serde(default = "default_dm_activity_window")]
+    pub window: u64,
+    /// Flags inside the window before escalating, a member is escalated once per window.
+    #[serde(default = "default_dm_activity_threshold")]
+    pub threshold: usize,
+    #[serde(default)]
+    pub ping_role: Option<RoleId>,
+    /// Seconds to time the member out for when escalated.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+fn default_dm_activity_window() -> u64 {
+    60 * 60 * 24 * 30
+}
+
+fn default_dm_activity_threshold() -> usize {
+    3
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct ScamLinks {
+    /// Links are only checked in guilds present here.
+    pub guilds: HashMap<GuildId, ScamLinkGuild>,
+    /// Loaded from `config/lists/scam_domains.txt`, subdomains of these are blocked too.
+    #[serde(skip)]
+    pub blocklist: Arc<HashSet<String>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScamLinkGuild {
+    /// Channel that scam links are reported to.
+    pub report_channel: GenericChannelId,
+    #[serde(default)]
+    pub ping_role: Option<RoleId>,
+    /// Members with any of these roles can post any link.
+    #[serde(default)]
+    pub exempt_roles: Vec<RoleId>,
+    /// Also delete links that only look like an official domain, instead of just reporting them.
+    #[serde(default)]
+    pub delete_lookalikes: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct CrossChannelSpam {
+    /// Messages are only compared in guilds present here.
+    pub guilds: HashMap<GuildId, CrossChannelSpamGuild>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CrossChannelSpamGuild {
+    /// Seconds that messages by the same member are compared against each other.
+    pub window: u64,
+    /// Distinct channels the same content or attachment has to be posted in.
+    pub channels: usize,
+    /// Messages shorter than this are ignored, so greetings and the like don't count.
+    pub min_length: usize,
+    /// Bits two image hashes can differ by and still be the same image.
+    pub image_distance: u32,
+    /// Seconds to time the member out for.
+    pub timeout: u64,
+    pub exempt_roles: Vec<RoleId>,
+}
+
+impl Default for CrossChannelSpamGuild {
+    fn default() -> Self {
+        Self {
+            window: 30,
+            channels: 3,
+            min_length: 10,
+            image_distance: 6,
+            timeout: 60 * 60,
+            exempt_roles: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct MentionFlood {
+    /// Mentions are only counted in guilds present here.
+    pub guilds: HashMap<GuildId, MentionFloodGuild>,
+}
+
+/// Unique users, unique roles and attempts at @everyone or @here all count as a mention.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MentionFloodGuild {
+    /// Most mentions a single message can have.
+    pub per_message: usize,
+    /// Seconds that mentions by the same member are added up over.
+    pub window: u64,
+    /// Most mentions a member can make inside the window.
+    pub per_window: usize,
+    /// Delete the messages that went over the limit.
+    pub delete: bool,
+    /// Seconds to time the member out for.
+    pub timeout: Option<u64>,
+    pub exempt_roles: Vec<RoleId>,
+}
+
+impl Default for MentionFloodGuild {
+    fn default() -> Self {
+        Self {
+            per_message: 8,
+            window: 30,
+            per_window: 15,
+            delete: true,
+            timeout: Some(60 * 60),
+            exempt_roles: Vec::new(),
+        }
+    }
 }
@@ -8,6 +8,18 @@ pub fn read_words_from_file(filename: &str) -> HashSet<String> {
         .collect()
 }
 
+/// Reads a list of domains, skipping blank lines and `#` comments.
+///
+/// Unlike word lists a missing file isn't fatal, it just means nothing is listed.
+pub fn read_domains_from_file(filename: &str) -> HashSet<String> {
+    std::fs::read_to_string(filename)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| line.trim().trim_end_matches('.').to_lowercase())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
 pub mod regex_patterns {
     use base64::engine::{Engine as _, general_purpose};
     use regex::Regex;
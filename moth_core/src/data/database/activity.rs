@@ -0,0 +1,108 @@
+use lumi::serenity_prelude::GuildId;
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+impl Database {
+    /// Stores a sample of how many members are playing each game in a guild.
+    pub async fn insert_activity_samples(
+        &self,
+        guild_id: GuildId,
+        counts: &[(String, u32)],
+    ) -> Result<(), Error> {
+        if counts.is_empty() {
+            return Ok(());
+        }
+
+        let guild_id = self.get_guild(guild_id).await?;
+        let (names, players): (Vec<String>, Vec<i32>) = counts
+            .iter()
+            .map(|(name, count)| (name.clone(), *count as i32))
+            .unzip();
+
+        query!(
+            r#"
+            INSERT INTO activity_samples (guild_id, name, players, sampled_at)
+            SELECT $1, name, players, $4
+            FROM UNNEST($2::TEXT[], $3::INT[]) AS s(name, players)
+            "#,
+            guild_id,
+            &names,
+            &players,
+            chrono::Utc::now(),
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes samples taken before the given time.
+    pub async fn prune_activity_samples(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), Error> {
+        query!("DELETE FROM activity_samples WHERE sampled_at < $1", before)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Gets the games with the highest peak player count since the given time, highest first.
+    pub async fn get_top_activities(
+        &self,
+        guild_id: GuildId,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<(String, i32)>, Error> {
+        let rows = query!(
+            r#"
+            SELECT s.name, MAX(s.players) AS "peak!"
+            FROM activity_samples s
+            JOIN guilds g ON g.id = s.guild_id
+            WHERE g.guild_id = $1 AND s.sampled_at >= $2
+            GROUP BY s.name
+            ORDER BY 2 DESC, s.name
+            "#,
+            guild_id.get() as i64,
+            since,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| (row.name, row.peak)).collect())
+    }
+
+    /// Gets the peak player count of a game since the given time, in buckets of an hour or a day.
+    ///
+    /// The name is matched case insensitively.
+    pub async fn get_activity_history(
+        &self,
+        guild_id: GuildId,
+        name: &str,
+        since: chrono::DateTime<chrono::Utc>,
+        hourly: bool,
+    ) -> Result<Vec<(chrono::DateTime<chrono::Utc>, i32)>, Error> {
+        let rows = query!(
+            r#"
+            SELECT
+                date_trunc(CASE WHEN $4 THEN 'hour' ELSE 'day' END, s.sampled_at) AS "bucket!",
+                MAX(s.players) AS "peak!"
+            FROM activity_samples s
+            JOIN guilds g ON g.id = s.guild_id
+            WHERE g.guild_id = $1 AND LOWER(s.name) = LOWER($2) AND s.sampled_at >= $3
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+            guild_id.get() as i64,
+            name,
+            since,
+            hourly,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| (row.bucket, row.peak)).collect())
+    }
+}
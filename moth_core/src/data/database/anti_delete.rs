@@ -0,0 +1,37 @@
+use lumi::serenity_prelude::{GenericChannelId, GuildId, UserId};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+impl Database {
+    /// Records a mass deletion incident.
+    pub async fn insert_anti_delete_incident(
+        &self,
+        guild_id: GuildId,
+        channel_id: GenericChannelId,
+        user_id: UserId,
+        deleted_count: i32,
+        digest: &str,
+        timed_out: bool,
+    ) -> Result<(), Error> {
+        let (channel_id, guild_id) = self.get_channel(channel_id, Some(guild_id)).await?;
+        let user_id = self.get_user(user_id).await?.id;
+
+        query!(
+            "INSERT INTO anti_delete_incidents (guild_id, channel_id, user_id, deleted_count, \
+             digest, timed_out, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            guild_id,
+            channel_id,
+            user_id,
+            deleted_count,
+            digest,
+            timed_out,
+            chrono::Utc::now(),
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use lumi::serenity_prelude::{GuildId, UserId};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+#[derive(Debug, Clone, Copy, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "snapshot_kind", rename_all = "snake_case")]
+pub enum SnapshotKind {
+    Channel,
+    Role,
+}
+
+/// A deleted channel or role.
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub id: i32,
+    pub kind: SnapshotKind,
+    pub target_id: u64,
+    pub name: String,
+    /// The channel or role serialized as json.
+    pub data: String,
+    pub restored: bool,
+}
+
+impl Database {
+    /// Stores a deleted channel or role so it can be recreated.
+    ///
+    /// The delete event can arrive after the audit log entry, so this is attached to a recent
+    /// incident that already targeted it.
+    pub async fn insert_snapshot(
+        &self,
+        guild_id: GuildId,
+        kind: SnapshotKind,
+        target_id: u64,
+        name: &str,
+        data: &str,
+    ) -> Result<(), Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+
+        query!(
+            r#"
+            INSERT INTO deleted_snapshots
+                (guild_id, kind, target_id, name, data, incident_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, (
+                SELECT id FROM nuke_incidents
+                WHERE guild_id = $1
+                    AND $3 = ANY(target_ids)
+                    AND created_at > $6::TIMESTAMPTZ - INTERVAL '1 hour'
+                ORDER BY id DESC
+                LIMIT 1
+            ), $6)
+            "#,
+            guild_id,
+            kind as SnapshotKind,
+            target_id as i64,
+            name,
+            data,
+            chrono::Utc::now(),
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records an anti nuke incident, attaching the snapshots of the channels and roles it
+    /// deleted.
+    pub async fn insert_nuke_incident(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        target_ids: &[u64],
+        roles_stripped: bool,
+    ) -> Result<i32, Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+        let user_id = self.get_user(user_id).await?.id;
+        let target_ids = target_ids.iter().map(|id| *id as i64).collect::<Vec<_>>();
+
+        let mut transaction = self.db.begin().await?;
+
+        let incident_id = query!(
+            "INSERT INTO nuke_incidents (guild_id, user_id, target_ids, roles_stripped, \
+             created_at) VALUES ($1, $2, $3, $4, $5) RETURNING id",
+            guild_id,
+            user_id,
+            &target_ids,
+            roles_stripped,
+            chrono::Utc::now(),
+        )
+        .fetch_one(&mut *transaction)
+        .await?
+        .id;
+
+        query!(
+            "UPDATE deleted_snapshots SET incident_id = $1 WHERE guild_id = $2 AND target_id = \
+             ANY($3) AND incident_id IS NULL",
+            incident_id,
+            guild_id,
+            &target_ids,
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(incident_id)
+    }
+
+    /// Attaches the snapshots of later actions from the same executor to an incident.
+    pub async fn attach_nuke_targets(
+        &self,
+        guild_id: GuildId,
+        incident_id: i32,
+        target_ids: &[u64],
+    ) -> Result<(), Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+        let target_ids = target_ids.iter().map(|id| *id as i64).collect::<Vec<_>>();
+
+        let mut transaction = self.db.begin().await?;
+
+        query!(
+            "UPDATE nuke_incidents SET target_ids = target_ids || $2 WHERE id = $1",
+            incident_id,
+            &target_ids,
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        query!(
+            "UPDATE deleted_snapshots SET incident_id = $1 WHERE guild_id = $2 AND target_id = \
+             ANY($3) AND incident_id IS NULL",
+            incident_id,
+            guild_id,
+            &target_ids,
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Gets the snapshots attached to an incident in a guild.
+    pub async fn get_incident_snapshots(
+        &self,
+        guild_id: GuildId,
+        incident_id: i32,
+    ) -> Result<Vec<Snapshot>, Error> {
+        let rows = query!(
+            r#"
+            SELECT s.id, s.kind AS "kind: SnapshotKind", s.target_id, s.name, s.data, s.restored
+            FROM deleted_snapshots s
+            JOIN guilds g ON g.id = s.guild_id
+            WHERE g.guild_id = $1 AND s.incident_id = $2
+            ORDER BY s.id
+            "#,
+            guild_id.get() as i64,
+            incident_id,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Snapshot {
+                id: row.id,
+                kind: row.kind,
+                target_id: row.target_id as u64,
+                name: row.name,
+                data: row.data,
+                restored: row.restored,
+            })
+            .collect())
+    }
+
+    pub async fn set_snapshot_restored(&self, snapshot_id: i32) -> Result<(), Error> {
+        query!(
+            "UPDATE deleted_snapshots SET restored = TRUE WHERE id = $1",
+            snapshot_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+use std::sync::Arc;
+
+use lumi::serenity_prelude::{GenericChannelId, GuildId, RoleId, SkuId, UserId};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+/// Roles given or taken away based on what a member is wearing.
+#[derive(Clone, Debug)]
+pub struct AppearanceRoleRule {
+    pub id: i32,
+    pub name: String,
+    pub decoration_skus: Vec<SkuId>,
+    pub nameplate_assets: Vec<String>,
+    /// Matched against the tag of the member's primary guild.
+    pub guild_tags: Vec<String>,
+    pub avatar_hashes: Vec<String>,
+    pub add_roles: Vec<RoleId>,
+    pub remove_roles: Vec<RoleId>,
+    pub announce_channel: Option<GenericChannelId>,
+    /// Replied to the message that triggered the rule, if it was triggered by one.
+    pub reply_message: Option<String>,
+    pub added_message: Option<String>,
+    /// Announced when an assigned member matches but no longer has the roles, usually a rejoin.
+    pub restored_message: Option<String>,
+    pub removed_message: Option<String>,
+}
+
+impl Database {
+    /// Gets the appearance role rules of a guild, from the cache if possible.
+    ///
+    /// The cache expires after a few minutes so edits to the rules are picked up.
+    pub async fn get_appearance_role_rules(
+        &self,
+        guild_id: GuildId,
+    ) -> Result<Arc<[AppearanceRoleRule]>, Error> {
+        if let Some(rules) = self.appearance_rules.get(&guild_id) {
+            return Ok(rules);
+        }
+
+        let rows = query!(
+            r#"
+            SELECT r.id, r.name, r.decoration_skus, r.nameplate_assets, r.guild_tags,
+                   r.avatar_hashes, r.add_roles, r.remove_roles, r.announce_channel,
+                   r.reply_message, r.added_message, r.restored_message, r.removed_message
+            FROM appearance_role_rules r
+            JOIN guilds g ON g.id = r.guild_id
+            WHERE g.guild_id = $1
+            ORDER BY r.id
+            "#,
+            guild_id.get() as i64,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let rules: Arc<[AppearanceRoleRule]> = rows
+            .into_iter()
+            .map(|row| AppearanceRoleRule {
+                id: row.id,
+                name: row.name,
+                decoration_skus: row
+                    .decoration_skus
+                    .into_iter()
+                    .map(|id| SkuId::new(id as u64))
+                    .collect(),
+                nameplate_assets: row.nameplate_assets,
+                guild_tags: row.guild_tags,
+                avatar_hashes: row.avatar_hashes,
+                add_roles: row
+                    .add_roles
+                    .into_iter()
+                    .map(|id| RoleId::new(id as u64))
+                    .collect(),
+                remove_roles: row
+                    .remove_roles
+                    .into_iter()
+                    .map(|id| RoleId::new(id as u64))
+                    .collect(),
+                announce_channel: row
+                    .announce_channel
+                    .map(|id| GenericChannelId::new(id as u64)),
+                reply_message: row.reply_message,
+                added_message: row.added_message,
+                restored_message: row.restored_message,
+                removed_message: row.removed_message,
+            })
+            .collect();
+
+        self.appearance_rules.insert(guild_id, rules.clone());
+
+        Ok(rules)
+    }
+
+    /// Creates a rule, or replaces the rule of the same name, returning true if it was created.
+    ///
+    /// The id of the given rule is ignored.
+    pub async fn set_appearance_role_rule(
+        &self,
+        guild_id: GuildId,
+        rule: &AppearanceRoleRule,
+    ) -> Result<bool, Error> {
+        let inner_guild_id = self.get_guild(guild_id).await?;
+
+        let row = query!(
+            r#"
+            INSERT INTO appearance_role_rules (
+                guild_id, name, decoration_skus, nameplate_assets, guild_tags, avatar_hashes,
+                add_roles, remove_roles, announce_channel, reply_message, added_message,
+                restored_message, removed_message
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (guild_id, name) DO UPDATE SET
+                decoration_skus = EXCLUDED.decoration_skus,
+                nameplate_assets = EXCLUDED.nameplate_assets,
+                guild_tags = EXCLUDED.guild_tags,
+                avatar_hashes = EXCLUDED.avatar_hashes,
+                add_roles = EXCLUDED.add_roles,
+                remove_roles = EXCLUDED.remove_roles,
+                announce_channel = EXCLUDED.announce_channel,
+                reply_message = EXCLUDED.reply_message,
+                added_message = EXCLUDED.added_message,
+                restored_message = EXCLUDED.restored_message,
+                removed_message = EXCLUDED.removed_message
+            RETURNING (xmax = 0) AS "created!"
+            "#,
+            inner_guild_id,
+            rule.name,
+            &rule
+                .decoration_skus
+                .iter()
+                .map(|id| id.get() as i64)
+                .collect::<Vec<_>>(),
+            &rule.nameplate_assets,
+            &rule.guild_tags,
+            &rule.avatar_hashes,
+            &rule
+                .add_roles
+                .iter()
+                .map(|id| id.get() as i64)
+                .collect::<Vec<_>>(),
+            &rule
+                .remove_roles
+                .iter()
+                .map(|id| id.get() as i64)
+                .collect::<Vec<_>>(),
+            rule.announce_channel.map(|id| id.get() as i64),
+            rule.reply_message,
+            rule.added_message,
+            rule.restored_message,
+            rule.removed_message,
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        self.appearance_rules.invalidate(&guild_id);
+
+        Ok(row.created)
+    }
+
+    /// Deletes a rule along with who it was applied to, returning its id if it existed.
+    pub async fn delete_appearance_role_rule(
+        &self,
+        guild_id: GuildId,
+        name: &str,
+    ) -> Result<Option<i32>, Error> {
+        let inner_guild_id = self.get_guild(guild_id).await?;
+
+        let row = query!(
+            "DELETE FROM appearance_role_rules WHERE guild_id = $1 AND name = $2 RETURNING id",
+            inner_guild_id,
+            name,
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        self.appearance_rules.invalidate(&guild_id);
+
+        Ok(row.map(|r| r.id))
+    }
+
+    /// Gets every member a rule has been applied to, keyed by the rule id.
+    pub async fn get_appearance_role_assignments(&self) -> Result<Vec<(i32, UserId)>, Error> {
+        let rows = query!(
+            "SELECT a.rule_id, u.user_id FROM appearance_role_assignments a JOIN users u ON u.id \
+             = a.user_id"
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.rule_id, UserId::new(row.user_id as u64)))
+            .collect())
+    }
+
+    /// Marks a rule as applied to a member, so it can be undone or restored later.
+    pub async fn insert_appearance_role_assignment(
+        &self,
+        rule_id: i32,
+        user_id: UserId,
+    ) -> Result<(), Error> {
+        let user_id = self.get_user(user_id).await?.id;
+
+        query!(
+            "INSERT INTO appearance_role_assignments (rule_id, user_id) VALUES ($1, $2) ON \
+             CONFLICT DO NOTHING",
+            rule_id,
+            user_id,
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_appearance_role_assignment(
+        &self,
+        rule_id: i32,
+        user_id: UserId,
+    ) -> Result<(), Error> {
+        let user_id = self.get_user(user_id).await?.id;
+
+        query!(
+            "DELETE FROM appearance_role_assignments WHERE rule_id = $1 AND user_id = $2",
+            rule_id,
+            user_id,
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+}
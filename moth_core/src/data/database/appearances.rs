@@ -0,0 +1,185 @@
+use lumi::serenity_prelude::{GuildId, SkuId, UserId};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+/// Where local copies of avatars, decorations and nameplates are stored.
+pub const APPEARANCE_ARCHIVE_PATH: &str = "config/appearances";
+
+#[derive(Debug, Clone, Copy, sqlx::Type, PartialEq, Eq, Hash)]
+#[sqlx(type_name = "appearance_kind", rename_all = "snake_case")]
+pub enum AppearanceKind {
+    Avatar,
+    GuildAvatar,
+    Decoration,
+    Nameplate,
+}
+
+impl AppearanceKind {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AppearanceKind::Avatar => "Avatar",
+            AppearanceKind::GuildAvatar => "Server avatar",
+            AppearanceKind::Decoration => "Avatar decoration",
+            AppearanceKind::Nameplate => "Nameplate",
+        }
+    }
+}
+
+/// The user, the guild for guild avatars and the kind of appearance.
+pub type AppearanceKey = (UserId, Option<GuildId>, AppearanceKind);
+
+#[derive(Clone, Debug)]
+pub struct Appearance {
+    pub id: i32,
+    pub guild_id: Option<GuildId>,
+    pub kind: AppearanceKind,
+    /// The image hash or nameplate asset path, `None` when it was removed.
+    pub asset: Option<String>,
+    pub sku_id: Option<SkuId>,
+    /// The local copy of the image, if it was downloaded.
+    pub file_path: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Database {
+    /// Records an observed appearance, unless it is already the latest of its kind.
+    ///
+    /// Returns the id of the new entry if one was recorded.
+    pub async fn insert_appearance(
+        &self,
+        key: AppearanceKey,
+        asset: Option<&str>,
+        sku_id: Option<SkuId>,
+    ) -> Result<Option<i32>, Error> {
+        if self
+            .appearances
+            .get(&key)
+            .is_some_and(|cached| cached.as_deref() == asset)
+        {
+            return Ok(None);
+        }
+
+        let (user_id, guild_id, kind) = key;
+        let user_id = self.get_user(user_id).await?.id;
+        let guild_id = match guild_id {
+            Some(guild_id) => Some(self.get_guild(guild_id).await?),
+            None => None,
+        };
+
+        let row = query!(
+            r#"
+            INSERT INTO appearance_history (user_id, guild_id, kind, asset, sku_id, created_at)
+            SELECT $1, $2, $3, $4, $5, $6
+            WHERE (
+                SELECT asset FROM appearance_history
+                WHERE user_id = $1 AND kind = $3 AND guild_id IS NOT DISTINCT FROM $2
+                ORDER BY id DESC
+                LIMIT 1
+            ) IS DISTINCT FROM $4
+            RETURNING id
+            "#,
+            user_id,
+            guild_id,
+            kind as AppearanceKind,
+            asset,
+            sku_id.map(|s| s.get() as i64),
+            chrono::Utc::now(),
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        self.appearances.insert(key, asset.map(ToString::to_string));
+
+        Ok(row.map(|r| r.id))
+    }
+
+    /// Sets the local copy of a recorded appearance once it has been downloaded.
+    pub async fn set_appearance_file(&self, id: i32, file_path: &str) -> Result<(), Error> {
+        query!(
+            "UPDATE appearance_history SET file_path = $1 WHERE id = $2",
+            file_path,
+            id,
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Forgets the local copies of a user's appearances beyond the newest `keep`.
+    ///
+    /// Returns the paths of the files that should be removed.
+    pub async fn take_old_appearance_files(
+        &self,
+        user_id: UserId,
+        keep: i64,
+    ) -> Result<Vec<String>, Error> {
+        let user_id = self.get_user(user_id).await?.id;
+
+        let rows = query!(
+            r#"
+            UPDATE appearance_history SET file_path = NULL
+            WHERE id IN (
+                SELECT id FROM appearance_history
+                WHERE user_id = $1 AND file_path IS NOT NULL
+                ORDER BY id DESC
+                OFFSET $2
+            )
+            RETURNING file_path AS "file_path!"
+            "#,
+            user_id,
+            keep,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.file_path).collect())
+    }
+
+    /// Gets the appearance history of a user, newest first.
+    ///
+    /// Guild avatars are only included for the given guild.
+    pub async fn get_appearances(
+        &self,
+        user_id: UserId,
+        guild_id: GuildId,
+    ) -> Result<Vec<Appearance>, Error> {
+        let rows = query!(
+            r#"
+            SELECT
+                a.id,
+                g.guild_id AS "guild_id?",
+                a.kind AS "kind: AppearanceKind",
+                a.asset,
+                a.sku_id,
+                a.file_path,
+                a.created_at
+            FROM appearance_history a
+            JOIN users u ON u.id = a.user_id
+            LEFT JOIN guilds g ON g.id = a.guild_id
+            WHERE u.user_id = $1 AND (a.guild_id IS NULL OR g.guild_id = $2)
+            ORDER BY a.id DESC
+            "#,
+            user_id.get() as i64,
+            guild_id.get() as i64,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Appearance {
+                id: row.id,
+                guild_id: row.guild_id.map(|g| GuildId::new(g as u64)),
+                kind: row.kind,
+                asset: row.asset,
+                sku_id: row.sku_id.map(|s| SkuId::new(s as u64)),
+                file_path: row.file_path,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use lumi::serenity_prelude::{GuildId, UserId};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+/// A single unusual dm activity flag observed on a member.
+#[derive(Clone, Debug)]
+pub struct DmActivityEvent {
+    pub until: DateTime<Utc>,
+    pub observed_at: DateTime<Utc>,
+    pub announced: bool,
+    pub escalated: bool,
+}
+
+impl Database {
+    /// Records an unusual dm activity flag, returning the id of the event.
+    pub async fn insert_dm_activity_event(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        until: DateTime<Utc>,
+        announced: bool,
+    ) -> Result<i32, Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+        let user_id = self.get_user(user_id).await?.id;
+
+        let row = query!(
+            "INSERT INTO dm_activity_events (guild_id, user_id, until, observed_at, announced) \
+             VALUES ($1, $2, $3, $4, $5) RETURNING id",
+            guild_id,
+            user_id,
+            until,
+            Utc::now(),
+            announced,
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(row.id)
+    }
+
+    /// Gets every flag of a member, oldest first.
+    pub async fn get_dm_activity_events(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Vec<DmActivityEvent>, Error> {
+        let rows = query!(
+            r#"
+            SELECT e.until, e.observed_at, e.announced, e.escalated
+            FROM dm_activity_events e
+            JOIN guilds g ON g.id = e.guild_id
+            JOIN users u ON u.id = e.user_id
+            WHERE g.guild_id = $1 AND u.user_id = $2
+            ORDER BY e.observed_at
+            "#,
+            guild_id.get() as i64,
+            user_id.get() as i64,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| DmActivityEvent {
+                until: row.until,
+                observed_at: row.observed_at,
+                announced: row.announced,
+                escalated: row.escalated,
+            })
+            .collect())
+    }
+
+    /// Counts the announced flags of a member since a point in time, and whether any of them
+    /// were escalated.
+    ///
+    /// Extensions of a flag that wasn't announced aren't counted, they are the same incident.
+    pub async fn count_dm_activity_flags(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        since: DateTime<Utc>,
+    ) -> Result<(i64, bool), Error> {
+        let row = query!(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE e.announced) AS "flags!",
+                COALESCE(BOOL_OR(e.escalated), FALSE) AS "escalated!"
+            FROM dm_activity_events e
+            JOIN guilds g ON g.id = e.guild_id
+            JOIN users u ON u.id = e.user_id
+            WHERE g.guild_id = $1 AND u.user_id = $2 AND e.observed_at >= $3
+            "#,
+            guild_id.get() as i64,
+            user_id.get() as i64,
+            since,
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok((row.flags, row.escalated))
+    }
+
+    pub async fn set_dm_activity_escalated(&self, event_id: i32) -> Result<(), Error> {
+        query!(
+            "UPDATE dm_activity_events SET escalated = TRUE WHERE id = $1",
+            event_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use lumi::serenity_prelude::{GuildId, UserId};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+impl Database {
+    /// Records a member joining, with the invite they used if it could be worked out.
+    pub async fn insert_member_join(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        invite_code: Option<&str>,
+        inviter_id: Option<UserId>,
+    ) -> Result<(), Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+        let user_id = self.get_user(user_id).await?.id;
+        let inviter_id = match inviter_id {
+            Some(inviter_id) => Some(self.get_user(inviter_id).await?.id),
+            None => None,
+        };
+
+        query!(
+            "INSERT INTO member_joins (guild_id, user_id, invite_code, inviter_id, joined_at) \
+             VALUES ($1, $2, $3, $4, $5)",
+            guild_id,
+            user_id,
+            invite_code,
+            inviter_id,
+            chrono::Utc::now(),
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets the users whose invites brought in the most members, counting each member once.
+    pub async fn get_top_inviters(
+        &self,
+        guild_id: GuildId,
+        limit: i64,
+    ) -> Result<Vec<(UserId, i64)>, Error> {
+        let rows = query!(
+            r#"
+            SELECT i.user_id, COUNT(DISTINCT j.user_id) AS "joins!"
+            FROM member_joins j
+            JOIN guilds g ON g.id = j.guild_id
+            JOIN users i ON i.id = j.inviter_id
+            WHERE g.guild_id = $1
+            GROUP BY i.user_id
+            ORDER BY 2 DESC
+            LIMIT $2
+            "#,
+            guild_id.get() as i64,
+            limit,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (UserId::new(row.user_id as u64), row.joins))
+            .collect())
+    }
+
+    /// Gets the members that joined through an invite code, newest first.
+    pub async fn get_invite_joins(
+        &self,
+        guild_id: GuildId,
+        invite_code: &str,
+    ) -> Result<Vec<(UserId, chrono::DateTime<chrono::Utc>)>, Error> {
+        let rows = query!(
+            r#"
+            SELECT u.user_id, j.joined_at
+            FROM member_joins j
+            JOIN guilds g ON g.id = j.guild_id
+            JOIN users u ON u.id = j.user_id
+            WHERE g.guild_id = $1 AND j.invite_code = $2
+            ORDER BY j.joined_at DESC
+            "#,
+            guild_id.get() as i64,
+            invite_code,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (UserId::new(row.user_id as u64), row.joined_at))
+            .collect())
+    }
+}
@@ -0,0 +1,86 @@
+use std::{collections::HashSet, sync::Arc};
+
+use lumi::serenity_prelude::{GuildId, UserId};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+impl Database {
+    /// Gets the domains that are never treated as scams in a guild, from the cache if possible.
+    pub async fn get_link_allowlist(
+        &self,
+        guild_id: GuildId,
+    ) -> Result<Arc<HashSet<String>>, Error> {
+        if let Some(allowlist) = self.link_allowlist.get(&guild_id) {
+            return Ok(allowlist.clone());
+        }
+
+        let rows = query!(
+            r#"
+            SELECT a.domain
+            FROM link_allowlist a
+            JOIN guilds g ON g.id = a.guild_id
+            WHERE g.guild_id = $1
+            "#,
+            guild_id.get() as i64,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let allowlist = Arc::new(
+            rows.into_iter()
+                .map(|row| row.domain)
+                .collect::<HashSet<_>>(),
+        );
+        self.link_allowlist.insert(guild_id, allowlist.clone());
+
+        Ok(allowlist)
+    }
+
+    /// Allows a domain in a guild, returning false if it already was.
+    pub async fn insert_link_allowlist(
+        &self,
+        guild_id: GuildId,
+        domain: &str,
+        added_by: UserId,
+    ) -> Result<bool, Error> {
+        let inner_guild_id = self.get_guild(guild_id).await?;
+        let added_by = self.get_user(added_by).await?.id;
+
+        let result = query!(
+            "INSERT INTO link_allowlist (guild_id, domain, added_by, created_at) VALUES ($1, $2, \
+             $3, $4) ON CONFLICT (guild_id, domain) DO NOTHING",
+            inner_guild_id,
+            domain,
+            added_by,
+            chrono::Utc::now(),
+        )
+        .execute(&self.db)
+        .await?;
+
+        self.link_allowlist.remove(&guild_id);
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Removes a domain from the allowlist of a guild, returning false if it wasn't on it.
+    pub async fn delete_link_allowlist(
+        &self,
+        guild_id: GuildId,
+        domain: &str,
+    ) -> Result<bool, Error> {
+        let result = query!(
+            "DELETE FROM link_allowlist a USING guilds g WHERE g.id = a.guild_id AND g.guild_id = \
+             $1 AND a.domain = $2",
+            guild_id.get() as i64,
+            domain,
+        )
+        .execute(&self.db)
+        .await?;
+
+        self.link_allowlist.remove(&guild_id);
+
+        Ok(result.rows_affected() != 0)
+    }
+}
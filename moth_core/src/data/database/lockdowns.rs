@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use lumi::serenity_prelude::{
+    ChannelId, GuildId, PermissionOverwrite, PermissionOverwriteType, Permissions, RoleId, UserId,
+};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+impl Database {
+    /// Snapshots the overwrites of a channel before it is locked down.
+    ///
+    /// Returns false without touching the snapshot if the channel is already locked down, so
+    /// the overwrites from before the first lockdown are the ones restored.
+    pub async fn insert_lockdown(
+        &self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        overwrites: &[PermissionOverwrite],
+    ) -> Result<bool, Error> {
+        let (channel_id, _) = self.get_channel(channel_id.widen(), Some(guild_id)).await?;
+        let guild_id = self.get_guild(guild_id).await?;
+
+        let mut transaction = self.db.begin().await?;
+
+        let inserted = query!(
+            "INSERT INTO lockdown_channels (channel_id, guild_id, created_at) VALUES ($1, $2, $3) \
+             ON CONFLICT (channel_id) DO NOTHING",
+            channel_id,
+            guild_id,
+            chrono::Utc::now(),
+        )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if !inserted {
+            return Ok(false);
+        }
+
+        for overwrite in overwrites {
+            let (target_id, is_role) = match overwrite.kind {
+                PermissionOverwriteType::Member(user_id) => (user_id.get(), false),
+                PermissionOverwriteType::Role(role_id) => (role_id.get(), true),
+                _ => continue,
+            };
+
+            query!(
+                "INSERT INTO lockdown_overwrites (channel_id, target_id, is_role, allow, deny) \
+                 VALUES ($1, $2, $3, $4, $5)",
+                channel_id,
+                target_id as i64,
+                is_role,
+                overwrite.allow.bits() as i64,
+                overwrite.deny.bits() as i64,
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Gets the snapshotted overwrites of every locked down channel in a guild.
+    pub async fn get_lockdowns(
+        &self,
+        guild_id: GuildId,
+    ) -> Result<HashMap<ChannelId, Vec<PermissionOverwrite>>, Error> {
+        let rows = query!(
+            r#"
+            SELECT
+                c.channel_id,
+                o.target_id AS "target_id?",
+                o.is_role AS "is_role?",
+                o.allow AS "allow?",
+                o.deny AS "deny?"
+            FROM lockdown_channels l
+            JOIN channels c ON c.id = l.channel_id
+            JOIN guilds g ON g.id = l.guild_id
+            LEFT JOIN lockdown_overwrites o ON o.channel_id = l.channel_id
+            WHERE g.guild_id = $1
+            "#,
+            guild_id.get() as i64,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut lockdowns: HashMap<ChannelId, Vec<PermissionOverwrite>> = HashMap::new();
+        for row in rows {
+            let overwrites = lockdowns
+                .entry(ChannelId::new(row.channel_id as u64))
+                .or_default();
+
+            // channels without any overwrites still need to be restored.
+            let (Some(target_id), Some(is_role), Some(allow), Some(deny)) =
+                (row.target_id, row.is_role, row.allow, row.deny)
+            else {
+                continue;
+            };
+
+            let kind = if is_role {
+                PermissionOverwriteType::Role(RoleId::new(target_id as u64))
+            } else {
+                PermissionOverwriteType::Member(UserId::new(target_id as u64))
+            };
+
+            overwrites.push(PermissionOverwrite {
+                allow: Permissions::from_bits_truncate(allow as u64),
+                deny: Permissions::from_bits_truncate(deny as u64),
+                kind,
+            });
+        }
+
+        Ok(lockdowns)
+    }
+
+    /// Removes the snapshot of a channel once it has been restored.
+    pub async fn delete_lockdown(&self, channel_id: ChannelId) -> Result<(), Error> {
+        query!(
+            "DELETE FROM lockdown_channels WHERE channel_id = (SELECT id FROM channels WHERE \
+             channel_id = $1)",
+            channel_id.get() as i64,
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+}
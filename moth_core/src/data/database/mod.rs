@@ -6,7 +6,12 @@ use dashmap::{DashMap, DashSet};
 use parking_lot::Mutex;
 use serenity::all::UserId;
 use sqlx::{Executor, PgPool, postgres::PgPoolOptions, query};
-use std::{collections::HashSet, env, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    sync::Arc,
+    time::Duration,
+};
 
 use crate::data::{
     database::private_vcs::task::GUILD,
@@ -15,13 +20,28 @@ use crate::data::{
 
 use lumi::serenity_prelude as serenity;
 
+pub mod activity;
+pub mod anti_delete;
+pub mod anti_nuke;
+pub mod appearance_roles;
+pub mod appearances;
 pub mod auth;
+pub mod dm_activity;
+pub mod joins;
+pub mod links;
+pub mod lockdowns;
+pub mod moderation;
+pub mod names;
+pub mod reaction_blocks;
 pub mod starboard;
 pub mod wrappers;
 pub use starboard::*;
 pub use wrappers::*;
 pub mod private_vcs;
 pub mod reactions;
+pub mod snipes;
+pub mod vc_status;
+pub mod voice;
 
 pub async fn init_data() -> Database {
     let database_url =
@@ -50,6 +70,14 @@ pub async fn init_data() -> Database {
         .time_to_idle(Duration::from_secs(1800))
         .build();
 
+    let appearance_cache = mini_moka::sync::CacheBuilder::new(10_000)
+        .time_to_idle(Duration::from_secs(86400))
+        .build();
+
+    let appearance_rules_cache = mini_moka::sync::CacheBuilder::new(100)
+        .time_to_live(Duration::from_secs(600))
+        .build();
+
     Database {
         starboard: Mutex::new(
             StarboardHandler::new(&database)
@@ -64,6 +92,10 @@ pub async fn init_data() -> Database {
         messages: DashMap::new(),
         emotes: DashMap::new(),
         private_vc: private_vc_cache,
+        appearances: appearance_cache,
+        appearance_rules: appearance_rules_cache,
+        link_allowlist: DashMap::new(),
+        reaction_blocks: DashMap::new(),
     }
 }
 
@@ -89,6 +121,15 @@ pub struct Database {
     // TODO: try and keep private and rewrite them when i eventually redo my users and starboard part.
     /// Runtime caches for dm activity.
     pub(crate) dm_activity: DashMap<UserId, DmActivity>,
+    /// The last recorded asset of each appearance, so unchanged ones don't hit the database.
+    appearances: mini_moka::sync::Cache<appearances::AppearanceKey, Option<String>>,
+    /// The appearance role rules of each guild.
+    appearance_rules:
+        mini_moka::sync::Cache<serenity::GuildId, Arc<[appearance_roles::AppearanceRoleRule]>>,
+    /// Domains allowed past the scam link checks in each guild.
+    link_allowlist: DashMap<serenity::GuildId, Arc<HashSet<String>>>,
+    /// Emoji removed when reacted onto a user's messages or in a channel, per guild.
+    reaction_blocks: DashMap<serenity::GuildId, Arc<[reaction_blocks::ReactionBlock]>>,
 }
 
 pub struct Transaction<'a> {
@@ -352,6 +393,39 @@ impl Database {
         self.messages.get(message_id).map(|v| *v)
     }
 
+    /// Resolves the authors of stored messages, messages that were never stored are omitted.
+    pub async fn get_message_authors(
+        &self,
+        message_ids: &[MessageId],
+    ) -> Result<HashMap<MessageId, UserId>, sqlx::Error> {
+        let ids = message_ids
+            .iter()
+            .map(|m| m.get() as i64)
+            .collect::<Vec<_>>();
+
+        let rows = query!(
+            r#"
+            SELECT m.message_id, u.user_id
+            FROM messages m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.message_id = ANY($1)
+            "#,
+            &ids
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    MessageId::new(row.message_id as u64),
+                    UserId::new(row.user_id as u64),
+                )
+            })
+            .collect())
+    }
+
     /// Gets the guild from the database, or inserts it if it doesn't exist, returning the inner id value.
     pub async fn get_guild(&self, guild_id: serenity::GuildId) -> Result<i32, sqlx::Error> {
         if let Some(id) = self.guilds.get(&guild_id) {
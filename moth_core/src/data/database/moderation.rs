@@ -0,0 +1,125 @@
+use lumi::serenity_prelude::{GuildId, UserId};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+#[derive(Debug, Clone, Copy, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "case_action", rename_all = "snake_case")]
+pub enum CaseAction {
+    Kick,
+    Ban,
+    Unban,
+    Prune,
+}
+
+impl CaseAction {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CaseAction::Kick => "Kick",
+            CaseAction::Ban => "Ban",
+            CaseAction::Unban => "Unban",
+            CaseAction::Prune => "Prune",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ModerationCase {
+    pub id: i32,
+    pub action: CaseAction,
+    pub moderator_id: Option<UserId>,
+    /// `None` for prunes.
+    pub target_id: Option<UserId>,
+    pub reason: Option<String>,
+    /// Days of inactivity and members removed, only set for prunes.
+    pub prune: Option<(i32, i32)>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Database {
+    /// Records a moderation case from an audit log entry, ignoring entries already recorded.
+    pub async fn insert_moderation_case(
+        &self,
+        guild_id: GuildId,
+        audit_log_id: u64,
+        case: &ModerationCase,
+    ) -> Result<(), Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+        let moderator_id = match case.moderator_id {
+            Some(user_id) => Some(self.get_user(user_id).await?.id),
+            None => None,
+        };
+        let target_id = match case.target_id {
+            Some(user_id) => Some(self.get_user(user_id).await?.id),
+            None => None,
+        };
+
+        query!(
+            "INSERT INTO moderation_cases (guild_id, audit_log_id, action, moderator_id, \
+             target_id, reason, prune_days, pruned_count, created_at) VALUES ($1, $2, $3, $4, $5, \
+             $6, $7, $8, $9) ON CONFLICT (audit_log_id) DO NOTHING",
+            guild_id,
+            audit_log_id as i64,
+            case.action as CaseAction,
+            moderator_id,
+            target_id,
+            case.reason,
+            case.prune.map(|(days, _)| days),
+            case.prune.map(|(_, count)| count),
+            case.created_at,
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets the cases against a user in a guild, newest first.
+    ///
+    /// Prunes have no target, so the prunes the user carried out are included instead.
+    pub async fn get_moderation_cases(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Vec<ModerationCase>, Error> {
+        let rows = query!(
+            r#"
+            SELECT
+                m.id,
+                m.action AS "action: CaseAction",
+                mu.user_id AS "moderator_id?",
+                t.user_id AS "target_id?",
+                m.reason,
+                m.prune_days,
+                m.pruned_count,
+                m.created_at
+            FROM moderation_cases m
+            JOIN guilds g ON g.id = m.guild_id
+            LEFT JOIN users t ON t.id = m.target_id
+            LEFT JOIN users mu ON mu.id = m.moderator_id
+            WHERE g.guild_id = $1
+                AND (t.user_id = $2 OR (m.target_id IS NULL AND mu.user_id = $2))
+            ORDER BY m.id DESC
+            "#,
+            guild_id.get() as i64,
+            user_id.get() as i64,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ModerationCase {
+                id: row.id,
+                action: row.action,
+                moderator_id: row.moderator_id.map(|id| UserId::new(id as u64)),
+                target_id: row.target_id.map(|id| UserId::new(id as u64)),
+                reason: row.reason,
+                prune: row.prune_days.zip(row.pruned_count),
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+}
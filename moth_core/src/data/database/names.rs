@@ -0,0 +1,160 @@
+use lumi::serenity_prelude::{GuildId, UserId};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+#[derive(Debug, Clone, Copy, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "name_kind", rename_all = "snake_case")]
+pub enum NameKind {
+    Username,
+    GlobalName,
+    Nickname,
+}
+
+impl NameKind {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NameKind::Username => "Username",
+            NameKind::GlobalName => "Display name",
+            NameKind::Nickname => "Nickname",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NameEntry {
+    pub user_id: UserId,
+    /// Only set for nicknames.
+    pub guild_id: Option<GuildId>,
+    pub kind: NameKind,
+    /// `None` when the display name or nickname was removed.
+    pub name: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Database {
+    /// Records an observed name, unless it is already the latest name of its kind.
+    ///
+    /// A missing name is only recorded as a removal when there was a name before it.
+    pub async fn insert_name(
+        &self,
+        user_id: UserId,
+        guild_id: Option<GuildId>,
+        kind: NameKind,
+        name: Option<&str>,
+    ) -> Result<(), Error> {
+        let user_id = self.get_user(user_id).await?.id;
+        let guild_id = match guild_id {
+            Some(guild_id) => Some(self.get_guild(guild_id).await?),
+            None => None,
+        };
+
+        query!(
+            r#"
+            INSERT INTO name_history (user_id, guild_id, kind, name, created_at)
+            SELECT $1, $2, $3, $4, $5
+            WHERE (
+                SELECT name FROM name_history
+                WHERE user_id = $1 AND kind = $3 AND guild_id IS NOT DISTINCT FROM $2
+                ORDER BY id DESC
+                LIMIT 1
+            ) IS DISTINCT FROM $4
+            AND ($4 IS NOT NULL OR EXISTS (
+                SELECT 1 FROM name_history
+                WHERE user_id = $1 AND kind = $3 AND guild_id IS NOT DISTINCT FROM $2
+            ))
+            "#,
+            user_id,
+            guild_id,
+            kind as NameKind,
+            name,
+            chrono::Utc::now(),
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets every name a user has been seen with, newest first.
+    ///
+    /// Nicknames are only included for the given guild.
+    pub async fn get_names(
+        &self,
+        user_id: UserId,
+        guild_id: GuildId,
+    ) -> Result<Vec<NameEntry>, Error> {
+        let rows = query!(
+            r#"
+            SELECT
+                g.guild_id AS "guild_id?",
+                n.kind AS "kind: NameKind",
+                n.name,
+                n.created_at
+            FROM name_history n
+            JOIN users u ON u.id = n.user_id
+            LEFT JOIN guilds g ON g.id = n.guild_id
+            WHERE u.user_id = $1 AND (n.guild_id IS NULL OR g.guild_id = $2)
+            ORDER BY n.id DESC
+            "#,
+            user_id.get() as i64,
+            guild_id.get() as i64,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| NameEntry {
+                user_id,
+                guild_id: row.guild_id.map(|g| GuildId::new(g as u64)),
+                kind: row.kind,
+                name: row.name,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+
+    /// Finds everyone that has ever used a name, ignoring case, newest first.
+    ///
+    /// Nicknames are only included for the given guild.
+    pub async fn search_names(
+        &self,
+        name: &str,
+        guild_id: GuildId,
+    ) -> Result<Vec<NameEntry>, Error> {
+        let rows = query!(
+            r#"
+            SELECT
+                u.user_id,
+                g.guild_id AS "guild_id?",
+                n.kind AS "kind: NameKind",
+                n.name,
+                n.created_at
+            FROM name_history n
+            JOIN users u ON u.id = n.user_id
+            LEFT JOIN guilds g ON g.id = n.guild_id
+            WHERE LOWER(n.name) = LOWER($1) AND (n.guild_id IS NULL OR g.guild_id = $2)
+            ORDER BY n.id DESC
+            LIMIT 500
+            "#,
+            name,
+            guild_id.get() as i64,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| NameEntry {
+                user_id: UserId::new(row.user_id as u64),
+                guild_id: row.guild_id.map(|g| GuildId::new(g as u64)),
+                kind: row.kind,
+                name: row.name,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use lumi::serenity_prelude::{GenericChannelId, GuildId, UserId};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+/// What a reaction block applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReactionBlockTarget {
+    /// Messages sent by this user.
+    User(UserId),
+    /// Any message in this channel.
+    Channel(GenericChannelId),
+}
+
+/// An emoji that gets removed when reacted onto a user's messages or in a channel.
+#[derive(Clone, Debug)]
+pub struct ReactionBlock {
+    pub id: i32,
+    pub target: ReactionBlockTarget,
+    /// The name of a custom emoji or the unicode emoji itself.
+    pub emoji: String,
+    pub added_by: Option<UserId>,
+}
+
+impl Database {
+    /// Gets the reaction blocks of a guild, from the cache if possible.
+    pub async fn get_reaction_blocks(
+        &self,
+        guild_id: GuildId,
+    ) -> Result<Arc<[ReactionBlock]>, Error> {
+        if let Some(blocks) = self.reaction_blocks.get(&guild_id) {
+            return Ok(blocks.clone());
+        }
+
+        let rows = query!(
+            r#"
+            SELECT b.id, t.user_id AS "target_user_id?", b.channel_id, b.emoji,
+                   a.user_id AS "added_by?"
+            FROM reaction_blocks b
+            JOIN guilds g ON g.id = b.guild_id
+            LEFT JOIN users t ON t.id = b.target_user_id
+            LEFT JOIN users a ON a.id = b.added_by
+            WHERE g.guild_id = $1
+            ORDER BY b.id
+            "#,
+            guild_id.get() as i64,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let blocks: Arc<[ReactionBlock]> = rows
+            .into_iter()
+            .filter_map(|row| {
+                let target = match (row.target_user_id, row.channel_id) {
+                    (Some(user_id), _) => ReactionBlockTarget::User(UserId::new(user_id as u64)),
+                    (None, Some(channel_id)) => {
+                        ReactionBlockTarget::Channel(GenericChannelId::new(channel_id as u64))
+                    }
+                    (None, None) => return None,
+                };
+
+                Some(ReactionBlock {
+                    id: row.id,
+                    target,
+                    emoji: row.emoji,
+                    added_by: row.added_by.map(|id| UserId::new(id as u64)),
+                })
+            })
+            .collect();
+
+        self.reaction_blocks.insert(guild_id, blocks.clone());
+
+        Ok(blocks)
+    }
+
+    /// Blocks an emoji on a user's messages or in a channel, returning false if it already was.
+    pub async fn insert_reaction_block(
+        &self,
+        guild_id: GuildId,
+        target: ReactionBlockTarget,
+        emoji: &str,
+        added_by: UserId,
+    ) -> Result<bool, Error> {
+        let inner_guild_id = self.get_guild(guild_id).await?;
+        let added_by = self.get_user(added_by).await?.id;
+
+        let (target_user_id, channel_id) = match target {
+            ReactionBlockTarget::User(user_id) => (Some(self.get_user(user_id).await?.id), None),
+            ReactionBlockTarget::Channel(channel_id) => (None, Some(channel_id.get() as i64)),
+        };
+
+        let result = query!(
+            "INSERT INTO reaction_blocks (guild_id, target_user_id, channel_id, emoji, added_by, \
+             created_at) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT DO NOTHING",
+            inner_guild_id,
+            target_user_id,
+            channel_id,
+            emoji,
+            added_by,
+            chrono::Utc::now(),
+        )
+        .execute(&self.db)
+        .await?;
+
+        self.reaction_blocks.remove(&guild_id);
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Removes a reaction block from a guild, returning false if it didn't exist.
+    pub async fn delete_reaction_block(&self, guild_id: GuildId, id: i32) -> Result<bool, Error> {
+        let result = query!(
+            "DELETE FROM reaction_blocks b USING guilds g WHERE g.id = b.guild_id AND g.guild_id \
+             = $1 AND b.id = $2",
+            guild_id.get() as i64,
+            id,
+        )
+        .execute(&self.db)
+        .await?;
+
+        self.reaction_blocks.remove(&guild_id);
+
+        Ok(result.rows_affected() != 0)
+    }
+}
@@ -44,6 +44,24 @@ impl PartialEq for EmoteUsage {
 
 impl Eq for EmoteUsage {}
 
+/// Reactions cleared from a message by a moderator, every reaction if no emoji is given.
+#[derive(Clone)]
+struct ReactionClear {
+    message: MessageId,
+    reaction_type: Option<ReactionType>,
+}
+
+impl ReactionClear {
+    fn covers(&self, usage: &EmoteUsage) -> bool {
+        usage.kind == EmoteUsageType::Reaction
+            && usage.message == self.message
+            && self
+                .reaction_type
+                .as_ref()
+                .is_none_or(|r| *r == usage.reaction_type)
+    }
+}
+
 impl std::hash::Hash for EmoteUsage {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.channel.hash(state);
@@ -205,6 +223,28 @@ impl Sender {
         });
     }
 
+    /// Sends the removal of every reaction on a message to the task, if one exists.
+    pub async fn reaction_remove_all(&self, message: MessageId) {
+        self.send_clear_(message, None).await;
+    }
+
+    /// Sends the removal of every reaction of one emoji on a message to the task, if one exists.
+    pub async fn reaction_remove_emoji(&self, reaction: &Reaction) {
+        self.send_clear_(reaction.message_id, Some(reaction.emoji.clone()))
+            .await;
+    }
+
+    async fn send_clear_(&self, message: MessageId, reaction_type: Option<ReactionType>) {
+        let lock = self.0.lock().await;
+
+        lock.as_ref().map(|s| {
+            s.send(EmoteCommand::ReactionClear(ReactionClear {
+                message,
+                reaction_type,
+            }))
+        });
+    }
+
     #[must_use]
     pub fn new() -> Self {
         Self(Arc::new(tokio::sync::Mutex::new(None)))
@@ -214,6 +254,7 @@ impl Sender {
 enum EmoteCommand {
     ReactionAdd(EmoteUsage),
     ReactionRemove(EmoteUsage),
+    ReactionClear(ReactionClear),
     Shutdown,
 }
 
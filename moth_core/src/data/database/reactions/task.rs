@@ -11,7 +11,7 @@ use tokio_util::time::{DelayQueue, delay_queue::Key};
 use crate::data::{
     database::{
         Database, EmoteUsageType,
-        reactions::{EmoteCommand, EmoteUsage},
+        reactions::{EmoteCommand, EmoteUsage, ReactionClear},
     },
     structs::Error,
 };
@@ -24,6 +24,7 @@ pub(super) async fn start(
     let mut keys = HashMap::new();
     let mut batch = Vec::new();
     let mut pending_db_removals = Vec::new();
+    let mut pending_db_clears = Vec::new();
     let mut batch_started: Option<Instant> = None;
 
     let mut interval = tokio::time::interval(Duration::from_secs(1));
@@ -32,10 +33,10 @@ pub(super) async fn start(
         tokio::select! {
             Some(cmd) = rx.recv() => {
                 // exit the task, we have shutdown
-                if !handle_command(cmd, &mut delay_queue, &mut keys, &mut batch, &mut pending_db_removals) {
-                    if should_flush_batch(&batch, batch_started) {
+                if !handle_command(cmd, &mut delay_queue, &mut keys, &mut batch, &mut pending_db_removals, &mut pending_db_clears) {
+                    if should_flush_batch(&batch, batch_started) || !pending_db_clears.is_empty() {
                         // TODO: log
-                        let _ = flush_batch(&batch, &pending_db_removals, &database).await;
+                        let _ = flush_batch(&batch, &pending_db_removals, &pending_db_clears, &database).await;
                     }
 
                     break
@@ -50,11 +51,13 @@ pub(super) async fn start(
                 batch.push(meta);
             },
             _ = interval.tick() => {
-                if should_flush_batch(&batch, batch_started) {
+                // clears don't wait for a batch, the starboard and stats should reflect them soon.
+                if should_flush_batch(&batch, batch_started) || !pending_db_clears.is_empty() {
                     // TODO: log
-                    let _ = flush_batch(&batch, &pending_db_removals, &database).await;
+                    let _ = flush_batch(&batch, &pending_db_removals, &pending_db_clears, &database).await;
                     batch.clear();
                     pending_db_removals.clear();
+                    pending_db_clears.clear();
                     batch_started = None;
                 }
             }
@@ -78,6 +81,7 @@ fn handle_command(
     keys: &mut HashMap<EmoteUsage, Key>,
     batch: &mut Vec<EmoteUsage>,
     pending_db_removals: &mut Vec<EmoteUsage>,
+    pending_db_clears: &mut Vec<ReactionClear>,
 ) -> bool {
     match cmd {
         EmoteCommand::ReactionAdd(reaction) => {
@@ -98,6 +102,20 @@ fn handle_command(
                 pending_db_removals.push(reaction);
             }
         }
+        EmoteCommand::ReactionClear(clear) => {
+            // anything not written yet is dropped, rows that were are deleted on the next flush.
+            keys.retain(|reaction, key| {
+                if clear.covers(reaction) {
+                    delay_queue.remove(key);
+                    false
+                } else {
+                    true
+                }
+            });
+            batch.retain(|reaction| !clear.covers(reaction));
+            pending_db_removals.retain(|reaction| !clear.covers(reaction));
+            pending_db_clears.push(clear);
+        }
         EmoteCommand::Shutdown => return false,
     }
     true // Keep running
@@ -106,10 +124,51 @@ fn handle_command(
 async fn flush_batch(
     batch: &[EmoteUsage],
     pending_db_removals: &[EmoteUsage],
+    pending_db_clears: &[ReactionClear],
     database: &Database,
 ) -> Result<(), Error> {
     use sqlx::Postgres;
 
+    // done first, anything left in the batch was added after the clear.
+    if !pending_db_clears.is_empty() {
+        let mut values = Vec::new();
+
+        for clear in pending_db_clears {
+            let Ok(message_data) = database.get_message_dataless(clear.message).await else {
+                continue;
+            };
+
+            let emote_id = match &clear.reaction_type {
+                Some(reaction_type) => Some(database.get_emote_id(reaction_type).await?),
+                None => None,
+            };
+
+            values.push((message_data.id, emote_id));
+        }
+
+        if !values.is_empty() {
+            let mut query_builder =
+                QueryBuilder::<Postgres>::new("DELETE FROM emote_usage WHERE usage_type = ");
+            query_builder.push_bind(EmoteUsageType::Reaction);
+            query_builder.push(" AND (");
+
+            let mut separated = query_builder.separated(" OR ");
+            for (message_id, emote_id) in values {
+                separated.push("(message_id = ");
+                separated.push_bind_unseparated(message_id);
+                if let Some(emote_id) = emote_id {
+                    separated.push_unseparated(" AND emote_id = ");
+                    separated.push_bind_unseparated(emote_id);
+                }
+                separated.push_unseparated(")");
+            }
+
+            query_builder.push(")");
+
+            query_builder.build().execute(&database.db).await?;
+        }
+    }
+
     if !batch.is_empty() {
         let mut query_builder = QueryBuilder::<Postgres>::new(
             "INSERT INTO emote_usage (message_id, guild_id, channel_id, emote_id, user_id, \
@@ -0,0 +1,230 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use dashmap::DashMap;
+use lumi::serenity_prelude::{GenericChannelId, GuildId, MessageId, UserId};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+/// The amount of snipes kept per channel, both in memory and in the database.
+pub const SNIPE_RING_SIZE: usize = 50;
+
+#[derive(Debug, Clone, Copy, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "snipe_kind", rename_all = "lowercase")]
+pub enum SnipeKind {
+    Deleted,
+    Edited,
+}
+
+#[derive(Clone, Debug)]
+pub struct Snipe {
+    pub message_id: MessageId,
+    pub user_id: UserId,
+    pub username: String,
+    pub kind: SnipeKind,
+    pub content: String,
+    /// The content before the edit, only set for edits.
+    pub old_content: Option<String>,
+    pub attachment_urls: Vec<String>,
+    /// The message holding the reuploaded attachments in the archive channel.
+    pub archive_message_id: Option<MessageId>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Default)]
+pub struct SnipeRing {
+    /// If the persisted snipes have been loaded into this ring.
+    pub loaded: bool,
+    /// Newest first.
+    pub entries: VecDeque<Arc<Snipe>>,
+}
+
+/// Recently deleted and edited messages per channel.
+#[derive(Default)]
+pub struct SnipeCache {
+    channels: DashMap<GenericChannelId, SnipeRing>,
+}
+
+impl SnipeCache {
+    pub fn push(&self, channel_id: GenericChannelId, snipe: Snipe) {
+        let mut ring = self.channels.entry(channel_id).or_default();
+        ring.entries.push_front(Arc::new(snipe));
+        ring.entries.truncate(SNIPE_RING_SIZE);
+    }
+
+    /// Returns the snipes in a channel if the persisted ones have been loaded.
+    #[must_use]
+    pub fn get(&self, channel_id: GenericChannelId) -> Option<Vec<Arc<Snipe>>> {
+        let ring = self.channels.get(&channel_id)?;
+        ring.loaded.then(|| ring.entries.iter().cloned().collect())
+    }
+
+    /// Fills the ring with the snipes loaded from the database, returning what it now holds.
+    ///
+    /// Snipes pushed while the ring was loading are newer than the persisted ones, so they are
+    /// kept in front instead of being replaced.
+    #[must_use]
+    pub fn set_loaded(
+        &self,
+        channel_id: GenericChannelId,
+        snipes: Vec<Arc<Snipe>>,
+    ) -> Vec<Arc<Snipe>> {
+        let mut ring = self.channels.entry(channel_id).or_default();
+
+        if !ring.loaded {
+            for snipe in snipes {
+                let pushed = ring.entries.iter().any(|s| {
+                    s.message_id == snipe.message_id
+                        && s.kind == snipe.kind
+                        && s.content == snipe.content
+                });
+                if !pushed {
+                    ring.entries.push_back(snipe);
+                }
+            }
+            ring.entries.truncate(SNIPE_RING_SIZE);
+            ring.loaded = true;
+        }
+
+        ring.entries.iter().cloned().collect()
+    }
+
+    /// Returns the snipes currently held in memory for a channel, loaded or not.
+    #[must_use]
+    pub fn recent(&self, channel_id: GenericChannelId) -> Vec<Arc<Snipe>> {
+        self.channels
+            .get(&channel_id)
+            .map(|ring| ring.entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Looks up the author of a deleted message if it was sniped.
+    #[must_use]
+    pub fn author(&self, channel_id: GenericChannelId, message_id: MessageId) -> Option<UserId> {
+        self.channels
+            .get(&channel_id)?
+            .entries
+            .iter()
+            .find(|s| s.message_id == message_id && s.kind == SnipeKind::Deleted)
+            .map(|s| s.user_id)
+    }
+}
+
+impl Database {
+    /// Persists a snipe, trimming the oldest snipes in the channel past [`SNIPE_RING_SIZE`].
+    pub async fn insert_snipe(
+        &self,
+        channel_id: GenericChannelId,
+        guild_id: Option<GuildId>,
+        snipe: &Snipe,
+    ) -> Result<(), Error> {
+        self.insert_snipes(channel_id, guild_id, std::slice::from_ref(snipe))
+            .await
+    }
+
+    /// Persists snipes from the same channel in one transaction, trimming the oldest snipes in the
+    /// channel past [`SNIPE_RING_SIZE`].
+    pub async fn insert_snipes(
+        &self,
+        channel_id: GenericChannelId,
+        guild_id: Option<GuildId>,
+        snipes: &[Snipe],
+    ) -> Result<(), Error> {
+        if snipes.is_empty() {
+            return Ok(());
+        }
+
+        let (channel_id, guild_id) = self.get_channel(channel_id, guild_id).await?;
+
+        let mut user_ids = Vec::with_capacity(snipes.len());
+        for snipe in snipes {
+            user_ids.push(self.get_user(snipe.user_id).await?.id);
+        }
+
+        let mut transaction = self.db.begin().await?;
+
+        for (snipe, user_id) in snipes.iter().zip(user_ids) {
+            query!(
+                r#"
+                INSERT INTO snipes (message_id, channel_id, guild_id, user_id, username, kind,
+                    content, old_content, attachment_urls, archive_message_id, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                "#,
+                snipe.message_id.get() as i64,
+                channel_id,
+                guild_id,
+                user_id,
+                snipe.username,
+                snipe.kind as SnipeKind,
+                snipe.content,
+                snipe.old_content,
+                &snipe.attachment_urls,
+                snipe.archive_message_id.map(|m| m.get() as i64),
+                snipe.created_at,
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        query!(
+            r#"
+            DELETE FROM snipes
+            WHERE channel_id = $1 AND id NOT IN (
+                SELECT id FROM snipes WHERE channel_id = $1 ORDER BY id DESC LIMIT $2
+            )
+            "#,
+            channel_id,
+            SNIPE_RING_SIZE as i64,
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Gets the persisted snipes in a channel, newest first.
+    pub async fn get_snipes(&self, channel_id: GenericChannelId) -> Result<Vec<Snipe>, Error> {
+        let rows = query!(
+            r#"
+            SELECT
+                s.message_id,
+                u.user_id,
+                s.username,
+                s.kind AS "kind: SnipeKind",
+                s.content,
+                s.old_content,
+                s.attachment_urls,
+                s.archive_message_id,
+                s.created_at
+            FROM snipes s
+            JOIN channels c ON c.id = s.channel_id
+            JOIN users u ON u.id = s.user_id
+            WHERE c.channel_id = $1
+            ORDER BY s.id DESC
+            LIMIT $2
+            "#,
+            channel_id.get() as i64,
+            SNIPE_RING_SIZE as i64,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Snipe {
+                message_id: MessageId::new(row.message_id as u64),
+                user_id: UserId::new(row.user_id as u64),
+                username: row.username,
+                kind: row.kind,
+                content: row.content,
+                old_content: row.old_content,
+                attachment_urls: row.attachment_urls,
+                archive_message_id: row.archive_message_id.map(|m| MessageId::new(m as u64)),
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+}
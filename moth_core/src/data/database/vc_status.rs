@@ -0,0 +1,119 @@
+use lumi::serenity_prelude::{ChannelId, GuildId, UserId};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+/// A change to the status of a voice channel.
+#[derive(Clone, Debug)]
+pub struct VcStatusChange {
+    pub id: i32,
+    /// None if the status was removed.
+    pub status: Option<String>,
+    /// None if the change couldn't be found in the audit log.
+    pub user_id: Option<UserId>,
+    pub blacklisted: bool,
+    /// The status was blacklisted and removed by the bot.
+    pub cleared: bool,
+    pub changed_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Database {
+    /// Records a status change of a voice channel, returning its id.
+    pub async fn insert_vc_status_change(
+        &self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        status: Option<&str>,
+        user_id: Option<UserId>,
+        blacklisted: bool,
+    ) -> Result<i32, Error> {
+        let (channel_id, _) = self.get_channel(channel_id.widen(), Some(guild_id)).await?;
+        let guild_id = self.get_guild(guild_id).await?;
+        let user_id = match user_id {
+            Some(user_id) => Some(self.get_user(user_id).await?.id),
+            None => None,
+        };
+
+        let row = query!(
+            "INSERT INTO vc_status_history (guild_id, channel_id, status, user_id, blacklisted, \
+             changed_at) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+            guild_id,
+            channel_id,
+            status,
+            user_id,
+            blacklisted,
+            chrono::Utc::now(),
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(row.id)
+    }
+
+    /// Sets who changed a recorded status, once it has been found in the audit log.
+    pub async fn set_vc_status_user(&self, id: i32, user_id: UserId) -> Result<(), Error> {
+        let user_id = self.get_user(user_id).await?.id;
+
+        query!(
+            "UPDATE vc_status_history SET user_id = $1 WHERE id = $2",
+            user_id,
+            id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks a recorded status as removed by the bot.
+    pub async fn set_vc_status_cleared(&self, id: i32) -> Result<(), Error> {
+        query!(
+            "UPDATE vc_status_history SET cleared = TRUE WHERE id = $1",
+            id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets the most recent status changes of a voice channel, newest first.
+    pub async fn get_vc_status_history(
+        &self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        limit: i64,
+    ) -> Result<Vec<VcStatusChange>, Error> {
+        let rows = query!(
+            r#"
+            SELECT h.id, h.status, u.user_id AS "user_id?", h.blacklisted, h.cleared,
+                   h.changed_at
+            FROM vc_status_history h
+            JOIN guilds g ON g.id = h.guild_id
+            JOIN channels c ON c.id = h.channel_id
+            LEFT JOIN users u ON u.id = h.user_id
+            WHERE g.guild_id = $1 AND c.channel_id = $2
+            ORDER BY h.changed_at DESC
+            LIMIT $3
+            "#,
+            guild_id.get() as i64,
+            channel_id.get() as i64,
+            limit,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| VcStatusChange {
+                id: row.id,
+                status: row.status,
+                user_id: row.user_id.map(|id| UserId::new(id as u64)),
+                blacklisted: row.blacklisted,
+                cleared: row.cleared,
+                changed_at: row.changed_at,
+            })
+            .collect())
+    }
+}
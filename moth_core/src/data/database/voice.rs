@@ -0,0 +1,278 @@
+use lumi::serenity_prelude::{ChannelId, GuildId, UserId};
+use sqlx::query;
+
+use super::Database;
+use crate::data::structs::Error;
+
+#[derive(Clone, Debug)]
+pub struct OpenVoiceSession {
+    pub user_id: UserId,
+    pub channel_id: ChannelId,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Voice time of a user in a single channel.
+#[derive(Clone, Debug)]
+pub struct ChannelVoiceTime {
+    pub channel_id: ChannelId,
+    pub sessions: i64,
+    pub seconds: i64,
+    pub muted_seconds: i64,
+    pub deafened_seconds: i64,
+}
+
+impl Database {
+    /// Opens a voice session, closing any session the user still had open in the guild.
+    pub async fn open_voice_session(
+        &self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        user_id: UserId,
+        muted: bool,
+        deafened: bool,
+    ) -> Result<(), Error> {
+        self.close_voice_session(guild_id, user_id).await?;
+
+        let (channel_id, _) = self.get_channel(channel_id.widen(), Some(guild_id)).await?;
+        let guild_id = self.get_guild(guild_id).await?;
+        let user_id = self.get_user(user_id).await?.id;
+        let now = chrono::Utc::now();
+
+        query!(
+            "INSERT INTO voice_sessions (user_id, guild_id, channel_id, started_at, last_seen_at, \
+             muted_since, deafened_since) VALUES ($1, $2, $3, $4, $4, $5, $6)",
+            user_id,
+            guild_id,
+            channel_id,
+            now,
+            muted.then_some(now),
+            deafened.then_some(now),
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Closes the open voice session of a user in a guild, if there is one.
+    pub async fn close_voice_session(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<(), Error> {
+        query!(
+            r#"
+            UPDATE voice_sessions v SET
+                ended_at = $3,
+                muted_seconds = muted_seconds
+                    + COALESCE(EXTRACT(EPOCH FROM $3 - muted_since)::INT, 0),
+                deafened_seconds = deafened_seconds
+                    + COALESCE(EXTRACT(EPOCH FROM $3 - deafened_since)::INT, 0),
+                muted_since = NULL,
+                deafened_since = NULL
+            FROM users u, guilds g
+            WHERE u.id = v.user_id AND g.id = v.guild_id
+                AND g.guild_id = $1 AND u.user_id = $2 AND v.ended_at IS NULL
+            "#,
+            guild_id.get() as i64,
+            user_id.get() as i64,
+            chrono::Utc::now(),
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Closes the open voice session of a user in a guild at the last time it was seen, for
+    /// sessions that ended while the bot was offline.
+    pub async fn close_stale_voice_session(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<(), Error> {
+        query!(
+            r#"
+            UPDATE voice_sessions v SET
+                ended_at = last_seen_at,
+                muted_seconds = muted_seconds
+                    + COALESCE(EXTRACT(EPOCH FROM last_seen_at - muted_since)::INT, 0),
+                deafened_seconds = deafened_seconds
+                    + COALESCE(EXTRACT(EPOCH FROM last_seen_at - deafened_since)::INT, 0),
+                muted_since = NULL,
+                deafened_since = NULL
+            FROM users u, guilds g
+            WHERE u.id = v.user_id AND g.id = v.guild_id
+                AND g.guild_id = $1 AND u.user_id = $2 AND v.ended_at IS NULL
+            "#,
+            guild_id.get() as i64,
+            user_id.get() as i64,
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks every open voice session as still going.
+    ///
+    /// Sessions that weren't seen in the last few minutes were open before a restart and are left
+    /// alone, so they can still be closed where the bot last saw them.
+    pub async fn touch_voice_sessions(&self) -> Result<(), Error> {
+        query!(
+            "UPDATE voice_sessions SET last_seen_at = $1 WHERE ended_at IS NULL AND last_seen_at \
+             > $1::TIMESTAMPTZ - INTERVAL '3 minutes'",
+            chrono::Utc::now(),
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Updates the mute and deafen state of an open voice session.
+    pub async fn update_voice_session_state(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        muted: bool,
+        deafened: bool,
+    ) -> Result<(), Error> {
+        query!(
+            r#"
+            UPDATE voice_sessions v SET
+                muted_seconds = muted_seconds + CASE WHEN $3 THEN 0
+                    ELSE COALESCE(EXTRACT(EPOCH FROM $5 - muted_since)::INT, 0) END,
+                muted_since = CASE WHEN $3 THEN COALESCE(muted_since, $5) ELSE NULL END,
+                deafened_seconds = deafened_seconds + CASE WHEN $4 THEN 0
+                    ELSE COALESCE(EXTRACT(EPOCH FROM $5 - deafened_since)::INT, 0) END,
+                deafened_since = CASE WHEN $4 THEN COALESCE(deafened_since, $5) ELSE NULL END,
+                last_seen_at = $5
+            FROM users u, guilds g
+            WHERE u.id = v.user_id AND g.id = v.guild_id
+                AND g.guild_id = $1 AND u.user_id = $2 AND v.ended_at IS NULL
+            "#,
+            guild_id.get() as i64,
+            user_id.get() as i64,
+            muted,
+            deafened,
+            chrono::Utc::now(),
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets the currently open voice sessions in a guild, oldest first.
+    pub async fn get_open_voice_sessions(
+        &self,
+        guild_id: GuildId,
+    ) -> Result<Vec<OpenVoiceSession>, Error> {
+        let rows = query!(
+            r#"
+            SELECT u.user_id, c.channel_id, v.started_at
+            FROM voice_sessions v
+            JOIN users u ON u.id = v.user_id
+            JOIN guilds g ON g.id = v.guild_id
+            JOIN channels c ON c.id = v.channel_id
+            WHERE g.guild_id = $1 AND v.ended_at IS NULL
+            ORDER BY v.started_at
+            "#,
+            guild_id.get() as i64,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| OpenVoiceSession {
+                user_id: UserId::new(row.user_id as u64),
+                channel_id: ChannelId::new(row.channel_id as u64),
+                started_at: row.started_at,
+            })
+            .collect())
+    }
+
+    /// Gets the voice time of a user in each channel of a guild, most time first.
+    ///
+    /// Open sessions count up to now.
+    pub async fn get_voice_time(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Vec<ChannelVoiceTime>, Error> {
+        let rows = query!(
+            r#"
+            SELECT
+                c.channel_id,
+                COUNT(*) AS "sessions!",
+                SUM(EXTRACT(EPOCH FROM COALESCE(v.ended_at, NOW()) - v.started_at))::BIGINT
+                    AS "seconds!",
+                SUM(v.muted_seconds
+                    + COALESCE(EXTRACT(EPOCH FROM NOW() - v.muted_since)::INT, 0))::BIGINT
+                    AS "muted_seconds!",
+                SUM(v.deafened_seconds
+                    + COALESCE(EXTRACT(EPOCH FROM NOW() - v.deafened_since)::INT, 0))::BIGINT
+                    AS "deafened_seconds!"
+            FROM voice_sessions v
+            JOIN users u ON u.id = v.user_id
+            JOIN guilds g ON g.id = v.guild_id
+            JOIN channels c ON c.id = v.channel_id
+            WHERE g.guild_id = $1 AND u.user_id = $2
+            GROUP BY c.channel_id
+            ORDER BY 3 DESC
+            "#,
+            guild_id.get() as i64,
+            user_id.get() as i64,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ChannelVoiceTime {
+                channel_id: ChannelId::new(row.channel_id as u64),
+                sessions: row.sessions,
+                seconds: row.seconds,
+                muted_seconds: row.muted_seconds,
+                deafened_seconds: row.deafened_seconds,
+            })
+            .collect())
+    }
+
+    /// Gets the users with the most voice time in a guild, or a single channel of it.
+    pub async fn get_voice_leaderboard(
+        &self,
+        guild_id: GuildId,
+        channel_id: Option<ChannelId>,
+        limit: i64,
+    ) -> Result<Vec<(UserId, i64)>, Error> {
+        let rows = query!(
+            r#"
+            SELECT
+                u.user_id,
+                SUM(EXTRACT(EPOCH FROM COALESCE(v.ended_at, NOW()) - v.started_at))::BIGINT
+                    AS "seconds!"
+            FROM voice_sessions v
+            JOIN users u ON u.id = v.user_id
+            JOIN guilds g ON g.id = v.guild_id
+            JOIN channels c ON c.id = v.channel_id
+            WHERE g.guild_id = $1 AND ($2::BIGINT IS NULL OR c.channel_id = $2)
+            GROUP BY u.user_id
+            ORDER BY 2 DESC
+            LIMIT $3
+            "#,
+            guild_id.get() as i64,
+            channel_id.map(|c| c.get() as i64),
+            limit,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (UserId::new(row.user_id as u64), row.seconds))
+            .collect())
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::TimeZone;
 use dashmap::DashMap;
-use parking_lot::{Mutex, RwLock};
+use parking_lot::RwLock;
 use rosu_v2::Osu;
 use serenity::all::{ChannelId, Member, RoleId, SecretString};
 use std::{
@@ -11,10 +11,12 @@ use std::{
 
 use lumi::serenity_prelude::{GenericChannelId, GuildId, MessageId, UserId};
 
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
 
 use crate::{
-    data::database::{private_vcs::PrivateVcHandler, reactions::EmoteProcessor},
+    data::database::{
+        private_vcs::PrivateVcHandler, reactions::EmoteProcessor, snipes::SnipeCache,
+    },
     standby::AuthenticationStandby,
     verification::{sender::VerificationSender, task},
 };
@@ -42,10 +44,17 @@ pub struct Data {
     pub starboard_config: StarboardConfig,
     /// ugh
     pub new_join_vc: DashMap<UserId, Fuck>,
-    /// ugh 2
-    pub osu_game_joins: Mutex<VecDeque<UserId>>,
+    /// Recent joins and lockdown state for raid detection.
+    pub raid: RaidTracker,
+    /// Recent destructive actions per executor for anti nuke.
+    pub anti_nuke: AntiNukeTracker,
+    /// Recent posts per member, for spam detection.
+    pub spam: SpamTracker,
+    /// Cached lookups of invite codes posted in messages.
+    pub invite_lookups: InviteLookups,
     pub web: WebServer,
-    pub auto_pooped: dashmap::DashSet<UserId>,
+    /// The appearance role rules applied to each member, by rule id.
+    pub appearance_roles: dashmap::DashSet<(i32, UserId)>,
     pub emote_processor: EmoteProcessor,
     pub private_vc: PrivateVcHandler,
 }
@@ -153,15 +162,151 @@ impl DmActivity {
     }
 }
 
+/// A member that joined recently, scored by the raid signals they matched.
+#[derive(Clone, Debug)]
+pub struct RecentJoin {
+    pub user_id: UserId,
+    pub joined_at: Instant,
+    pub name: String,
+    /// The invite code used to join, if it could be worked out.
+    pub invite: Option<String>,
+    pub score: u32,
+}
+
+#[derive(Default)]
+pub struct RaidTracker {
+    /// Recent joins per guild, oldest first.
+    pub joins: DashMap<GuildId, VecDeque<RecentJoin>>,
+    /// Guilds with an ongoing raid, alerts and lockdowns aren't repeated until it ends.
+    pub active: dashmap::DashSet<GuildId>,
+    /// Invites per guild by code, their uses are diffed on join to work out the invite used.
+    pub invites: DashMap<GuildId, HashMap<String, TrackedInvite>>,
+    /// Held while diffing the invites of a guild, so concurrent joins don't race on the cache.
+    pub invite_locks: DashMap<GuildId, Arc<tokio::sync::Mutex<()>>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TrackedInvite {
+    pub uses: u64,
+    /// Zero if the invite has unlimited uses.
+    pub max_uses: u64,
+    pub inviter: Option<UserId>,
+}
+
+/// A channel deletion, role deletion or ban seen in the audit log.
+#[derive(Clone, Debug)]
+pub struct DestructiveAction {
+    pub at: Instant,
+    pub kind: &'static str,
+    /// The channel, role or user acted on.
+    pub target_id: u64,
+}
+
+#[derive(Default)]
+pub struct AntiNukeTracker {
+    /// Recent destructive actions per guild and executor, oldest first.
+    pub actions: DashMap<(GuildId, UserId), VecDeque<DestructiveAction>>,
+    /// Executors that already had an incident, so it isn't repeated until the window passes.
+    pub handled: DashMap<(GuildId, UserId), OpenIncident>,
+}
+
+/// An incident that later actions from the same executor are attached to.
+#[derive(Default)]
+pub struct OpenIncident {
+    /// `None` until the incident is recorded.
+    pub incident_id: Option<i32>,
+    /// Targets of actions seen before the incident was recorded.
+    pub pending: Vec<u64>,
+}
+
+/// Something posted in a message, hashed so copies of it in other channels can be found.
+#[derive(Clone, Copy, Debug)]
+pub enum SpamPayload {
+    Content(u64),
+    /// The size and name of a file, with a perceptual hash if it's an image.
+    Attachment {
+        size: u32,
+        name: u64,
+        image: Option<u64>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct RecentPost {
+    pub at: Instant,
+    pub channel_id: GenericChannelId,
+    pub message_id: MessageId,
+    pub payloads: Vec<SpamPayload>,
+}
+
+/// The mentions in a single message.
+#[derive(Clone, Debug)]
+pub struct RecentMentions {
+    pub at: Instant,
+    pub channel_id: GenericChannelId,
+    pub message_id: MessageId,
+    pub users: Vec<UserId>,
+    pub roles: Vec<RoleId>,
+    /// Attempts at @everyone or @here, whether or not they pinged anyone.
+    pub everyone: usize,
+}
+
+#[derive(Default)]
+pub struct SpamTracker {
+    /// Recent posts per guild and member, oldest first.
+    pub posts: DashMap<(GuildId, UserId), VecDeque<RecentPost>>,
+    /// Recent messages with mentions per guild and member, oldest first.
+    pub mentions: DashMap<(GuildId, UserId), VecDeque<RecentMentions>>,
+}
+
+impl SpamTracker {
+    /// Drops members that haven't posted recently, the handlers only prune members that post.
+    pub fn prune(&self, max_age: Duration) {
+        self.posts
+            .retain(|_, posts| posts.back().is_some_and(|p| p.at.elapsed() < max_age));
+        self.mentions
+            .retain(|_, mentions| mentions.back().is_some_and(|m| m.at.elapsed() < max_age));
+    }
+}
+
+/// The server an invite code points to.
+#[derive(Clone, Debug)]
+pub struct InviteLookup {
+    pub code: String,
+    pub guild_id: GuildId,
+    pub guild_name: String,
+    pub icon_url: Option<String>,
+    pub members: Option<u64>,
+    pub online: Option<u64>,
+}
+
+pub struct InviteLookups {
+    /// Lookups by code, invalid or expired codes are cached as `None`.
+    pub cache: mini_moka::sync::Cache<String, Option<InviteLookup>>,
+}
+
+impl Default for InviteLookups {
+    fn default() -> Self {
+        Self {
+            cache: mini_moka::sync::CacheBuilder::new(2_000)
+                .time_to_live(Duration::from_secs(3600))
+                .build(),
+        }
+    }
+}
+
 pub const ANTI_DELETE_CACHE_CYCLE_TIME: Duration = Duration::from_mins(10);
-pub const ANTI_DELETE_MAX_DELETES_PER_CYCLE: usize = 250;
 #[derive(Default)]
 pub struct AntiDeleteCache {
     pub val: DashMap<GuildId, Decay>,
     // Dashmap using guild key, containing the last deleted msg and a hashmap of stored message ids.
     pub map: DashMap<GuildId, InnerCache>,
-    // this may get a lot of writes very fast, std rwlock has reader bias which can choke writers so use fairer tokio rwlock
-    pub deletes_per_cycle: tokio::sync::RwLock<usize>,
+    // this may get a lot of writes very fast, so keep it lock free.
+    pub deletes_per_cycle: AtomicUsize,
+    /// When an incident was last recorded for a user, used to debounce incidents.
+    pub incidents: DashMap<(GuildId, UserId), Instant>,
+    /// Recently deleted and edited messages, also used to resolve the author of deletions.
+    pub snipes: SnipeCache,
 }
 pub struct InnerCache {
     pub last_deleted_msg: MessageId,
@@ -0,0 +1,387 @@
+use std::fmt::Write;
+
+use lumi::serenity_prelude::{Attachment, AttachmentId};
+use moth_ansi::{BOLD, DIM, HI_GREEN, RED, RESET};
+
+/// Word level diffs between two versions of a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change<'a> {
+    Equal(&'a str),
+    Insert(&'a str),
+    Delete(&'a str),
+}
+
+// Past this many changed words the table gets too big, so they are treated as replaced. The
+// table is a u16 per pair of words, so this keeps it around half a megabyte.
+const MAX_WORDS: usize = 500;
+
+/// Splits content into words, leading whitespace stays attached to the word that follows it.
+fn tokenize(content: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_word = false;
+
+    for (index, c) in content.char_indices() {
+        if c.is_whitespace() {
+            if in_word {
+                tokens.push(&content[start..index]);
+                start = index;
+                in_word = false;
+            }
+        } else {
+            in_word = true;
+        }
+    }
+
+    if start < content.len() {
+        tokens.push(&content[start..]);
+    }
+
+    tokens
+}
+
+/// Computes the word level diff between `old` and `new` using the longest common subsequence.
+///
+/// The common prefix and suffix are matched first, so only the changed middle goes in the table.
+#[must_use]
+pub fn diff_words<'a>(old: &'a str, new: &'a str) -> Vec<Change<'a>> {
+    let old_words = tokenize(old);
+    let new_words = tokenize(new);
+
+    let same = |a: &str, b: &str| a.trim_start() == b.trim_start();
+
+    let prefix = old_words
+        .iter()
+        .zip(&new_words)
+        .take_while(|(a, b)| same(a, b))
+        .count();
+    let suffix = old_words[prefix..]
+        .iter()
+        .rev()
+        .zip(new_words[prefix..].iter().rev())
+        .take_while(|(a, b)| same(a, b))
+        .count();
+
+    let old_middle = &old_words[prefix..old_words.len() - suffix];
+    let new_middle = &new_words[prefix..new_words.len() - suffix];
+
+    let mut changes = Vec::with_capacity(old_words.len().max(new_words.len()));
+    changes.extend(new_words[..prefix].iter().map(|w| Change::Equal(w)));
+
+    if old_middle.len() > MAX_WORDS || new_middle.len() > MAX_WORDS {
+        changes.extend(old_middle.iter().map(|w| Change::Delete(w)));
+        changes.extend(new_middle.iter().map(|w| Change::Insert(w)));
+    } else {
+        diff_middle(old_middle, new_middle, &mut changes);
+    }
+
+    changes.extend(
+        new_words[new_words.len() - suffix..]
+            .iter()
+            .map(|w| Change::Equal(w)),
+    );
+
+    changes
+}
+
+fn diff_middle<'a>(old_words: &[&'a str], new_words: &[&'a str], changes: &mut Vec<Change<'a>>) {
+    let (n, m) = (old_words.len(), new_words.len());
+
+    // lengths[i][j] is the lcs of old_words[i..] and new_words[j..]
+    let mut lengths = vec![0_u16; (n + 1) * (m + 1)];
+    let index = |i: usize, j: usize| i * (m + 1) + j;
+
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[index(i, j)] = if old_words[i].trim_start() == new_words[j].trim_start() {
+                lengths[index(i + 1, j + 1)] + 1
+            } else {
+                lengths[index(i + 1, j)].max(lengths[index(i, j + 1)])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old_words[i].trim_start() == new_words[j].trim_start() {
+            changes.push(Change::Equal(new_words[j]));
+            i += 1;
+            j += 1;
+        } else if lengths[index(i + 1, j)] >= lengths[index(i, j + 1)] {
+            changes.push(Change::Delete(old_words[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Insert(new_words[j]));
+            j += 1;
+        }
+    }
+
+    changes.extend(old_words[i..].iter().map(|w| Change::Delete(w)));
+    changes.extend(new_words[j..].iter().map(|w| Change::Insert(w)));
+}
+
+/// Returns true if anything other than whitespace changed.
+#[must_use]
+pub fn has_changes(changes: &[Change<'_>]) -> bool {
+    // words keep their leading whitespace, so compare them trimmed and skip whitespace only ones.
+    let old = changes.iter().filter_map(|c| match c {
+        Change::Equal(word) | Change::Delete(word) => Some(word.trim()),
+        Change::Insert(_) => None,
+    });
+    let new = changes.iter().filter_map(|c| match c {
+        Change::Equal(word) | Change::Insert(word) => Some(word.trim()),
+        Change::Delete(_) => None,
+    });
+
+    !old.filter(|word| !word.is_empty())
+        .eq(new.filter(|word| !word.is_empty()))
+}
+
+/// Renders the diff for the console, removed words are red and struck through, inserted words
+/// are bold and green.
+#[must_use]
+pub fn render_ansi(changes: &[Change<'_>]) -> String {
+    const STRIKE: &str = "\x1B[9m";
+
+    let mut result = String::new();
+    for change in changes {
+        match change {
+            Change::Equal(word) => result.push_str(word),
+            Change::Insert(word) => {
+                let (space, word) = split_space(word);
+                write!(result, "{space}{BOLD}{HI_GREEN}{word}{RESET}").unwrap();
+            }
+            Change::Delete(word) => {
+                let (space, word) = split_space(word);
+                write!(result, "{space}{DIM}{RED}{STRIKE}{word}{RESET}").unwrap();
+            }
+        }
+    }
+
+    result
+}
+
+/// Renders the diff as discord markdown in at most `max` characters, removed runs are struck
+/// through and inserted runs are bold.
+///
+/// When cut short the output ends in an ellipsis, a cut run keeps its closing marker.
+#[must_use]
+pub fn render_markdown(changes: &[Change<'_>], max: usize) -> String {
+    let mut result = String::new();
+    // one character is always kept spare for the ellipsis.
+    let mut remaining = max.saturating_sub(1);
+    let mut index = 0;
+
+    while index < changes.len() {
+        match changes[index] {
+            Change::Equal(word) => {
+                let word = escape_markdown(word);
+                let length = word.chars().count();
+                if length > remaining {
+                    result.push('…');
+                    return result;
+                }
+
+                result.push_str(&word);
+                remaining -= length;
+                index += 1;
+            }
+            Change::Insert(_) | Change::Delete(_) => {
+                let is_insert = matches!(changes[index], Change::Insert(_));
+                let mut run = String::new();
+
+                while let Some(change) = changes.get(index) {
+                    match (change, is_insert) {
+                        (Change::Insert(word), true) | (Change::Delete(word), false) => {
+                            run.push_str(word);
+                            index += 1;
+                        }
+                        _ => break,
+                    }
+                }
+
+                let (space, run) = split_space(&run);
+                let marker = if is_insert { "**" } else { "~~" };
+                let overhead = space.chars().count() + marker.len() * 2;
+
+                let escaped = escape_markdown(run);
+                if overhead + escaped.chars().count() <= remaining {
+                    write!(result, "{space}{marker}{escaped}{marker}").unwrap();
+                    remaining -= overhead + escaped.chars().count();
+                    continue;
+                }
+
+                // cut inside the run, escaping per character so an escape is never split.
+                let Some(mut available) = remaining.checked_sub(overhead).filter(|a| *a > 0) else {
+                    result.push('…');
+                    return result;
+                };
+
+                let mut partial = String::new();
+                for c in run.chars() {
+                    let escaped = escape_markdown(c.encode_utf8(&mut [0; 4]));
+                    let length = escaped.chars().count();
+                    if length > available {
+                        break;
+                    }
+                    partial.push_str(&escaped);
+                    available -= length;
+                }
+
+                write!(result, "{space}{marker}{partial}…{marker}").unwrap();
+                return result;
+            }
+        }
+    }
+
+    result
+}
+
+fn split_space(word: &str) -> (&str, &str) {
+    let trimmed = word.trim_start();
+    (&word[..word.len() - trimmed.len()], trimmed)
+}
+
+fn escape_markdown(content: &str) -> String {
+    let mut escaped = String::with_capacity(content.len());
+    for c in content.chars() {
+        // formatting, plus the quotes, headers, lists and masked links that start with these.
+        if matches!(
+            c,
+            '*' | '_' | '~' | '`' | '|' | '\\' | '>' | '#' | '-' | '[' | ']'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Attachments that were removed or added between two versions of a message.
+pub struct AttachmentChanges<'a> {
+    pub removed: Vec<&'a Attachment>,
+    pub added: Vec<&'a Attachment>,
+}
+
+impl AttachmentChanges<'_> {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+
+    /// Formats the changes for the console.
+    #[must_use]
+    pub fn ansi(&self) -> String {
+        let mut result = String::new();
+        for attachment in &self.removed {
+            write!(result, " {RED}-<{}>{RESET}", attachment.filename).unwrap();
+        }
+        for attachment in &self.added {
+            write!(result, " {HI_GREEN}+<{}>{RESET}", attachment.filename).unwrap();
+        }
+        result
+    }
+
+    /// Formats the changes for discord, one attachment per line.
+    #[must_use]
+    pub fn markdown(&self) -> String {
+        let mut result = String::new();
+        for attachment in &self.removed {
+            writeln!(result, "- ~~{}~~ ({})", attachment.filename, attachment.url).unwrap();
+        }
+        for attachment in &self.added {
+            writeln!(result, "+ **{}** ({})", attachment.filename, attachment.url).unwrap();
+        }
+        result
+    }
+}
+
+/// Compares attachments by id, as discord only allows removing attachments on edit.
+#[must_use]
+pub fn diff_attachments<'a>(old: &'a [Attachment], new: &'a [Attachment]) -> AttachmentChanges<'a> {
+    let contains = |list: &[Attachment], id: AttachmentId| list.iter().any(|a| a.id == id);
+
+    AttachmentChanges {
+        removed: old.iter().filter(|a| !contains(new, a.id)).collect(),
+        added: new.iter().filter(|a| !contains(old, a.id)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_keeps_unchanged_words() {
+        let changes = diff_words("the quick fox", "the slow fox");
+        assert_eq!(
+            changes,
+            [
+                Change::Equal("the"),
+                Change::Delete(" quick"),
+                Change::Insert(" slow"),
+                Change::Equal(" fox"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_replaces_long_changes() {
+        let old = vec!["a"; MAX_WORDS + 1].join(" ");
+        let new = vec!["b"; MAX_WORDS + 1].join(" ");
+        let changes = diff_words(&old, &new);
+
+        assert!(!changes.iter().any(|c| matches!(c, Change::Equal(_))));
+        assert_eq!(changes.len(), (MAX_WORDS + 1) * 2);
+    }
+
+    #[test]
+    fn diff_matches_prefix_and_suffix_past_the_cap() {
+        let common = "word ".repeat(MAX_WORDS * 2);
+        let old = format!("{common}old {common}");
+        let new = format!("{common}new {common}");
+        let changes = diff_words(&old, &new);
+
+        let changed = changes
+            .iter()
+            .filter(|c| !matches!(c, Change::Equal(_)))
+            .count();
+        assert_eq!(changed, 2);
+    }
+
+    #[test]
+    fn whitespace_only_edits_have_no_changes() {
+        assert!(!has_changes(&diff_words("hello world", "hello  world ")));
+        assert!(!has_changes(&diff_words("hello\nworld", "hello world")));
+        assert!(has_changes(&diff_words("hello world", "hello there")));
+    }
+
+    #[test]
+    fn markdown_marks_runs() {
+        let changes = diff_words("one two three", "one four three");
+        assert_eq!(
+            render_markdown(&changes, 1024),
+            "one ~~two~~ **four** three"
+        );
+    }
+
+    #[test]
+    fn markdown_escapes_formatting() {
+        assert_eq!(
+            escape_markdown("*a* _b_ ~c~ `d` |e| \\ > # - [f]"),
+            "\\*a\\* \\_b\\_ \\~c\\~ \\`d\\` \\|e\\| \\\\ \\> \\# \\- \\[f\\]"
+        );
+    }
+
+    #[test]
+    fn markdown_cut_keeps_markers_balanced() {
+        let changes = diff_words("keep", &format!("keep {}", "*".repeat(100)));
+
+        for max in 1..60 {
+            let rendered = render_markdown(&changes, max);
+            assert!(rendered.chars().count() <= max, "{rendered} is over {max}");
+            assert_eq!(rendered.matches("**").count() % 2, 0, "{rendered}");
+            assert!(!rendered.ends_with("\\**"), "{rendered}");
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use std::sync::Arc;
+
+use crate::{Data, Error};
+use lumi::serenity_prelude::{
+    self as serenity, CreateMessage, GuildId, GuildMemberUpdateEvent, Message, RoleId, SkuId, User,
+    UserId,
+};
+use moth_core::data::database::appearance_roles::AppearanceRoleRule;
+
+/// What a member is wearing, taken from a message or a member update.
+struct Appearance<'a> {
+    user: &'a User,
+    /// The decoration set only for this guild.
+    member_decoration: Option<SkuId>,
+    /// The hash of the guild avatar.
+    member_avatar: Option<String>,
+}
+
+/// Applies the appearance role rules to the author of a message.
+pub async fn check_message(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    msg: &Message,
+) -> Result<(), Error> {
+    let (Some(guild_id), Some(member)) = (msg.guild_id, &msg.member) else {
+        return Ok(());
+    };
+
+    let appearance = Appearance {
+        user: &msg.author,
+        member_decoration: member.avatar_decoration_data.map(|d| d.sku_id),
+        member_avatar: member.avatar.map(|hash| hash.to_string()),
+    };
+
+    reconcile(ctx, data, guild_id, &appearance, &member.roles, Some(msg)).await
+}
+
+/// Applies the appearance role rules to a member that was updated.
+pub async fn check_member(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    event: &GuildMemberUpdateEvent,
+) -> Result<(), Error> {
+    // the cached member may predate the update, so only the event itself is trusted.
+    let appearance = Appearance {
+        user: &event.user,
+        member_decoration: event.avatar_decoration_data.map(|d| d.sku_id),
+        member_avatar: event.avatar.map(|hash| hash.to_string()),
+    };
+
+    reconcile(ctx, data, event.guild_id, &appearance, &event.roles, None).await
+}
+
+async fn reconcile(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    guild_id: GuildId,
+    appearance: &Appearance<'_>,
+    roles: &[RoleId],
+    msg: Option<&Message>,
+) -> Result<(), Error> {
+    let rules = data.database.get_appearance_role_rules(guild_id).await?;
+
+    for rule in rules.iter() {
+        // one broken rule, like a deleted role, shouldn't stop the others.
+        if let Err(e) = reconcile_rule(ctx, data, guild_id, rule, appearance, roles, msg).await {
+            println!("Failed to apply the {} appearance rule: {e}", rule.name);
+        }
+    }
+
+    Ok(())
+}
+
+async fn reconcile_rule(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    guild_id: GuildId,
+    rule: &AppearanceRoleRule,
+    appearance: &Appearance<'_>,
+    roles: &[RoleId],
+    msg: Option<&Message>,
+) -> Result<(), Error> {
+    let user_id = appearance.user.id;
+    let assigned = data.appearance_roles.contains(&(rule.id, user_id));
+    let matched = matches(rule, appearance);
+
+    let missing = rule
+        .add_roles
+        .iter()
+        .filter(|r| !roles.contains(r))
+        .copied()
+        .collect::<Vec<_>>();
+    let unwanted = rule
+        .remove_roles
+        .iter()
+        .filter(|r| roles.contains(r))
+        .copied()
+        .collect::<Vec<_>>();
+
+    if matched && (!missing.is_empty() || !unwanted.is_empty()) {
+        // an unassigned member already in sync had the roles handed out by someone else, so
+        // they are left alone, same as when they stop matching.
+        let reason = format!("Matched the {} appearance rule.", rule.name);
+        for role in missing {
+            ctx.http
+                .add_member_role(guild_id, user_id, role, Some(&reason))
+                .await?;
+        }
+        for role in unwanted {
+            ctx.http
+                .remove_member_role(guild_id, user_id, role, Some(&reason))
+                .await?;
+        }
+
+        if !assigned {
+            data.database
+                .insert_appearance_role_assignment(rule.id, user_id)
+                .await?;
+            data.appearance_roles.insert((rule.id, user_id));
+        }
+
+        if let Some(msg) = msg
+            && let Some(reply) = &rule.reply_message
+        {
+            msg.channel_id
+                .send_message(
+                    &ctx.http,
+                    CreateMessage::new()
+                        .content(fill_template(reply, rule, user_id, Some(msg)))
+                        .reference_message(msg),
+                )
+                .await?;
+        }
+
+        // already assigned but out of sync means the roles were lost, usually by rejoining.
+        let template = if assigned {
+            &rule.restored_message
+        } else {
+            &rule.added_message
+        };
+        announce(ctx, rule, template.as_deref(), user_id, msg).await?;
+    } else if !matched && assigned {
+        let reason = format!("No longer matches the {} appearance rule.", rule.name);
+        for role in rule.add_roles.iter().filter(|r| roles.contains(r)) {
+            ctx.http
+                .remove_member_role(guild_id, user_id, *role, Some(&reason))
+                .await?;
+        }
+
+        data.database
+            .delete_appearance_role_assignment(rule.id, user_id)
+            .await?;
+        data.appearance_roles.remove(&(rule.id, user_id));
+
+        announce(ctx, rule, rule.removed_message.as_deref(), user_id, msg).await?;
+    }
+
+    Ok(())
+}
+
+fn matches(rule: &AppearanceRoleRule, appearance: &Appearance<'_>) -> bool {
+    let user = appearance.user;
+
+    let user_decoration = user.avatar_decoration_data.map(|d| d.sku_id);
+    if [user_decoration, appearance.member_decoration]
+        .iter()
+        .flatten()
+        .any(|sku| rule.decoration_skus.contains(sku))
+    {
+        return true;
+    }
+
+    if let Some(nameplate) = user
+        .collectibles
+        .as_ref()
+        .and_then(|c| c.nameplate.as_ref())
+        && rule
+            .nameplate_assets
+            .iter()
+            .any(|asset| asset.as_str() == &*nameplate.asset)
+    {
+        return true;
+    }
+
+    if let Some(tag) = user.primary_guild.as_ref().and_then(|p| p.tag.as_deref())
+        && rule.guild_tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    {
+        return true;
+    }
+
+    let user_avatar = user.avatar.map(|hash| hash.to_string());
+    [user_avatar.as_ref(), appearance.member_avatar.as_ref()]
+        .iter()
+        .flatten()
+        .any(|hash| rule.avatar_hashes.contains(hash))
+}
+
+async fn announce(
+    ctx: &serenity::Context,
+    rule: &AppearanceRoleRule,
+    template: Option<&str>,
+    user_id: UserId,
+    msg: Option<&Message>,
+) -> Result<(), Error> {
+    let (Some(channel), Some(template)) = (rule.announce_channel, template) else {
+        return Ok(());
+    };
+
+    channel
+        .send_message(
+            &ctx.http,
+            CreateMessage::new().content(fill_template(template, rule, user_id, msg)),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Fills in `{user}`, `{rule}` and `{link}`, the link being empty without a message.
+fn fill_template(
+    template: &str,
+    rule: &AppearanceRoleRule,
+    user_id: UserId,
+    msg: Option<&Message>,
+) -> String {
+    template
+        .replace("{user}", &format!("<@{user_id}>"))
+        .replace("{rule}", &rule.name)
+        .replace("{link}", &msg.map(Message::link).unwrap_or_default())
+        .trim()
+        .to_string()
+}
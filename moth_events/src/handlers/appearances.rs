@@ -0,0 +1,172 @@
+use std::{path::Path, sync::Arc};
+
+use crate::{Data, Error};
+use lumi::serenity_prelude::{GuildId, Member, SkuId, User, UserId};
+use moth_core::data::database::appearances::{
+    APPEARANCE_ARCHIVE_PATH, AppearanceKey, AppearanceKind,
+};
+
+const CDN: &str = "https://cdn.discordapp.com";
+
+/// How many local copies are kept per user, older ones are deleted to bound the archive.
+const ARCHIVED_PER_USER: i64 = 25;
+
+/// Records the avatar, avatar decoration and nameplate of a user.
+pub async fn record_user(data: &Arc<Data>, user: &User) {
+    let avatar = user.avatar.map(|hash| hash.to_string());
+    record(data, (user.id, None, AppearanceKind::Avatar), avatar, None).await;
+
+    let decoration = user.avatar_decoration_data;
+    record(
+        data,
+        (user.id, None, AppearanceKind::Decoration),
+        decoration.map(|d| d.asset.to_string()),
+        decoration.map(|d| d.sku_id),
+    )
+    .await;
+
+    let nameplate = user
+        .collectibles
+        .as_ref()
+        .and_then(|c| c.nameplate.as_ref());
+    record(
+        data,
+        (user.id, None, AppearanceKind::Nameplate),
+        nameplate.map(|n| n.asset.to_string()),
+        nameplate.map(|n| n.sku_id),
+    )
+    .await;
+}
+
+/// Records the appearance of a member, including their guild avatar.
+pub async fn record_member(data: &Arc<Data>, member: &Member) {
+    record_user(data, &member.user).await;
+
+    record(
+        data,
+        (
+            member.user.id,
+            Some(member.guild_id),
+            AppearanceKind::GuildAvatar,
+        ),
+        member.avatar.map(|hash| hash.to_string()),
+        None,
+    )
+    .await;
+}
+
+/// Records an appearance, downloading a local copy of the image in the background if it's new.
+async fn record(
+    data: &Arc<Data>,
+    key: AppearanceKey,
+    asset: Option<String>,
+    sku_id: Option<SkuId>,
+) {
+    let id = match data
+        .database
+        .insert_appearance(key, asset.as_deref(), sku_id)
+        .await
+    {
+        Ok(Some(id)) => id,
+        Ok(None) => return,
+        Err(e) => {
+            println!("Failed to record appearance: {e}");
+            return;
+        }
+    };
+
+    let Some(asset) = asset else {
+        return;
+    };
+
+    let (user_id, guild_id, kind) = key;
+    let url = cdn_url(user_id, guild_id, kind, &asset);
+
+    let data = data.clone();
+    tokio::spawn(async move {
+        if let Err(e) = archive(&data, id, user_id, kind, &url).await {
+            println!("Failed to archive appearance: {e}");
+        }
+    });
+}
+
+async fn archive(
+    data: &Data,
+    id: i32,
+    user_id: UserId,
+    kind: AppearanceKind,
+    url: &str,
+) -> Result<(), Error> {
+    let bytes = data
+        .reqwest
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    let extension = url
+        .split('?')
+        .next()
+        .and_then(|path| path.rsplit('.').next())
+        .unwrap_or("png");
+
+    let directory = Path::new(APPEARANCE_ARCHIVE_PATH).join(user_id.to_string());
+    tokio::fs::create_dir_all(&directory).await?;
+
+    let path = directory.join(format!("{id}-{}.{extension}", kind_slug(kind)));
+    tokio::fs::write(&path, &bytes).await?;
+
+    data.database
+        .set_appearance_file(id, &path.to_string_lossy())
+        .await?;
+
+    let stale = data
+        .database
+        .take_old_appearance_files(user_id, ARCHIVED_PER_USER)
+        .await?;
+    for path in stale {
+        if let Err(e) = tokio::fs::remove_file(&path).await {
+            println!("Failed to remove archived appearance {path}: {e}");
+        }
+    }
+
+    Ok(())
+}
+
+fn kind_slug(kind: AppearanceKind) -> &'static str {
+    match kind {
+        AppearanceKind::Avatar => "avatar",
+        AppearanceKind::GuildAvatar => "guild_avatar",
+        AppearanceKind::Decoration => "decoration",
+        AppearanceKind::Nameplate => "nameplate",
+    }
+}
+
+fn ext(hash: &str) -> &'static str {
+    if hash.starts_with("a_") { "gif" } else { "png" }
+}
+
+/// The cdn url of an appearance, used when there is no local copy.
+#[must_use]
+pub fn cdn_url(
+    user_id: UserId,
+    guild_id: Option<GuildId>,
+    kind: AppearanceKind,
+    asset: &str,
+) -> String {
+    match (kind, guild_id) {
+        (AppearanceKind::GuildAvatar, Some(guild_id)) => format!(
+            "{CDN}/guilds/{guild_id}/users/{user_id}/avatars/{asset}.{}?size=1024",
+            ext(asset)
+        ),
+        (AppearanceKind::Avatar | AppearanceKind::GuildAvatar, _) => {
+            format!("{CDN}/avatars/{user_id}/{asset}.{}?size=1024", ext(asset))
+        }
+        (AppearanceKind::Decoration, _) => {
+            format!("{CDN}/avatar-decoration-presets/{asset}.png?size=240&passthrough=true")
+        }
+        (AppearanceKind::Nameplate, _) => format!("{CDN}/assets/collectibles/{asset}static.png"),
+    }
+}
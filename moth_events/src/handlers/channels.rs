@@ -4,14 +4,16 @@ use crate::helper::{
     sort_order_to_string,
 };
 
+use crate::logging::{diff_block, log_embed, mirror};
 use crate::{Data, Error};
 
 use lumi::serenity_prelude::audit_log::Action::VoiceChannelStatus;
 use lumi::serenity_prelude::{
-    self as serenity, ChannelFlags, ChannelId, ChannelType, CreateEmbed, ForumEmoji, GuildChannel,
-    GuildId, GuildThread, PartialGuildThread, UserId, VoiceChannelStatusAction,
+    self as serenity, ChannelFlags, ChannelId, ChannelType, Colour, CreateEmbed, ForumEmoji,
+    GuildChannel, GuildId, GuildThread, PartialGuildThread, UserId, VoiceChannelStatusAction,
 };
 use moth_ansi::{BLUE, HI_BLUE, RESET};
+use moth_core::config::LogEvent;
 
 use std::fmt::Write;
 use std::sync::Arc;
@@ -29,6 +31,18 @@ pub async fn channel_create(
         "{BLUE}[{}] #{} ({}) was created!{RESET}",
         guild_name, channel.base.name, kind
     );
+
+    let embed = log_embed("Channel created", Colour::DARK_GREEN, None)
+        .description(format!("<#{}> ({kind})", channel.id));
+    mirror(
+        ctx,
+        &data,
+        channel.base.guild_id,
+        LogEvent::ChannelCreate,
+        embed,
+    )
+    .await;
+
     Ok(())
 }
 
@@ -277,6 +291,24 @@ pub async fn channel_update(
     diff = diff.trim_end_matches('\n').to_string();
     if !diff.is_empty() {
         println!("{BLUE}[{guild_name}] #{channel_name} was updated! ({kind}){RESET}\n{diff}");
+
+        let embed = log_embed("Channel updated", Colour::BLUE, None).description(format!(
+            "<#{}> ({kind})\n{}",
+            new.id,
+            diff_block(&diff)
+        ));
+        mirror(
+            ctx,
+            &data,
+            new.base.guild_id,
+            LogEvent::ChannelUpdate,
+            embed,
+        )
+        .await;
+    }
+
+    if let Some(old) = old {
+        super::permission_alerts::channel_update(ctx, old, new).await;
     }
     Ok(())
 }
@@ -294,6 +326,21 @@ pub async fn channel_delete(
         guild_name, channel.base.name, kind
     );
 
+    super::guilds::anti_nuke::snapshot_channel(&data, channel).await;
+
+    let embed = log_embed("Channel deleted", Colour::RED, None).description(format!(
+        "#{} ({kind}) (ID:{})",
+        channel.base.name, channel.id
+    ));
+    mirror(
+        ctx,
+        &data,
+        channel.base.guild_id,
+        LogEvent::ChannelDelete,
+        embed,
+    )
+    .await;
+
     Ok(())
 }
 
@@ -440,53 +487,27 @@ pub async fn voice_channel_status_update(
     guild_id: &GuildId,
     data: Arc<Data>,
 ) -> Result<(), Error> {
-    let vcstatus = {
-        let config = data.config.read();
-        config.vcstatus.clone()
+    // the history is only kept while statuses are tracked or cleaned up.
+    let enabled = {
+        let vcstatus = &data.config.read().vcstatus;
+        vcstatus.action || vcstatus.clear_blacklisted || vcstatus.warn_message.is_some()
     };
-    if vcstatus.action {
-        let old_field: Option<String>;
-        let new_field: Option<String>;
-        match (old, status.clone()) {
-            (None, None) => {
-                old_field = None;
-                new_field = None;
-                add(ctx, id, guild_id, old_field, new_field, status, &data).await?;
-            }
-            (Some(old), Some(status)) => {
-                old_field = Some(old.to_string());
-                new_field = Some(status.clone());
-                if old_field != new_field {
-                    add(
-                        ctx,
-                        id,
-                        guild_id,
-                        old_field,
-                        new_field,
-                        &Some(status),
-                        &data,
-                    )
-                    .await?;
-                }
-            }
-            (None, Some(status)) => {
-                old_field = None;
-                new_field = Some(status.clone());
-                add(
-                    ctx,
-                    id,
-                    guild_id,
-                    old_field,
-                    new_field,
-                    &Some(status),
-                    &data,
-                )
-                .await?;
-            }
-            _ => {}
-        }
+
+    // the same status is sent again when other parts of the channel change.
+    if !enabled || (old.is_some() && old == status) {
+        return Ok(());
     }
-    Ok(())
+
+    add(
+        ctx,
+        id,
+        guild_id,
+        old.clone(),
+        status.clone(),
+        status,
+        &data,
+    )
+    .await
 }
 
 pub async fn add(
@@ -498,38 +519,6 @@ pub async fn add(
     status: &Option<String>,
     data: &Data,
 ) -> Result<(), Error> {
-    tokio::time::sleep(Duration::from_secs(2)).await;
-    let logs = guild_id
-        .audit_logs(
-            &ctx.http,
-            Some(VoiceChannelStatus(VoiceChannelStatusAction::StatusUpdate)),
-            None,
-            None,
-            Some(nonmax::NonMaxU8::new(5).unwrap()),
-        )
-        .await?;
-    let mut user_id: Option<UserId> = None;
-
-    for log in &logs.entries {
-        if log.options.is_none() {
-            continue;
-        }
-
-        let options = &log.options.as_ref().unwrap();
-
-        if let Some(str) = &options.status
-            && str == status.as_deref().unwrap_or_default()
-            && options.channel_id == Some(id.widen())
-        {
-            user_id = Some(log.user_id.unwrap());
-            break;
-        }
-    }
-
-    let Some(user_id) = user_id else {
-        return Ok(());
-    };
-
     let vcstatus = {
         let config = data.config.read();
         config.vcstatus.clone()
@@ -542,6 +531,53 @@ pub async fn add(
         false
     };
 
+    // recorded before the audit log is checked, a failed insert only loses the history.
+    let history_id = match data
+        .database
+        .insert_vc_status_change(*guild_id, *id, new_field.as_deref(), None, blacklisted)
+        .await
+    {
+        Ok(history_id) => Some(history_id),
+        Err(e) => {
+            println!("Failed to record vc status change: {e}");
+            None
+        }
+    };
+
+    let cleared =
+        blacklisted && vcstatus.clear_blacklisted && clear_status(ctx, data, *id, history_id).await;
+
+    // removals are only kept in the history.
+    let removed = old_field.is_some() && new_field.is_none();
+    let post = vcstatus.action && !removed;
+    let warn = blacklisted && vcstatus.warn_message.is_some();
+
+    // the audit log is only needed to post the change or warn whoever set it.
+    if !post && !warn {
+        return Ok(());
+    }
+
+    let Some(user_id) = status_author(ctx, *id, *guild_id, status).await else {
+        return Ok(());
+    };
+
+    if let Some(history_id) = history_id
+        && let Err(e) = data.database.set_vc_status_user(history_id, user_id).await
+    {
+        println!("Failed to record who changed a vc status: {e}");
+    }
+
+    if warn && let Some(warning) = &vcstatus.warn_message {
+        let content = warning.replace("{channel}", &format!("<#{id}>"));
+        let _ = user_id
+            .dm(&ctx.http, serenity::CreateMessage::new().content(content))
+            .await;
+    }
+
+    if !post {
+        return Ok(());
+    }
+
     post_messages(
         ctx,
         data,
@@ -551,11 +587,73 @@ pub async fn add(
         new_field.as_deref(),
         user_id,
         blacklisted,
+        cleared,
     )
     .await?;
     Ok(())
 }
 
+/// Finds who set a status from the audit log, `None` if the entry can't be found.
+async fn status_author(
+    ctx: &serenity::Context,
+    id: ChannelId,
+    guild_id: GuildId,
+    status: &Option<String>,
+) -> Option<UserId> {
+    // the audit log entry isn't always there by the time the event is.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let logs = match guild_id
+        .audit_logs(
+            &ctx.http,
+            Some(VoiceChannelStatus(VoiceChannelStatusAction::StatusUpdate)),
+            None,
+            None,
+            Some(nonmax::NonMaxU8::new(5).unwrap()),
+        )
+        .await
+    {
+        Ok(logs) => logs,
+        Err(e) => {
+            println!("Failed to fetch the audit log for a vc status: {e}");
+            return None;
+        }
+    };
+
+    logs.entries
+        .iter()
+        .find(|log| {
+            log.options.as_ref().is_some_and(|options| {
+                options.status.as_deref() == Some(status.as_deref().unwrap_or_default())
+                    && options.channel_id == Some(id.widen())
+            })
+        })
+        .and_then(|log| log.user_id)
+}
+
+/// Removes a blacklisted status, returning true if it was removed.
+async fn clear_status(
+    ctx: &serenity::Context,
+    data: &Data,
+    channel_id: ChannelId,
+    history_id: Option<i32>,
+) -> bool {
+    let cleared = ctx
+        .http
+        .edit_voice_status(
+            channel_id,
+            &serde_json::json!({ "status": null }),
+            Some("Blacklisted word in status."),
+        )
+        .await
+        .is_ok();
+
+    if cleared && let Some(history_id) = history_id {
+        let _ = data.database.set_vc_status_cleared(history_id).await;
+    }
+
+    cleared
+}
+
 async fn check_blacklisted(msg: &str, patterns: &[regex::Regex]) -> bool {
     patterns.iter().any(|pattern| pattern.is_match(msg))
 }
@@ -571,6 +669,7 @@ async fn post_messages(
     new: Option<&str>,
     user_id: UserId,
     blacklisted: bool,
+    cleared: bool,
 ) -> Result<(), Error> {
     let channel_str: &str = &format!("<#{}>", channel_id.get());
 
@@ -600,7 +699,7 @@ async fn post_messages(
         .author(author)
         .footer(footer);
 
-    send_msgs(ctx, data, user_id, embed, blacklisted).await?;
+    send_msgs(ctx, data, user_id, embed, blacklisted, cleared).await?;
 
     Ok(())
 }
@@ -611,13 +710,16 @@ async fn send_msgs(
     user_id: UserId,
     embed: CreateEmbed<'_>,
     blacklisted: bool,
+    cleared: bool,
 ) -> Result<(), Error> {
     let (post, announce) = {
         let status = &data.config.read().vcstatus;
         (status.post_channel.clone(), status.announce_channel)
     };
 
-    let content = if blacklisted {
+    let content = if cleared {
+        format!("<@{user_id}>: **Blacklisted word in status!** (removed)")
+    } else if blacklisted {
         format!("<@{user_id}>: **Blacklisted word in status!**")
     } else {
         format!("<@{user_id}>")
@@ -0,0 +1,296 @@
+use std::{
+    fmt::Write,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Data, Error,
+    logging::{field_value, log_embed, mirror},
+};
+use lumi::serenity_prelude::{
+    self as serenity, AuditLogEntry, ChannelAction, Colour, EditMember, GuildChannel, GuildId,
+    MemberAction, Role, RoleAction, UserId, audit_log::Action,
+};
+use moth_ansi::{RED, RESET};
+use moth_core::{
+    config::LogEvent,
+    data::{
+        database::anti_nuke::SnapshotKind,
+        structs::{DestructiveAction, OpenIncident},
+    },
+};
+
+/// Counts destructive actions from the audit log per executor, stripping the roles of an
+/// executor that crosses the threshold inside the window.
+pub(super) async fn handle(ctx: &serenity::Context, entry: &AuditLogEntry, guild_id: GuildId) {
+    let kind = match entry.action {
+        Action::Channel(ChannelAction::Delete) => "Channel deleted",
+        Action::Role(RoleAction::Delete) => "Role deleted",
+        Action::Member(MemberAction::BanAdd) => "Member banned",
+        _ => return,
+    };
+
+    let Some(executor) = entry.user_id else {
+        return;
+    };
+
+    let data = ctx.data::<Data>();
+    let Some(settings) = data.config.read().anti_nuke.guilds.get(&guild_id).cloned() else {
+        return;
+    };
+
+    if settings.trusted.contains(&executor) || executor == ctx.cache.current_user().id {
+        return;
+    }
+
+    let window = Duration::from_secs(settings.window);
+    let now = Instant::now();
+    let key = (guild_id, executor);
+
+    let target_id = entry.target_id.map_or(0, |t| t.get());
+
+    let actions = {
+        let mut actions = data.anti_nuke.actions.entry(key).or_default();
+        while actions
+            .front()
+            .is_some_and(|a| now.duration_since(a.at) > window)
+        {
+            actions.pop_front();
+        }
+
+        actions.push_back(DestructiveAction {
+            at: now,
+            kind,
+            target_id,
+        });
+
+        actions.iter().cloned().collect::<Vec<_>>()
+    };
+
+    // only act once per executor until the window passes, later actions join the open incident.
+    let mut is_new = false;
+    let open_incident = {
+        let mut open = if actions.len() >= settings.threshold {
+            data.anti_nuke.handled.entry(key).or_insert_with(|| {
+                is_new = true;
+                OpenIncident::default()
+            })
+        } else if let Some(open) = data.anti_nuke.handled.get_mut(&key) {
+            open
+        } else {
+            return;
+        };
+
+        // the incident is still being recorded, it picks these up once it has an id.
+        if !is_new && open.incident_id.is_none() {
+            open.pending.push(target_id);
+        }
+        open.incident_id
+    };
+
+    if !is_new {
+        if let Some(incident_id) = open_incident {
+            attach(&data, guild_id, incident_id, &[target_id]).await;
+        }
+        return;
+    }
+
+    println!(
+        "{RED}Possible nuke in {guild_id} by {executor}: {} destructive actions{RESET}",
+        actions.len()
+    );
+
+    let incident_id = incident(ctx, &data, guild_id, executor, &actions).await;
+
+    let pending = data
+        .anti_nuke
+        .handled
+        .get_mut(&key)
+        .map(|mut open| {
+            open.incident_id = incident_id;
+            std::mem::take(&mut open.pending)
+        })
+        .unwrap_or_default();
+
+    if let Some(incident_id) = incident_id
+        && !pending.is_empty()
+    {
+        attach(&data, guild_id, incident_id, &pending).await;
+    }
+
+    let data = data.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(window).await;
+        data.anti_nuke.handled.remove(&key);
+    });
+}
+
+async fn attach(data: &Data, guild_id: GuildId, incident_id: i32, target_ids: &[u64]) {
+    if let Err(e) = data
+        .database
+        .attach_nuke_targets(guild_id, incident_id, target_ids)
+        .await
+    {
+        println!("Failed to attach actions to nuke incident {incident_id}: {e}");
+    }
+}
+
+/// Strips the executor and records the incident, returning its id if it was recorded.
+async fn incident(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    guild_id: GuildId,
+    executor: UserId,
+    actions: &[DestructiveAction],
+) -> Option<i32> {
+    let stripped = strip_roles(ctx, guild_id, executor).await;
+
+    let target_ids = actions.iter().map(|a| a.target_id).collect::<Vec<_>>();
+    let incident_id = match data
+        .database
+        .insert_nuke_incident(guild_id, executor, &target_ids, stripped.is_ok())
+        .await
+    {
+        Ok(id) => Some(id),
+        Err(e) => {
+            println!("Failed to record nuke incident: {e}");
+            None
+        }
+    };
+
+    let mut description = format!(
+        "<@{executor}> performed {} destructive actions in quick succession.\n\n",
+        actions.len()
+    );
+    for action in actions {
+        writeln!(description, "{} (ID:{})", action.kind, action.target_id).unwrap();
+    }
+
+    let result = match stripped {
+        Ok(0) => String::from("They had no roles to strip."),
+        Ok(count) => format!("Stripped {count} roles."),
+        Err(e) => format!("Failed to strip roles: {e}"),
+    };
+
+    let mut embed = log_embed("Possible nuke detected", Colour::DARK_RED, None)
+        .description(crate::logging::truncate(&description, 4096))
+        .field("Action", field_value(&result), false);
+
+    if let Some(incident_id) = incident_id {
+        embed = embed.field(
+            "Restore",
+            format!(
+                "Deleted channels and roles can be recreated with `nuke-restore {incident_id}`."
+            ),
+            false,
+        );
+    }
+
+    mirror(ctx, data, guild_id, LogEvent::AntiNuke, embed).await;
+
+    incident_id
+}
+
+/// Removes every role from the executor that isn't managed by an integration, returning how many
+/// were removed.
+async fn strip_roles(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    executor: UserId,
+) -> Result<usize, Error> {
+    let member = guild_id.member(ctx, executor).await?;
+
+    // managed roles can't be removed, bots will have to be kicked manually.
+    let kept = {
+        let Some(guild) = ctx.cache.guild(guild_id) else {
+            return Err("the guild isn't cached, so their managed roles are unknown".into());
+        };
+        member
+            .roles
+            .iter()
+            .filter(|id| guild.roles.get(*id).is_some_and(Role::managed))
+            .copied()
+            .collect::<Vec<_>>()
+    };
+
+    let count = member.roles.len() - kept.len();
+    if count == 0 {
+        return Ok(0);
+    }
+
+    guild_id
+        .edit_member(
+            &ctx.http,
+            executor,
+            EditMember::new()
+                .roles(kept)
+                .audit_log_reason("Automatic anti nuke, too many destructive actions."),
+        )
+        .await?;
+
+    Ok(count)
+}
+
+fn is_tracked(data: &Data, guild_id: GuildId) -> bool {
+    data.config.read().anti_nuke.guilds.contains_key(&guild_id)
+}
+
+/// Stores a deleted channel so it can be recreated after an incident.
+pub(crate) async fn snapshot_channel(data: &Data, channel: &GuildChannel) {
+    if !is_tracked(data, channel.base.guild_id) {
+        return;
+    }
+
+    snapshot(
+        data,
+        channel.base.guild_id,
+        SnapshotKind::Channel,
+        channel.id.get(),
+        &channel.base.name,
+        serde_json::to_string(channel),
+    )
+    .await;
+}
+
+/// Stores a deleted role so it can be recreated after an incident.
+pub(crate) async fn snapshot_role(data: &Data, role: &Role) {
+    if !is_tracked(data, role.guild_id) {
+        return;
+    }
+
+    snapshot(
+        data,
+        role.guild_id,
+        SnapshotKind::Role,
+        role.id.get(),
+        &role.name,
+        serde_json::to_string(role),
+    )
+    .await;
+}
+
+async fn snapshot(
+    data: &Data,
+    guild_id: GuildId,
+    kind: SnapshotKind,
+    target_id: u64,
+    name: &str,
+    serialized: Result<String, serde_json::Error>,
+) {
+    let serialized = match serialized {
+        Ok(serialized) => serialized,
+        Err(e) => {
+            println!("Failed to serialize snapshot: {e}");
+            return;
+        }
+    };
+
+    if let Err(e) = data
+        .database
+        .insert_snapshot(guild_id, kind, target_id, name, &serialized)
+        .await
+    {
+        println!("Failed to store snapshot: {e}");
+    }
+}
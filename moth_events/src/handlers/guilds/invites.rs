@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use lumi::serenity_prelude::{
+    self as serenity, GuildId, InviteCreateEvent, InviteDeleteEvent, UserId,
+};
+use moth_core::data::structs::{Data, TrackedInvite};
+
+/// The invite a member most likely joined through.
+#[derive(Clone, Debug)]
+pub(super) struct UsedInvite {
+    pub code: String,
+    pub inviter: Option<UserId>,
+}
+
+/// Fetches the invites of a guild, replacing what is cached.
+///
+/// Returns `None` if they can't be fetched, usually from missing permissions.
+async fn fetch_invites(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+) -> Option<HashMap<String, TrackedInvite>> {
+    let invites = guild_id.invites(&ctx.http).await.ok()?;
+
+    Some(
+        invites
+            .iter()
+            .map(|i| {
+                (
+                    i.code.to_string(),
+                    TrackedInvite {
+                        uses: u64::from(i.uses),
+                        max_uses: u64::from(i.max_uses),
+                        inviter: i.inviter.as_ref().map(|u| u.id),
+                    },
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Whether joins to a guild are diffed against its invites, only guilds with raid detection are.
+fn is_tracked(data: &Data, guild_id: GuildId) -> bool {
+    data.config
+        .read()
+        .raid
+        .guilds
+        .get(&guild_id)
+        .is_some_and(|settings| settings.track_invites)
+}
+
+/// Caches the invites of a guild so the first join after startup can be diffed.
+pub(super) async fn cache_invites(ctx: &serenity::Context, data: &Data, guild_id: GuildId) {
+    if !is_tracked(data, guild_id) {
+        return;
+    }
+
+    if let Some(invites) = fetch_invites(ctx, guild_id).await {
+        data.raid.invites.insert(guild_id, invites);
+    }
+}
+
+pub async fn invite_create(data: &Data, event: &InviteCreateEvent) {
+    let Some(guild_id) = event.guild_id else {
+        return;
+    };
+
+    // guilds that couldn't be fetched aren't tracked, a partial cache would misattribute joins.
+    if let Some(mut invites) = data.raid.invites.get_mut(&guild_id) {
+        invites.insert(
+            event.code.to_string(),
+            TrackedInvite {
+                uses: 0,
+                max_uses: u64::from(event.max_uses),
+                inviter: event.inviter.as_ref().map(|u| u.id),
+            },
+        );
+    }
+}
+
+pub async fn invite_delete(data: &Data, event: &InviteDeleteEvent) {
+    let Some(guild_id) = event.guild_id else {
+        return;
+    };
+
+    if let Some(mut invites) = data.raid.invites.get_mut(&guild_id) {
+        invites.remove(event.code.as_str());
+    }
+}
+
+/// Works out the invite a member joined through by diffing the use counts against the cache.
+pub(super) async fn used_invite(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+) -> Option<UsedInvite> {
+    if !is_tracked(data, guild_id) || !data.raid.invites.contains_key(&guild_id) {
+        return None;
+    }
+
+    // joins are diffed one at a time, otherwise a later fetch can replace the cache between the
+    // fetch and insert of an earlier one.
+    let lock = data.raid.invite_locks.entry(guild_id).or_default().clone();
+    let _guard = lock.lock().await;
+
+    let current = fetch_invites(ctx, guild_id).await?;
+    let previous = data.raid.invites.insert(guild_id, current.clone())?;
+
+    let used = current
+        .iter()
+        .find(|(code, invite)| previous.get(*code).map_or(0, |p| p.uses) < invite.uses)
+        .map(|(code, invite)| (code.clone(), *invite));
+
+    // an invite that hit its max uses is deleted, so it only shows up by vanishing.
+    let used = used.or_else(|| {
+        previous
+            .iter()
+            .find(|(code, invite)| {
+                !current.contains_key(*code)
+                    && invite.max_uses != 0
+                    && invite.uses + 1 == invite.max_uses
+            })
+            .map(|(code, invite)| (code.clone(), *invite))
+    });
+
+    used.map(|(code, invite)| UsedInvite {
+        code,
+        inviter: invite.inviter,
+    })
+}
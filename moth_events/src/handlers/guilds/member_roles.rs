@@ -2,11 +2,14 @@ use std::borrow::Cow;
 
 use aformat::ToArrayString;
 use moth_ansi::{MAGENTA, RESET};
+use moth_core::config::LogEvent;
+use moth_core::data::database::moderation::{CaseAction, ModerationCase};
 use serenity::all::audit_log::Action;
-use serenity::all::{AffectedRole, AuditLogEntry, Context, GuildId, MemberAction, UserId};
+use serenity::all::{AffectedRole, AuditLogEntry, Colour, Context, GuildId, MemberAction, UserId};
 use small_fixed_array::{FixedArray, FixedString};
 
 use crate::helper::{get_guild_name_override, get_user};
+use crate::logging::{log_embed, mirror};
 
 pub(super) async fn handle(ctx: &Context, entry: &AuditLogEntry, guild_id: GuildId) {
     if let Action::Member(member_action) = &entry.action {
@@ -116,6 +119,22 @@ async fn log_role_change(
                 );
             }
         }
+
+        let mentions = roles
+            .iter()
+            .map(|r| format!("<@&{}>", r.id))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let target = entry
+            .target_id
+            .map_or_else(|| String::from("themselves"), |t| format!("<@{t}>"));
+
+        let embed =
+            log_embed(format!("Roles {action}"), Colour::MAGENTA, None).description(format!(
+                "<@{}> {action} {mentions} for {target}",
+                entry.user_id.unwrap()
+            ));
+        mirror(ctx, &ctx.data(), guild_id, LogEvent::MemberRoles, embed).await;
     }
 }
 
@@ -128,32 +147,74 @@ async fn kick_prune_ban(
     let guild_name = get_guild_name_override(ctx, &ctx.data(), Some(guild_id));
 
     let user = get_username(ctx, guild_id, entry.user_id).await;
-    let target = get_username(ctx, guild_id, entry.target_id.map(|t| UserId::new(t.get()))).await;
+    let target_id = entry.target_id.map(|t| UserId::new(t.get()));
+    let target = get_username(ctx, guild_id, target_id).await;
 
     let reason = entry
         .reason
         .clone()
         .unwrap_or_else(|| FixedString::from_static_trunc("No reason given."));
 
-    match member_action {
+    let mut prune = None;
+    let action = match member_action {
         MemberAction::Kick => {
             println!(
                 "{MAGENTA}[{guild_name}] {target} was kicked by {user}.\nReason: {reason}{RESET}"
             );
+            CaseAction::Kick
+        }
+        MemberAction::Prune => {
+            // discord sends these as strings, so go through the display impl for either.
+            let options = entry.options.as_ref();
+            let days = options
+                .and_then(|o| o.delete_member_days.as_ref())
+                .and_then(|d| d.to_string().parse::<i32>().ok())
+                .unwrap_or(0);
+            let removed = options
+                .and_then(|o| o.members_removed.as_ref())
+                .and_then(|m| m.to_string().parse::<i32>().ok())
+                .unwrap_or(0);
+
+            println!(
+                "{MAGENTA}[{guild_name}] {user} pruned {removed} members inactive for {days} \
+                 days.\nReason: {reason}{RESET}"
+            );
+            prune = Some((days, removed));
+            CaseAction::Prune
         }
-        // TODO: prune support
-        MemberAction::Prune => {}
         MemberAction::BanAdd => {
             println!(
                 "{MAGENTA}[{guild_name}] {target} was banned by {user}.\nReason: {reason}{RESET}"
             );
+            CaseAction::Ban
         }
         MemberAction::BanRemove => {
             println!(
                 "{MAGENTA}[{guild_name}] {target} was unbanned by {user}.\nReason: {reason}{RESET}"
             );
+            CaseAction::Unban
         }
         _ => unreachable!(),
+    };
+
+    let case = ModerationCase {
+        id: 0,
+        action,
+        moderator_id: entry.user_id,
+        // prunes target the guild rather than a member.
+        target_id: target_id.filter(|_| action != CaseAction::Prune),
+        reason: entry.reason.as_ref().map(ToString::to_string),
+        prune,
+        created_at: chrono::Utc::now(),
+    };
+
+    if let Err(e) = ctx
+        .data()
+        .database
+        .insert_moderation_case(guild_id, entry.id.get(), &case)
+        .await
+    {
+        println!("Failed to record moderation case: {e}");
     }
 }
 
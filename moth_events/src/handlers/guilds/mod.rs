@@ -1,13 +1,15 @@
 use std::{collections::HashSet, sync::Arc};
 
+pub(crate) mod anti_nuke;
+pub(crate) mod invites;
 mod member_roles;
+mod raid;
 pub(crate) mod roles;
 
-use std::fmt::Write;
-
 use crate::{
     Data, Error,
     helper::{get_channel_name, get_guild_name_override, get_user},
+    logging::{log_embed, mirror},
 };
 use lumi::serenity_prelude::{
     self as serenity, AuditLogEntry, AutoModAction, ChannelId, CreateEmbedAuthor, Guild, GuildId,
@@ -16,8 +18,8 @@ use lumi::serenity_prelude::{
 
 use moth_ansi::{RESET, YELLOW};
 
-use ::serenity::all::{CreateMessage, GenericChannelId, UserId};
-use moth_core::data::structs::Fuck;
+use ::serenity::all::{Colour, CreateMessage, GenericChannelId};
+use moth_core::{config::LogEvent, data::structs::Fuck};
 use serenity::model::guild::audit_log::Action;
 
 pub async fn guild_create(
@@ -33,6 +35,10 @@ pub async fn guild_create(
             ctx.cache.guilds().len()
         );
     }
+
+    super::voice::sessions::reconcile(ctx, guild).await;
+    invites::cache_invites(ctx, ctx.data_ref::<Data>(), guild.id).await;
+
     Ok(())
 }
 
@@ -62,65 +68,47 @@ pub async fn guild_member_addition(
         new_member.user.tag(),
         joined_user_id
     );
-    Ok(())
-}
-
-pub async fn join_time(ctx: &serenity::Context, data: &Data, user_id: UserId) {
-    let mut groups = Vec::new();
-
-    {
-        let mut lock = data.osu_game_joins.lock();
-
-        if !lock.contains(&user_id) {
-            lock.push_back(user_id);
-        }
-
-        while lock.len() > 500 {
-            lock.pop_front();
-        }
-
-        let mut users_sorted = lock.iter().copied().collect::<Vec<_>>();
-        users_sorted.sort_by_key(|uid| uid.created_at().timestamp());
-
-        let timestamps: Vec<_> = users_sorted
-            .iter()
-            .map(|uid| uid.created_at().timestamp())
-            .collect();
 
-        let mut start = 0;
-        for end in 0..users_sorted.len() {
-            while timestamps[end] - timestamps[start] > 86400 {
-                start += 1;
-            }
+    // scored before the invite is worked out, which waits behind earlier joins.
+    raid::check(ctx, &data, new_member).await;
 
-            if end - start + 1 > 1 {
-                groups.push(users_sorted[start..=end].to_vec());
-            }
-        }
+    let invite = invites::used_invite(ctx, &data, guild_id).await;
+    if let Some(invite) = &invite {
+        raid::record_invite(ctx, &data, new_member, invite.code.clone()).await;
     }
 
-    let relevant_groups: Vec<_> = groups
-        .into_iter()
-        .filter(|group| group.contains(&user_id))
-        .collect();
-
-    for group in relevant_groups {
-        let mut string = String::new();
+    let mut embed = log_embed("Member joined", Colour::DARK_GREEN, Some(&new_member.user))
+        .description(format!("<@{joined_user_id}>"))
+        .field(
+            "Account created",
+            format!("<t:{}:R>", joined_user_id.created_at().unix_timestamp()),
+            true,
+        );
+    if let Some(invite) = &invite {
+        let inviter = invite
+            .inviter
+            .map_or_else(String::new, |id| format!(" by <@{id}>"));
+        embed = embed.field("Invite", format!("`{}`{inviter}", invite.code), true);
+    }
+    mirror(ctx, &data, guild_id, LogEvent::MemberJoin, embed).await;
+
+    if let Err(e) = data
+        .database
+        .insert_member_join(
+            guild_id,
+            joined_user_id,
+            invite.as_ref().map(|i| i.code.as_str()),
+            invite.as_ref().and_then(|i| i.inviter),
+        )
+        .await
+    {
+        println!("Failed to record member join: {e}");
+    }
 
-        for uid in group {
-            write!(string, "<@{uid}> ").unwrap();
-        }
+    super::users::record_names(&data, None, new_member).await;
+    super::appearances::record_member(&data, new_member).await;
 
-        let _ = GenericChannelId::new(158484765136125952)
-            .send_message(
-                &ctx.http,
-                CreateMessage::new().content(format!(
-                    "group detected with join date within 1 day of each \
-                     other: {string}(possible raid, please watch vcs!)"
-                )),
-            )
-            .await;
-    }
+    Ok(())
 }
 
 pub async fn guild_member_removal(
@@ -138,6 +126,10 @@ pub async fn guild_member_removal(
         user.id
     );
 
+    let embed =
+        log_embed("Member left", Colour::ORANGE, Some(user)).description(format!("<@{}>", user.id));
+    mirror(ctx, &data, *guild_id, LogEvent::MemberLeave, embed).await;
+
     Ok(())
 }
 
@@ -147,6 +139,7 @@ pub async fn guild_audit_log_entry_create(
     guild_id: &GuildId,
 ) -> Result<(), Error> {
     member_roles::handle(ctx, entry, *guild_id).await;
+    anti_nuke::handle(ctx, entry, *guild_id).await;
 
     if *guild_id != 98226572468690944 {
         return Ok(());
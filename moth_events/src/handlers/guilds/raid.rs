@@ -0,0 +1,339 @@
+use std::{
+    fmt::Write,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Data, Error,
+    logging::{log_embed, mirror},
+};
+use lumi::serenity_prelude::{
+    self as serenity, ChannelId, Colour, EditChannel, EditGuild, GuildId, Member, VerificationLevel,
+};
+use moth_ansi::{RED, RESET};
+use moth_core::{
+    config::{LogEvent, RaidGuild, RaidLockdown},
+    data::structs::RecentJoin,
+};
+use nonmax::NonMaxU16;
+
+/// Scores a new member against the raid signals, alerting and starting a lockdown if the joins
+/// inside the window cross the threshold.
+///
+/// The invite used isn't known yet, it is scored separately by [`record_invite`].
+pub(super) async fn check(ctx: &serenity::Context, data: &Arc<Data>, member: &Member) {
+    let guild_id = member.guild_id;
+    let Some(settings) = data.config.read().raid.guilds.get(&guild_id).cloned() else {
+        return;
+    };
+
+    let window = Duration::from_secs(settings.window);
+    let now = Instant::now();
+    let name = member.user.name.to_lowercase();
+
+    let (joins, signals) = {
+        let mut joins = data.raid.joins.entry(guild_id).or_default();
+        while joins
+            .front()
+            .is_some_and(|j| now.duration_since(j.joined_at) > window)
+        {
+            joins.pop_front();
+        }
+
+        let mut signals = Vec::new();
+        let mut score = 0;
+
+        let age = chrono::Utc::now().timestamp() - member.user.id.created_at().timestamp();
+        if age < settings.new_account_age as i64 {
+            score += settings.new_account_score;
+            signals.push("new account");
+        }
+
+        if member.user.avatar.is_none() {
+            score += settings.default_avatar_score;
+            signals.push("default avatar");
+        }
+
+        // short names are too likely to be similar by chance.
+        if name.chars().count() > settings.similar_name_distance * 2
+            && joins
+                .iter()
+                .any(|j| moth_filter::levenshtein(&j.name, &name) <= settings.similar_name_distance)
+        {
+            score += settings.similar_name_score;
+            signals.push("similar username");
+        }
+
+        if joins.len() + 1 >= settings.velocity_joins {
+            score += settings.velocity_score;
+            signals.push("join velocity");
+        }
+
+        joins.push_back(RecentJoin {
+            user_id: member.user.id,
+            joined_at: now,
+            name,
+            invite: None,
+            score,
+        });
+
+        (joins.iter().cloned().collect::<Vec<_>>(), signals)
+    };
+
+    alert(ctx, data, member, &settings, &joins, &signals).await;
+}
+
+/// Attaches the invite a member joined through once it is known, scoring it if enough of the
+/// other recent joins used it too.
+pub(super) async fn record_invite(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    member: &Member,
+    code: String,
+) {
+    let guild_id = member.guild_id;
+    let Some(settings) = data.config.read().raid.guilds.get(&guild_id).cloned() else {
+        return;
+    };
+
+    let joins = {
+        let Some(mut joins) = data.raid.joins.get_mut(&guild_id) else {
+            return;
+        };
+
+        let shared = joins
+            .iter()
+            .filter(|j| j.invite.as_ref() == Some(&code))
+            .count()
+            + 1
+            >= settings.shared_invite_joins;
+
+        let Some(join) = joins.iter_mut().rev().find(|j| j.user_id == member.user.id) else {
+            return;
+        };
+
+        join.invite = Some(code);
+        if !shared {
+            return;
+        }
+        join.score += settings.shared_invite_score;
+
+        joins.iter().cloned().collect::<Vec<_>>()
+    };
+
+    alert(ctx, data, member, &settings, &joins, &["shared invite"]).await;
+}
+
+/// Alerts and starts a lockdown if the joins inside the window cross the threshold.
+async fn alert(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    member: &Member,
+    settings: &RaidGuild,
+    joins: &[RecentJoin],
+    signals: &[&str],
+) {
+    let guild_id = member.guild_id;
+    let window = Duration::from_secs(settings.window);
+
+    let total = joins.iter().map(|j| j.score).sum::<u32>();
+    // only alert once per raid.
+    if total < settings.threshold || !data.raid.active.insert(guild_id) {
+        return;
+    }
+
+    println!(
+        "{RED}Possible raid detected in {guild_id}: {} joins scoring {total}/{}{RESET}",
+        joins.len(),
+        settings.threshold
+    );
+
+    let mut description = format!(
+        "{} joins in the last {} seconds scored {total}/{}.\n\n",
+        joins.len(),
+        settings.window,
+        settings.threshold
+    );
+    for join in joins {
+        write!(description, "<@{}> ({})", join.user_id, join.score).unwrap();
+        if let Some(code) = &join.invite {
+            write!(description, " via `{code}`").unwrap();
+        }
+        description.push('\n');
+    }
+
+    let mut embed = log_embed("Possible raid detected", Colour::RED, None)
+        .description(crate::logging::truncate(&description, 4096))
+        .field(
+            "Latest join",
+            format!("<@{}>: {}", member.user.id, signals.join(", ")),
+            false,
+        );
+
+    let duration = if let Some(lockdown) = settings.lockdown.clone() {
+        let duration = Duration::from_secs(lockdown.duration);
+        let actions = start_lockdown(ctx, data, guild_id, lockdown).await;
+        embed = embed.field(
+            "Lockdown",
+            crate::logging::field_value(&actions.join("\n")),
+            false,
+        );
+        duration
+    } else {
+        window
+    };
+
+    mirror(ctx, data, guild_id, LogEvent::Raid, embed).await;
+
+    // the raid is over once the lockdown is lifted, or once the window passes without one.
+    let data = data.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(duration).await;
+        data.raid.active.remove(&guild_id);
+    });
+}
+
+/// Applies the configured lockdown, returning a description of each action taken.
+///
+/// The verification level and slowmode are reverted once the lockdown duration passes, pausing
+/// invites expires on its own.
+async fn start_lockdown(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    guild_id: GuildId,
+    lockdown: RaidLockdown,
+) -> Vec<String> {
+    let reason = "Automatic raid lockdown.";
+    let mut actions = Vec::new();
+
+    let (old_level, old_slowmodes) = {
+        let Some(guild) = ctx.cache.guild(guild_id) else {
+            return actions;
+        };
+
+        let slowmodes = lockdown
+            .slowmode_channels
+            .iter()
+            .filter_map(|id| {
+                let channel = guild.channels.get(id)?;
+                Some((
+                    *id,
+                    channel.base.rate_limit_per_user.map_or(0, NonMaxU16::get),
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        (guild.verification_level, slowmodes)
+    };
+
+    let mut raised_level = false;
+    if let Some(level) = lockdown.verification_level {
+        // never lower a level that is already stricter than the lockdown's.
+        if level <= u8::from(old_level) {
+            actions.push(format!("Verification level is already {old_level:?}"));
+        } else {
+            let level = VerificationLevel::from(level);
+            if guild_id
+                .edit(
+                    &ctx.http,
+                    EditGuild::new()
+                        .verification_level(level)
+                        .audit_log_reason(reason),
+                )
+                .await
+                .is_ok()
+            {
+                raised_level = true;
+                actions.push(format!("Raised verification level to {level:?}"));
+            } else {
+                actions.push(String::from("Failed to raise the verification level"));
+            }
+        }
+    }
+
+    let mut slowed = Vec::new();
+    if let Some(slowmode) = lockdown.slowmode {
+        for (channel_id, old) in &old_slowmodes {
+            if set_slowmode(ctx, *channel_id, slowmode, reason).await {
+                slowed.push((*channel_id, *old));
+            }
+        }
+        actions.push(format!(
+            "Set a {slowmode}s slowmode in {}/{} channels",
+            slowed.len(),
+            lockdown.slowmode_channels.len()
+        ));
+    }
+
+    if lockdown.pause_invites {
+        let until = chrono::Utc::now() + chrono::Duration::seconds(lockdown.duration as i64);
+        match pause_invites(ctx, guild_id, until).await {
+            Ok(()) => actions.push(format!("Paused invites until <t:{}:f>", until.timestamp())),
+            Err(e) => actions.push(format!("Failed to pause invites: {e}")),
+        }
+    }
+
+    let ctx = ctx.clone();
+    let data = data.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(lockdown.duration)).await;
+
+        let reason = "Lifting automatic raid lockdown.";
+        if raised_level {
+            let _ = guild_id
+                .edit(
+                    &ctx.http,
+                    EditGuild::new()
+                        .verification_level(old_level)
+                        .audit_log_reason(reason),
+                )
+                .await;
+        }
+
+        for (channel_id, old) in slowed {
+            set_slowmode(&ctx, channel_id, old, reason).await;
+        }
+
+        let embed = log_embed("Raid lockdown lifted", Colour::DARK_GREEN, None);
+        mirror(&ctx, &data, guild_id, LogEvent::Raid, embed).await;
+    });
+
+    actions
+}
+
+async fn set_slowmode(
+    ctx: &serenity::Context,
+    channel_id: ChannelId,
+    seconds: u16,
+    reason: &str,
+) -> bool {
+    let Some(seconds) = NonMaxU16::new(seconds) else {
+        return false;
+    };
+
+    channel_id
+        .edit(
+            &ctx.http,
+            EditChannel::new()
+                .rate_limit_per_user(seconds)
+                .audit_log_reason(reason),
+        )
+        .await
+        .is_ok()
+}
+
+/// Pauses invites using the guild incident actions, through serenity so it shares the
+/// ratelimiter.
+async fn pause_invites(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    until: chrono::DateTime<chrono::Utc>,
+) -> Result<(), Error> {
+    let body = serde_json::json!({ "invites_disabled_until": until.to_rfc3339() });
+    ctx.http
+        .edit_guild_incident_actions(guild_id, &body)
+        .await?;
+
+    Ok(())
+}
@@ -1,6 +1,11 @@
-use crate::{helper::get_guild_name_override, Error};
+use crate::{
+    Error,
+    helper::get_guild_name_override,
+    logging::{diff_block, log_embed, mirror},
+};
 use moth_ansi::{HI_GREEN, MAGENTA, RED, RESET};
-use serenity::all::{Context, GuildId, Permissions, Role, RoleId};
+use moth_core::config::LogEvent;
+use serenity::all::{Colour, Context, GuildId, Permissions, Role, RoleId};
 
 use std::fmt::Write;
 
@@ -14,6 +19,12 @@ pub(crate) async fn role_create(ctx: &Context, role: &Role) -> Result<(), Error>
         role.name
     );
 
+    let embed = log_embed("Role created", Colour::DARK_GREEN, None)
+        .description(format!("<@&{}> ({})", role.id, role.name));
+    mirror(ctx, &ctx.data(), role.guild_id, LogEvent::RoleCreate, embed).await;
+
+    crate::handlers::permission_alerts::role_create(ctx, role).await;
+
     Ok(())
 }
 
@@ -26,16 +37,25 @@ pub(crate) async fn role_delete(
     let guild_name = get_guild_name_override(ctx, &ctx.data(), Some(guild_id));
 
     if let Some(role) = role {
+        super::anti_nuke::snapshot_role(&ctx.data(), role).await;
+    }
+
+    let description = if let Some(role) = role {
         println!(
             "{MAGENTA}[{guild_name}] A role called {} was deleted! (ID:{role_id}){RESET}",
             role.name
         );
+        format!("{} (ID:{role_id})", role.name)
     } else {
         println!(
             "{MAGENTA}[{guild_name}] A role called {role_id} was deleted, but was not \
              cached!{RESET}"
         );
-    }
+        format!("{role_id} (not cached)")
+    };
+
+    let embed = log_embed("Role deleted", Colour::RED, None).description(description);
+    mirror(ctx, &ctx.data(), guild_id, LogEvent::RoleDelete, embed).await;
 
     Ok(())
 }
@@ -170,8 +190,20 @@ pub(crate) async fn role_update(
     if modified {
         string.strip_suffix('\n').unwrap_or(&string);
         println!("{string}");
+
+        // the first line is the header, which the embed already covers.
+        let diff = string.split_once('\n').map_or("", |(_, diff)| diff);
+        let embed = log_embed("Role updated", Colour::BLURPLE, None).description(format!(
+            "<@&{}> ({})\n{}",
+            role.id,
+            role.name,
+            diff_block(diff)
+        ));
+        mirror(ctx, &ctx.data(), role.guild_id, LogEvent::RoleUpdate, embed).await;
     }
 
+    crate::handlers::permission_alerts::role_update(ctx, old_role, role).await;
+
     Ok(())
 }
 
@@ -1,7 +1,16 @@
 use crate::Data;
+use crate::helper::timeout_member;
+use crate::logging::{field_value, log_embed, mirror_with_files};
 use lumi::serenity_prelude as serenity;
-use moth_core::data::structs::{ANTI_DELETE_MAX_DELETES_PER_CYCLE, Decay, InnerCache};
-use serenity::{GenericChannelId, GetMessages, GuildId, MessageId, UserId};
+use moth_core::config::{LogEvent, MAX_TIMEOUT_SECONDS};
+use moth_core::data::database::snipes::SnipeKind;
+use moth_core::data::structs::{Decay, InnerCache};
+use serenity::{
+    Colour, CreateAttachment, CreateEmbedFooter, GenericChannelId, GetMessages, GuildId, MessageId,
+    UserId,
+};
+use std::fmt::Write;
+use std::sync::atomic::Ordering;
 use std::{collections::HashMap, sync::Arc, time::Instant};
 
 async fn fetch(
@@ -46,7 +55,18 @@ pub async fn anti_delete(
     guild_id: GuildId,
     deleted_message_id: MessageId,
 ) -> Option<UserId> {
-    *data.anti_delete_cache.deletes_per_cycle.write().await += 1; // no need to scope since there's no let binding
+    let (settings, max_deletes_per_cycle) = {
+        let config = data.config.read();
+        (
+            config.anti_delete.guild(guild_id),
+            config.anti_delete.max_deletes_per_cycle,
+        )
+    };
+    let deletes = data
+        .anti_delete_cache
+        .deletes_per_cycle
+        .fetch_add(1, Ordering::Relaxed)
+        + 1;
     // increase value.
     {
         let now = Instant::now();
@@ -62,19 +82,26 @@ pub async fn anti_delete(
             return None;
         };
         let secs_since_last_updated = now.duration_since(value.last_updated).as_secs();
-        if value.val > 0 && value.val < 5 {
+        if value.val > 0 && value.val < settings.max_heat {
             value.val += 1;
             value.last_updated = now;
         }
         // low heat or debounce or cycle overflow = no check.
-        if value.val < 3
+        if value.val < settings.min_heat
             || secs_since_last_updated < 1
-            || *data.anti_delete_cache.deletes_per_cycle.read().await
-                > ANTI_DELETE_MAX_DELETES_PER_CYCLE
+            || deletes > max_deletes_per_cycle
         {
             return None;
         }
     }
+    // the author is already known if the message was sniped.
+    if let Some(user_id) = data
+        .anti_delete_cache
+        .snipes
+        .author(channel_id, deleted_message_id)
+    {
+        return Some(user_id);
+    }
     let last_deleted = {
         let Some(mut value) = data.anti_delete_cache.map.get_mut(&guild_id) else {
             fetch(ctx, channel_id, guild_id, deleted_message_id, data, false).await;
@@ -96,3 +123,141 @@ pub async fn anti_delete(
     }
     None
 }
+
+/// Counts a bulk delete towards the heat of a guild.
+pub fn bulk_heat(data: &Arc<Data>, guild_id: GuildId, count: usize) {
+    data.anti_delete_cache
+        .deletes_per_cycle
+        .fetch_add(count, Ordering::Relaxed);
+
+    let max_heat = data.config.read().anti_delete.guild(guild_id).max_heat;
+    let now = Instant::now();
+    let count = u16::try_from(count).unwrap_or(u16::MAX);
+    data.anti_delete_cache
+        .val
+        .entry(guild_id)
+        .and_modify(|value| {
+            value.val = value.val.saturating_add(count).min(max_heat);
+            value.last_updated = now;
+        })
+        .or_insert(Decay {
+            val: count.min(max_heat),
+            recorded_at: now,
+            last_updated: now,
+        });
+}
+
+/// Records an incident for a user whose messages are being mass deleted, sending mods a digest
+/// of the deleted messages and timing them out if configured.
+pub async fn incident(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    channel_id: GenericChannelId,
+    guild_id: GuildId,
+    user_id: UserId,
+) {
+    let settings = data.config.read().anti_delete.guild(guild_id);
+
+    let now = Instant::now();
+    if let Some(last) = data.anti_delete_cache.incidents.get(&(guild_id, user_id))
+        && now.duration_since(*last).as_secs() < settings.incident_cooldown
+    {
+        return;
+    }
+    data.anti_delete_cache
+        .incidents
+        .insert((guild_id, user_id), now);
+
+    // anything deleted since the last incident could have been part of this one.
+    let cutoff = chrono::Utc::now() - chrono::Duration::seconds(settings.incident_cooldown as i64);
+
+    let mut deleted = data
+        .anti_delete_cache
+        .snipes
+        .recent(channel_id)
+        .into_iter()
+        .filter(|s| s.kind == SnipeKind::Deleted && s.user_id == user_id && s.created_at > cutoff)
+        .collect::<Vec<_>>();
+    deleted.reverse();
+
+    let mut digest = String::new();
+    for snipe in &deleted {
+        write!(
+            digest,
+            "[{}] {}",
+            snipe.created_at.format("%H:%M:%S"),
+            snipe.content
+        )
+        .unwrap();
+        for url in &snipe.attachment_urls {
+            write!(digest, " <{url}>").unwrap();
+        }
+        digest.push('\n');
+    }
+
+    // heat is shared by the guild, so someone who lost a single message during a busy period
+    // isn't timed out.
+    let timeout = settings
+        .timeout
+        .filter(|_| deleted.len() >= settings.timeout_threshold);
+
+    let timed_out = match timeout {
+        Some(seconds) => {
+            timeout_member(
+                ctx,
+                guild_id,
+                user_id,
+                seconds,
+                "Automatic timeout for mass message deletion.",
+            )
+            .await
+        }
+        None => false,
+    };
+
+    if let Err(e) = data
+        .database
+        .insert_anti_delete_incident(
+            guild_id,
+            channel_id,
+            user_id,
+            deleted.len() as i32,
+            &digest,
+            timed_out,
+        )
+        .await
+    {
+        println!("Failed to record anti delete incident: {e}");
+    }
+
+    let mut embed = log_embed("Possible mass deletion?", Colour::DARK_RED, None)
+        .description(format!("Triggered on <@{user_id}> in <#{channel_id}>"))
+        .field("Recently deleted", field_value(&digest), false)
+        .footer(CreateEmbedFooter::new(
+            "This doesn't check my own database or oinks database.",
+        ));
+
+    if let Some(seconds) = timeout {
+        let action = if timed_out {
+            format!(
+                "Timed out for {} seconds.",
+                seconds.min(MAX_TIMEOUT_SECONDS)
+            )
+        } else {
+            String::from("Failed to time out.")
+        };
+        embed = embed.field("Action", action, false);
+    }
+
+    // the embed field only fits so much, so attach the full digest when needed.
+    let files = if digest.chars().count() > 1024 {
+        vec![CreateAttachment::bytes(
+            digest.into_bytes(),
+            format!("deleted-{user_id}.txt"),
+        )]
+    } else {
+        vec![]
+    };
+
+    mirror_with_files(ctx, data, guild_id, LogEvent::AntiDelete, embed, files).await;
+}
@@ -1,17 +1,34 @@
 use std::sync::{Arc, LazyLock};
 
-use lumi::serenity_prelude::{self as serenity, CreateMessage, GuildId, Message};
-use moth_core::data::structs::Data;
+use lumi::serenity_prelude::{self as serenity, CreateMessage, Message};
+use moth_core::data::structs::{Data, InviteLookup};
 use regex::Regex;
 
-use ::serenity::all::{CreateAllowedMentions, CreateEmbedAuthor, GenericChannelId};
-use resvg::{tiny_skia::Pixmap, usvg::Tree};
+use ::serenity::all::{CreateAllowedMentions, CreateEmbedAuthor};
+use ::serenity::http::HttpError;
+use resvg::{
+    tiny_skia::{
+        FillRule, FilterQuality, Paint, PathBuilder, Pattern, Pixmap, SpreadMode, Transform,
+    },
+    usvg::Tree,
+};
 
 use crate::Error;
 
 pub static INVITE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"discord(?:(?:app)?\.com/invite|\.gg)/([a-zA-Z0-9-]+)").unwrap());
 
+const CARD_TEMPLATE: &str = include_str!("invite_card.svg");
+
+/// Text in the card is useless without fonts, so load the system fonts once.
+static SVG_OPTIONS: LazyLock<usvg::Options<'static>> = LazyLock::new(|| {
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    options
+});
+
+/// Reports invites to servers that aren't allowed in the guild, deleting the message if
+/// configured.
 pub async fn moderate_invites(
     ctx: &serenity::Context,
     data: &Arc<Data>,
@@ -21,27 +38,53 @@ pub async fn moderate_invites(
         return Ok(());
     };
 
-    if guild_id != GuildId::new(98226572468690944) {
+    if msg.author.bot() {
+        return Ok(());
+    }
+
+    let Some(settings) = data.config.read().invites.guilds.get(&guild_id).cloned() else {
+        return Ok(());
+    };
+
+    if msg
+        .member
+        .as_ref()
+        .is_some_and(|m| m.roles.iter().any(|r| settings.exempt_roles.contains(r)))
+    {
         return Ok(());
     }
 
     let mut invites = Vec::new();
-    for invite in INVITE.find_iter(&msg.content) {
-        let Ok(details) = ctx
-            .http
-            .get_invite(invite.as_str(), false, false, None)
-            .await
-        else {
+    for captures in INVITE.captures_iter(&msg.content) {
+        let Some(lookup) = lookup_invite(ctx, data, &captures[1]).await else {
             continue;
         };
 
-        let Some(guild) = details.guild else {
+        if lookup.guild_id == guild_id || settings.allowed_guilds.contains(&lookup.guild_id) {
             continue;
-        };
+        }
 
-        invites.push((details.code, guild.name));
+        if !invites
+            .iter()
+            .any(|i: &InviteLookup| i.guild_id == lookup.guild_id)
+        {
+            invites.push(lookup);
+        }
+    }
+
+    if invites.is_empty() {
+        return Ok(());
     }
 
+    let deleted = settings.delete
+        && msg
+            .delete(
+                &ctx.http,
+                Some("Posted an invite to a server that isn't allowed."),
+            )
+            .await
+            .is_ok();
+
     let mut embeds = Vec::with_capacity(invites.len());
     let mut builder = CreateMessage::new().allowed_mentions(
         CreateAllowedMentions::new()
@@ -49,81 +92,174 @@ pub async fn moderate_invites(
             .all_roles(false)
             .everyone(false),
     );
-    let mut first_name = None;
-    for (index, (code, name)) in invites.iter().enumerate() {
-        let Ok(response) = data
-            .reqwest
-            .get(format!("https://invidget.switchblade.xyz/{code}"))
-            .send()
-            .await
-        else {
-            continue;
-        };
-
-        // https://github.com/SwitchbladeBot/invidget/pull/82
-        let text = response.text().await?.replace("image/jpg", "image/jpeg");
+    for (index, invite) in invites.iter().take(10).enumerate() {
+        let mut embed =
+            serenity::CreateEmbed::new().description(format!("https://discord.gg/{}", invite.code));
 
-        let Ok(png_data) = convert_svg_to_png(&text) else {
-            continue;
-        };
-
-        let attachment_name = format!("{index}.png");
-        let attachment = serenity::CreateAttachment::bytes(png_data, attachment_name.clone());
-        builder = builder.add_file(attachment);
-
-        let mut embed = serenity::CreateEmbed::new()
-            .attachment(attachment_name)
-            .description(format!("https://discord.gg/{code}"));
+        match render_card(data, invite).await {
+            Ok(png_data) => {
+                let attachment_name = format!("{index}.png");
+                builder = builder.add_file(serenity::CreateAttachment::bytes(
+                    png_data,
+                    attachment_name.clone(),
+                ));
+                embed = embed.attachment(attachment_name);
+            }
+            Err(e) => println!("Failed to render invite card: {e}"),
+        }
 
         if index == 0 {
             embed = embed.author(CreateEmbedAuthor::from(&msg.author));
         }
 
-        if first_name.is_none() {
-            first_name = Some(name);
-        }
-
-        if embeds.len() < 10 {
-            embeds.push(embed);
-        }
+        embeds.push(embed);
     }
 
     builder = builder.embeds(&embeds);
 
-    if !embeds.is_empty() {
-        if embeds.len() == 1 {
-            builder = builder.content(format!(
-                "{} posted an invite to {} in <#{}>",
-                msg.author,
-                first_name.expect("This should always be populated."),
-                msg.channel_id
-            ));
-        } else {
-            builder = builder.content(format!(
-                "{} posted multiple invites in <#{}>",
-                msg.author, msg.channel_id
-            ));
+    let action = if deleted { ", it was deleted." } else { "" };
+    builder = builder.content(if invites.len() == 1 {
+        format!(
+            "{} posted an invite to {} in <#{}>{action}",
+            msg.author, invites[0].guild_name, msg.channel_id
+        )
+    } else {
+        format!(
+            "{} posted multiple invites in <#{}>{action}",
+            msg.author, msg.channel_id
+        )
+    });
+
+    settings
+        .report_channel
+        .send_message(&ctx.http, builder)
+        .await?;
+
+    Ok(())
+}
+
+/// Looks up the server an invite code points to, caching the result.
+async fn lookup_invite(ctx: &serenity::Context, data: &Data, code: &str) -> Option<InviteLookup> {
+    if let Some(cached) = data.invite_lookups.cache.get(&code.to_string()) {
+        return cached;
+    }
+
+    let lookup = match ctx.http.get_invite(code, true, false, None).await {
+        Ok(invite) => invite.guild.map(|guild| InviteLookup {
+            code: invite.code.to_string(),
+            guild_id: guild.id,
+            guild_name: guild.name.to_string(),
+            icon_url: guild.icon.map(|hash| {
+                format!(
+                    "https://cdn.discordapp.com/icons/{}/{hash}.png?size=128",
+                    guild.id
+                )
+            }),
+            members: invite.approximate_member_count.map(u64::from),
+            online: invite.approximate_presence_count.map(u64::from),
+        }),
+        // unknown invites are cached too, so spamming a dead invite doesn't spam the api.
+        Err(serenity::Error::Http(HttpError::UnsuccessfulRequest(response)))
+            if response.status_code.as_u16() == 404 =>
+        {
+            None
         }
+        // anything else may well work next time.
+        Err(e) => {
+            println!("Failed to look up invite {code}: {e}");
+            return None;
+        }
+    };
 
-        GenericChannelId::new(277163440999628800)
-            .send_message(&ctx.http, builder)
-            .await?;
+    data.invite_lookups
+        .cache
+        .insert(code.to_string(), lookup.clone());
+
+    lookup
+}
+
+/// Renders an invite card from the svg template, drawing the server icon over the initials if
+/// it can be fetched.
+async fn render_card(data: &Data, invite: &InviteLookup) -> Result<Vec<u8>, Error> {
+    let initials = invite
+        .guild_name
+        .split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(3)
+        .collect::<String>();
+    let count = |count: Option<u64>| count.map_or_else(|| String::from("?"), |c| c.to_string());
+
+    let svg = CARD_TEMPLATE
+        .replace("{{initials}}", &escape_xml(&initials))
+        .replace(
+            "{{name}}",
+            &escape_xml(&crate::logging::truncate(&invite.guild_name, 32)),
+        )
+        .replace("{{online}}", &count(invite.online))
+        .replace("{{members}}", &count(invite.members))
+        .replace("{{code}}", &escape_xml(&invite.code));
+
+    let mut pixmap = render_svg(&svg)?;
+
+    if let Some(url) = &invite.icon_url
+        && let Ok(response) = data.reqwest.get(url).send().await
+        && let Ok(bytes) = response.bytes().await
+        && let Ok(icon) = Pixmap::decode_png(&bytes)
+    {
+        draw_icon(&mut pixmap, &icon);
     }
 
-    Ok(())
+    Ok(pixmap.encode_png()?)
+}
+
+/// Draws the icon over the placeholder in the template, scaled and rounded to fit.
+#[allow(clippy::cast_precision_loss)]
+fn draw_icon(pixmap: &mut Pixmap, icon: &Pixmap) {
+    const X: f32 = 16.0;
+    const Y: f32 = 40.0;
+    const SIZE: f32 = 56.0;
+
+    let scale = SIZE / icon.width().max(1) as f32;
+    let transform = Transform::from_row(scale, 0.0, 0.0, scale, X, Y);
+
+    let mut paint = Paint::default();
+    paint.shader = Pattern::new(
+        icon.as_ref(),
+        SpreadMode::Pad,
+        FilterQuality::Bicubic,
+        1.0,
+        transform,
+    );
+    paint.anti_alias = true;
+
+    let Some(circle) = PathBuilder::from_circle(X + SIZE / 2.0, Y + SIZE / 2.0, SIZE / 2.0) else {
+        return;
+    };
+
+    pixmap.fill_path(
+        &circle,
+        &paint,
+        FillRule::Winding,
+        Transform::identity(),
+        None,
+    );
 }
 
-fn convert_svg_to_png(svg_data: &str) -> Result<Vec<u8>, Error> {
-    let tree = Tree::from_str(svg_data, &usvg::Options::default())?;
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn render_svg(svg_data: &str) -> Result<Pixmap, Error> {
+    let tree = Tree::from_str(svg_data, &SVG_OPTIONS)?;
 
     let size = tree.size().to_int_size();
     let mut pixmap = Pixmap::new(size.width(), size.height()).expect("Failed to create a pixmap");
 
-    resvg::render(
-        &tree,
-        resvg::tiny_skia::Transform::default(),
-        &mut pixmap.as_mut(),
-    );
+    resvg::render(&tree, Transform::default(), &mut pixmap.as_mut());
 
-    Ok(pixmap.encode_png()?)
+    Ok(pixmap)
 }
@@ -0,0 +1,104 @@
+use std::{fmt::Write, sync::Arc};
+
+use lumi::serenity_prelude::{
+    self as serenity, Colour, CreateAllowedMentions, CreateEmbed, CreateEmbedAuthor,
+    CreateEmbedFooter, CreateMessage, Message,
+};
+use moth_core::data::structs::Data;
+use moth_filter::links::{LinkReason, check_links};
+
+use crate::Error;
+
+/// Deletes messages with links to blocklisted domains and alerts the mods, look-alike domains are
+/// only deleted if configured.
+pub async fn moderate_links(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    msg: &Message,
+) -> Result<(), Error> {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(());
+    };
+
+    if msg.author.bot() {
+        return Ok(());
+    }
+
+    let (settings, blocklist) = {
+        let config = data.config.read();
+        let Some(settings) = config.scam_links.guilds.get(&guild_id).cloned() else {
+            return Ok(());
+        };
+
+        (settings, config.scam_links.blocklist.clone())
+    };
+
+    if msg
+        .member
+        .as_ref()
+        .is_some_and(|m| m.roles.iter().any(|r| settings.exempt_roles.contains(r)))
+    {
+        return Ok(());
+    }
+
+    let allowlist = data.database.get_link_allowlist(guild_id).await?;
+    let matches = check_links(&msg.content, &blocklist, &allowlist);
+
+    if matches.is_empty() {
+        return Ok(());
+    }
+
+    // look-alikes catch the odd legitimate site, so they are only reported unless configured.
+    let delete = settings.delete_lookalikes
+        || matches
+            .iter()
+            .any(|m| matches!(m.reason, LinkReason::Blocklisted(_)));
+    let deleted = delete
+        && msg
+            .delete(&ctx.http, Some("Posted a scam link."))
+            .await
+            .is_ok();
+
+    let mut domains = String::new();
+    for link_match in &matches {
+        let reason = match &link_match.reason {
+            LinkReason::Blocklisted(entry) => format!("blocklisted as `{entry}`"),
+            LinkReason::Lookalike(official) => format!("imitates `{official}`"),
+        };
+        writeln!(domains, "`{}` {reason}", link_match.domain).unwrap();
+    }
+
+    let embed = CreateEmbed::new()
+        .author(CreateEmbedAuthor::from(&msg.author))
+        .title(match (delete, deleted) {
+            (_, true) => "Scam link deleted",
+            (true, false) => "Scam link (failed to delete)",
+            (false, _) => "Possible scam link",
+        })
+        .description(format!(
+            "{} in <#{}>\n\n{}",
+            msg.author,
+            msg.channel_id,
+            crate::logging::truncate(&msg.content, 1500)
+        ))
+        .field("Domains", domains, false)
+        .colour(Colour::RED)
+        .footer(CreateEmbedFooter::new(format!(
+            "User ID: {} • Use link-allow to allow a domain",
+            msg.author.id
+        )));
+
+    let mut builder = CreateMessage::new().embed(embed);
+    if let Some(role_id) = settings.ping_role {
+        builder = builder
+            .content(format!("<@&{role_id}>"))
+            .allowed_mentions(CreateAllowedMentions::new().roles(vec![role_id]));
+    }
+
+    settings
+        .report_channel
+        .send_message(&ctx.http, builder)
+        .await?;
+
+    Ok(())
+}
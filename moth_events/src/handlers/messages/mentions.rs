@@ -0,0 +1,225 @@
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use lumi::serenity_prelude::{
+    self as serenity, Colour, GenericChannelId, Message, MessageId, RoleId, UserId,
+};
+use moth_core::{
+    config::{LogEvent, MentionFloodGuild},
+    data::structs::{Data, RecentMentions},
+};
+
+use crate::{
+    Error,
+    helper::timeout_member,
+    logging::{field_value, log_embed, mirror},
+};
+
+use super::spam::delete_messages;
+
+/// The limit that was broken and the messages that broke it.
+struct Flood {
+    /// Whether it was a single message over the limit, rather than the window.
+    single: bool,
+    users: HashSet<UserId>,
+    roles: HashSet<RoleId>,
+    everyone: usize,
+    messages: Vec<(GenericChannelId, MessageId)>,
+}
+
+impl Flood {
+    fn total(&self) -> usize {
+        self.users.len() + self.roles.len() + self.everyone
+    }
+}
+
+/// Counts mentions per message and per member over a sliding window, acting on members that go
+/// over the limits of the guild.
+pub async fn check_mentions(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    msg: &Message,
+) -> Result<(), Error> {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(());
+    };
+
+    if msg.author.bot() {
+        return Ok(());
+    }
+
+    let Some(settings) = data
+        .config
+        .read()
+        .mention_flood
+        .guilds
+        .get(&guild_id)
+        .cloned()
+    else {
+        return Ok(());
+    };
+
+    if msg
+        .member
+        .as_ref()
+        .is_some_and(|m| m.roles.iter().any(|r| settings.exempt_roles.contains(r)))
+    {
+        return Ok(());
+    }
+
+    let mentions = RecentMentions {
+        at: Instant::now(),
+        channel_id: msg.channel_id,
+        message_id: msg.id,
+        users: msg
+            .mentions
+            .iter()
+            .map(|u| u.id)
+            .filter(|id| *id != msg.author.id)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect(),
+        roles: msg
+            .mention_roles
+            .iter()
+            .copied()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect(),
+        // counted from the content, members without permission don't actually ping anyone.
+        everyone: msg.content.matches("@everyone").count() + msg.content.matches("@here").count(),
+    };
+
+    if mentions.users.is_empty() && mentions.roles.is_empty() && mentions.everyone == 0 {
+        return Ok(());
+    }
+
+    let flood = {
+        let mut recent = data
+            .spam
+            .mentions
+            .entry((guild_id, msg.author.id))
+            .or_default();
+
+        let window = Duration::from_secs(settings.window);
+        while recent.front().is_some_and(|m| m.at.elapsed() > window) {
+            recent.pop_front();
+        }
+
+        let single = flood_of(std::iter::once(&mentions), true);
+        recent.push_back(mentions);
+        let windowed = flood_of(recent.iter(), false);
+
+        let flood = if single.total() > settings.per_message {
+            Some(single)
+        } else if windowed.total() > settings.per_window {
+            Some(windowed)
+        } else {
+            None
+        };
+
+        // cleared so the same burst is only acted on once.
+        if flood.is_some() {
+            recent.clear();
+        }
+
+        flood
+    };
+
+    if let Some(flood) = flood {
+        act(ctx, data, msg, &settings, &flood).await;
+    }
+
+    Ok(())
+}
+
+fn flood_of<'a>(mentions: impl Iterator<Item = &'a RecentMentions>, single: bool) -> Flood {
+    let mut flood = Flood {
+        single,
+        users: HashSet::new(),
+        roles: HashSet::new(),
+        everyone: 0,
+        messages: Vec::new(),
+    };
+
+    for m in mentions {
+        flood.users.extend(&m.users);
+        flood.roles.extend(&m.roles);
+        flood.everyone += m.everyone;
+        flood.messages.push((m.channel_id, m.message_id));
+    }
+
+    flood
+}
+
+async fn act(
+    ctx: &serenity::Context,
+    data: &Data,
+    msg: &Message,
+    settings: &MentionFloodGuild,
+    flood: &Flood,
+) {
+    let Some(guild_id) = msg.guild_id else {
+        return;
+    };
+    let reason = "Mentioned too many members or roles.";
+
+    let deleted = if settings.delete {
+        let deleted = delete_messages(ctx, &flood.messages, reason).await;
+        format!("Deleted {deleted}/{} messages", flood.messages.len())
+    } else {
+        String::from("Not deleted")
+    };
+
+    let timeout = match settings.timeout {
+        Some(seconds) if timeout_member(ctx, guild_id, msg.author.id, seconds, reason).await => {
+            format!("timed out for {} minutes", seconds / 60)
+        }
+        Some(_) => String::from("failed to time out"),
+        None => String::from("not timed out"),
+    };
+
+    let (description, limit) = if flood.single {
+        (
+            format!(
+                "<@{}> sent a message with too many mentions.",
+                msg.author.id
+            ),
+            settings.per_message,
+        )
+    } else {
+        (
+            format!(
+                "<@{}> mentioned too many members or roles in {} messages over {} seconds.",
+                msg.author.id,
+                flood.messages.len(),
+                settings.window
+            ),
+            settings.per_window,
+        )
+    };
+
+    let mut counts = format!("{} users, {} roles", flood.users.len(), flood.roles.len());
+    if flood.everyone != 0 {
+        counts.push_str(&format!(", {} @everyone or @here", flood.everyone));
+    }
+
+    let embed = log_embed("Mention flood", Colour::RED, Some(&msg.author))
+        .description(description)
+        .field(
+            "Mentions",
+            format!(
+                "{counts} ({} over the limit of {limit})",
+                flood.total() - limit
+            ),
+            false,
+        )
+        .field("Channel", format!("<#{}>", msg.channel_id), true)
+        .field("Action", format!("{deleted}, {timeout}."), true)
+        .field("Content", field_value(&msg.content), false);
+
+    mirror(ctx, data, guild_id, LogEvent::Spam, embed).await;
+}
@@ -1,20 +1,29 @@
+use std::collections::HashMap;
 use std::fmt::Write;
 use std::sync::Arc;
 
 mod anti_delete;
 mod database;
+pub mod snipe;
 use ::serenity::all::GenericChannelId;
 pub use database::EMOJI_REGEX;
 pub mod invites;
+mod links;
+mod mentions;
+mod spam;
 
+use crate::diff::{diff_attachments, diff_words, has_changes, render_ansi, render_markdown};
 use crate::helper::{get_channel_name, get_guild_name_override};
+use crate::logging::{before_after, field_value, log_embed, mirror, mirror_with_files};
 use crate::{Data, Error};
 
 use moth_ansi::{CYAN, DIM, HI_BLACK, HI_RED, RESET};
+use moth_core::config::LogEvent;
+use moth_core::data::database::snipes::{Snipe, SnipeKind};
 
 use lumi::serenity_prelude::{
-    self as serenity, Colour, CreateEmbed, CreateEmbedFooter, CreateMessage, GuildId, Message,
-    MessageId, UserId,
+    self as serenity, Colour, CreateAttachment, CreateEmbedFooter, GuildId, Message, MessageId,
+    UserId,
 };
 
 pub async fn message(ctx: &serenity::Context, msg: &Message, data: Arc<Data>) -> Result<(), Error> {
@@ -55,177 +64,17 @@ pub async fn message(ctx: &serenity::Context, msg: &Message, data: Arc<Data>) ->
 
     let _ = tokio::join!(
         handle_dm(ctx, msg),
-        auto_super_poop(ctx, msg),
+        super::appearance_roles::check_message(ctx, &data, msg),
+        super::appearances::record_user(&data, &msg.author),
+        invites::moderate_invites(ctx, &data, msg),
+        links::moderate_links(ctx, &data, msg),
+        spam::check_cross_channel(ctx, &data, msg),
+        mentions::check_mentions(ctx, &data, msg),
     );
 
     Ok(())
 }
 
-async fn auto_super_poop(ctx: &serenity::Context, msg: &Message) -> Result<(), Error> {
-    let expected_guild = GuildId::new(98226572468690944);
-    let super_poop_role = serenity::RoleId::new(1384235804678684712);
-    let announce_thread = GenericChannelId::new(1390062742274310317);
-
-    // Early return if not the correct guild
-    if msg.guild_id != Some(expected_guild) {
-        return Ok(());
-    }
-
-    let send_message = "Seems like you have a horrible avatar decoration or nameplate! As such, \
-                        you have been awarded with a role that reflects your choice! Remove it to \
-                        remove this role";
-
-    let data = ctx.data_ref::<Data>();
-
-    // Check if user is marked as auto_pooped in memory
-    let auto_pooped = data.auto_pooped.contains(&msg.author.id);
-
-    // Check if user currently qualifies for the role (based on nameplate/avatar)
-    let should_be_pooped = should_be_pooped(msg);
-
-    // Check if user currently has the role
-    let has_super_poop_role = msg
-        .member
-        .as_ref()
-        .is_some_and(|member| member.roles.contains(&super_poop_role));
-
-    if auto_pooped {
-        // User is marked as auto_pooped
-        if should_be_pooped && !has_super_poop_role {
-            // Should have the role but doesn't - add it back
-            ctx.http
-                .add_member_role(
-                    expected_guild,
-                    msg.author.id,
-                    super_poop_role,
-                    Some("User contains shitty decor/nameplate but no longer had the role"),
-                )
-                .await?;
-
-            msg.channel_id
-                .send_message(
-                    &ctx.http,
-                    CreateMessage::new()
-                        .content(send_message)
-                        .reference_message(msg),
-                )
-                .await?;
-
-            announce_thread
-                .send_message(
-                    &ctx.http,
-                    CreateMessage::new().content(format!(
-                        "Added super poop to <@{}> because they should still be pooped, but no \
-                         longer had the role (rejoined?)",
-                        msg.author.id
-                    )),
-                )
-                .await?;
-        } else if !should_be_pooped && has_super_poop_role {
-            // Should NOT have the role but does - remove it and update DB/memory
-            ctx.http
-                .remove_member_role(
-                    expected_guild,
-                    msg.author.id,
-                    super_poop_role,
-                    Some("Member no longer has the shitty decor/nameplate"),
-                )
-                .await?;
-
-            sqlx::query!(
-                "DELETE FROM auto_bad_role WHERE user_id = $1",
-                data.database.get_user(msg.author.id).await?.id,
-            )
-            .execute(&data.database.db)
-            .await?;
-
-            data.auto_pooped.remove(&msg.author.id);
-
-            announce_thread
-                .send_message(
-                    &ctx.http,
-                    CreateMessage::new().content(format!(
-                        "Removed super poop from <@{}> because they are no longer using the \
-                         decor/nameplate.",
-                        msg.author.id
-                    )),
-                )
-                .await?;
-        }
-    } else {
-        // User is NOT marked as auto_pooped
-
-        if should_be_pooped && !has_super_poop_role {
-            // user should be pooped, does not have the role.
-            sqlx::query!(
-                "INSERT INTO auto_bad_role (user_id) VALUES ($1)",
-                data.database.get_user(msg.author.id).await?.id,
-            )
-            .execute(&data.database.db)
-            .await?;
-
-            data.auto_pooped.insert(msg.author.id);
-
-            ctx.http
-                .add_member_role(
-                    expected_guild,
-                    msg.author.id,
-                    super_poop_role,
-                    Some("User has shitty decor/nameplate."),
-                )
-                .await?;
-
-            msg.channel_id
-                .send_message(
-                    &ctx.http,
-                    CreateMessage::new()
-                        .content(send_message)
-                        .reference_message(msg),
-                )
-                .await?;
-
-            announce_thread
-                .send_message(
-                    &ctx.http,
-                    CreateMessage::new().content(format!(
-                        "Added super poop to <@{}> because they have a shitty decor/nameplate. {}",
-                        msg.author.id,
-                        msg.link()
-                    )),
-                )
-                .await?;
-        }
-        // the user either do have the role already, or shouldn't have the role, but *they weren't* done by the bot, so it doesn't matter.
-    }
-
-    Ok(())
-}
-
-fn should_be_pooped(msg: &Message) -> bool {
-    let decor_sku = serenity::SkuId::new(1387888352539312288);
-
-    if let Some(user_decor) = msg.author.avatar_decoration_data
-        && user_decor.sku_id == decor_sku
-    {
-        return true;
-    }
-
-    if let Some(nameplate) = msg.author.collectibles.as_ref().map(|c| &c.nameplate)
-        && let Some(nameplate) = nameplate
-        && &nameplate.asset == "nameplates/paper/skibidi_toilet/"
-    {
-        return true;
-    }
-
-    if let Some(Some(member_decor)) = msg.member.as_ref().map(|m| m.avatar_decoration_data)
-        && member_decor.sku_id == decor_sku
-    {
-        return true;
-    }
-
-    false
-}
-
 pub async fn message_edit(
     ctx: &serenity::Context,
     old_if_available: &Option<Message>,
@@ -242,27 +91,71 @@ pub async fn message_edit(
             return Ok(());
         }
 
-        if old_message.content != new_message.content {
-            let (attachments, embeds) = attachments_embed_fmt(new_message);
+        let changes = diff_words(&old_message.content, &new_message.content);
+        let attachment_changes =
+            diff_attachments(&old_message.attachments, &new_message.attachments);
+
+        if has_changes(&changes) || !attachment_changes.is_empty() {
+            let (_, embeds) = attachments_embed_fmt(new_message);
 
             println!(
-                "{CYAN}[{}] [#{}] A message by {RESET}{}{CYAN} was edited:",
+                "{CYAN}[{}] [#{}] A message by {RESET}{}{CYAN} was edited:{RESET}",
                 guild_name,
                 channel_name,
                 new_message.author.tag()
             );
             println!(
-                "BEFORE: {}: {}",
+                "{}: {}{}{}{RESET}",
                 new_message.author.tag(),
-                old_message.content
-            ); // potentially check old attachments in the future.
-            println!(
-                "AFTER: {}: {}{}{}{RESET}",
-                new_message.author.tag(),
-                new_message.content,
-                attachments.as_deref().unwrap_or(""),
+                render_ansi(&changes),
+                attachment_changes.ansi(),
                 embeds.as_deref().unwrap_or("")
             );
+
+            if let Some(guild_id) = guild_id {
+                let mut embed =
+                    log_embed("Message edited", Colour::GOLD, Some(&new_message.author))
+                        .description(format!(
+                            "<#{}> [Jump to message]({})",
+                            new_message.channel_id,
+                            new_message.link()
+                        ));
+
+                if has_changes(&changes) {
+                    embed = embed.field(
+                        "Changes",
+                        field_value(&render_markdown(&changes, 1024)),
+                        false,
+                    );
+                    embed = before_after(embed, &old_message.content, &new_message.content);
+                }
+
+                if !attachment_changes.is_empty() {
+                    embed = embed.field(
+                        "Attachments",
+                        field_value(&attachment_changes.markdown()),
+                        false,
+                    );
+                }
+
+                mirror(ctx, &data, guild_id, LogEvent::MessageEdit, embed).await;
+            }
+
+            let removed = attachment_changes
+                .removed
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>();
+
+            snipe::record(
+                ctx,
+                &data,
+                new_message,
+                SnipeKind::Edited,
+                Some(old_message.content.to_string()),
+                &removed,
+            )
+            .await;
         }
     } else {
         println!(
@@ -307,32 +200,238 @@ pub async fn message_delete(
             attachments_fmt.as_deref().unwrap_or(""),
             embeds_fmt.as_deref().unwrap_or("")
         );
+
+        if let Some(guild_id) = guild_id {
+            let mut embed = log_embed("Message deleted", Colour::RED, Some(&message.author))
+                .description(format!("<#{channel_id}>"))
+                .field("Content", field_value(&content), false);
+
+            if !message.attachments.is_empty() {
+                let urls = message
+                    .attachments
+                    .iter()
+                    .map(|a| a.url.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                embed = embed.field("Attachments", field_value(&urls), false);
+            }
+
+            mirror(ctx, &data, guild_id, LogEvent::MessageDelete, embed).await;
+        }
+
+        snipe::record(
+            ctx,
+            &data,
+            &message,
+            SnipeKind::Deleted,
+            None,
+            &message.attachments,
+        )
+        .await;
     } else {
         println!(
             "{HI_RED}{DIM}A message (ID:{deleted_message_id}) was deleted but was not in \
              cache{RESET}"
         );
+
+        if let Some(guild_id) = guild_id {
+            let embed = log_embed("Message deleted", Colour::RED, None).description(format!(
+                "A message (ID:{deleted_message_id}) was deleted in <#{channel_id}> but was not \
+                 in cache."
+            ));
+
+            mirror(ctx, &data, guild_id, LogEvent::MessageDelete, embed).await;
+        }
     }
 
     if let Some(guild_id) = guild_id
         && let Some(user) =
             anti_delete::anti_delete(ctx, &data, channel_id, guild_id, deleted_message_id).await
-        && guild_id.get() == 98226572468690944
     {
-        let embed = CreateEmbed::new()
-            .title("Possible mass deletion?")
-            .description(format!("Triggered on <@{user}>"))
-            .footer(CreateEmbedFooter::new(
-                "This doesn't check my own database or oinks database.",
-            ));
-        let builder = CreateMessage::new().embed(embed);
-        let _ = GenericChannelId::new(1284217769423798282)
-            .send_message(&ctx.http, builder)
-            .await;
+        anti_delete::incident(ctx, &data, channel_id, guild_id, user).await;
     }
     Ok(())
 }
 
+pub async fn message_delete_bulk(
+    ctx: &serenity::Context,
+    channel_id: GenericChannelId,
+    message_ids: &[MessageId],
+    guild_id: Option<GuildId>,
+    data: Arc<Data>,
+) -> Result<(), Error> {
+    let guild_name = get_guild_name_override(ctx, &data, guild_id);
+    let channel_name = get_channel_name(ctx, guild_id, channel_id).await;
+
+    let mut cached = Vec::new();
+    let mut missing = Vec::new();
+    for id in message_ids {
+        match ctx.cache.message(channel_id, *id) {
+            Some(message) => cached.push(message.clone()),
+            None => missing.push(*id),
+        }
+    }
+
+    // edited messages are in the snipe store with their latest content, newest first.
+    let mut sniped = HashMap::new();
+    if !missing.is_empty()
+        && let Ok(snipes) = snipe::get_snipes(&data, channel_id).await
+    {
+        for snipe in snipes {
+            if missing.contains(&snipe.message_id) {
+                sniped.entry(snipe.message_id).or_insert(snipe);
+            }
+        }
+
+        missing.retain(|id| !sniped.contains_key(id));
+    }
+
+    // fall back to the anti delete cache and the database to at least know who the author was.
+    let mut authors = HashMap::new();
+    if !missing.is_empty() {
+        if let Some(guild_id) = guild_id
+            && let Some(value) = data.anti_delete_cache.map.get(&guild_id)
+        {
+            for id in &missing {
+                if let Some(user_id) = value.msg_user_cache.get(id) {
+                    authors.insert(*id, *user_id);
+                }
+            }
+        }
+
+        if let Ok(stored) = data.database.get_message_authors(&missing).await {
+            authors.extend(stored);
+        }
+    }
+
+    println!(
+        "{HI_RED}{DIM}[{guild_name}] [#{channel_name}] {} messages were bulk deleted ({} cached, \
+         {} resolved from archive){RESET}",
+        message_ids.len(),
+        cached.len(),
+        sniped.len() + authors.len()
+    );
+
+    if let Some(guild_id) = guild_id {
+        anti_delete::bulk_heat(&data, guild_id, message_ids.len());
+
+        let mut counts: HashMap<UserId, usize> = HashMap::new();
+        for message in &cached {
+            *counts.entry(message.author.id).or_default() += 1;
+        }
+        for snipe in sniped.values() {
+            *counts.entry(snipe.user_id).or_default() += 1;
+        }
+        for user_id in authors.values() {
+            *counts.entry(*user_id).or_default() += 1;
+        }
+
+        let mut counts = counts.into_iter().collect::<Vec<_>>();
+        counts.sort_unstable_by(|a, b| b.1.cmp(&a.1));
+
+        let mut summary = String::new();
+        for (user_id, count) in counts.iter().take(10) {
+            writeln!(summary, "<@{user_id}>: {count}").unwrap();
+        }
+
+        let unknown = message_ids.len() - cached.len() - sniped.len() - authors.len();
+        if unknown > 0 {
+            writeln!(summary, "Unknown: {unknown}").unwrap();
+        }
+
+        let embed = log_embed("Messages bulk deleted", Colour::DARK_RED, None)
+            .description(format!(
+                "{} messages were deleted in <#{channel_id}>",
+                message_ids.len()
+            ))
+            .field("Authors", field_value(&summary), false);
+
+        let transcript = bulk_transcript(message_ids, &cached, &sniped, &authors);
+        let file =
+            CreateAttachment::bytes(transcript.into_bytes(), format!("deleted-{channel_id}.txt"));
+
+        mirror_with_files(
+            ctx,
+            &data,
+            guild_id,
+            LogEvent::MessageDelete,
+            embed,
+            vec![file],
+        )
+        .await;
+    }
+
+    // keep them snipeable like single deletes, in one batch and without archiving attachments, as
+    // a purge can be a hundred messages.
+    let snipes = cached
+        .iter()
+        .filter(|message| !message.author.bot())
+        .map(|message| snipe::unarchived(message, SnipeKind::Deleted))
+        .chain(sniped.into_values().map(|edited| Snipe {
+            kind: SnipeKind::Deleted,
+            old_content: None,
+            attachment_urls: Vec::new(),
+            archive_message_id: None,
+            created_at: chrono::Utc::now(),
+            ..(*edited).clone()
+        }))
+        .collect();
+
+    snipe::store_all(&data, channel_id, guild_id, snipes).await;
+
+    Ok(())
+}
+
+/// Builds a plain text transcript of bulk deleted messages, oldest first.
+fn bulk_transcript(
+    message_ids: &[MessageId],
+    cached: &[Message],
+    sniped: &HashMap<MessageId, Arc<Snipe>>,
+    authors: &HashMap<MessageId, UserId>,
+) -> String {
+    let mut message_ids = message_ids.to_vec();
+    message_ids.sort_unstable();
+
+    let mut transcript = String::new();
+    for id in message_ids {
+        let time = id.created_at().format("%Y-%m-%d %H:%M:%S");
+
+        if let Some(message) = cached.iter().find(|m| m.id == id) {
+            write!(
+                transcript,
+                "[{time}] {} ({}): {}",
+                message.author.tag(),
+                message.author.id,
+                message.content
+            )
+            .unwrap();
+
+            for attachment in &message.attachments {
+                write!(transcript, " <{}>", attachment.url).unwrap();
+            }
+
+            transcript.push('\n');
+        } else if let Some(snipe) = sniped.get(&id) {
+            writeln!(
+                transcript,
+                "[{time}] {} ({}): {}",
+                snipe.username, snipe.user_id, snipe.content
+            )
+            .unwrap();
+        } else if let Some(user_id) = authors.get(&id) {
+            writeln!(
+                transcript,
+                "[{time}] {user_id}: <content unavailable> ({id})"
+            )
+            .unwrap();
+        } else {
+            writeln!(transcript, "[{time}] <unknown message> ({id})").unwrap();
+        }
+    }
+
+    transcript
+}
+
 fn should_skip_msg(
     no_log_users: Option<&Vec<u64>>,
     no_log_channels: Option<&Vec<u64>>,
@@ -0,0 +1,194 @@
+use std::sync::Arc;
+
+use crate::Data;
+use futures_util::future::join_all;
+use lumi::serenity_prelude::{
+    self as serenity, Attachment, CreateAllowedMentions, CreateAttachment, CreateMessage,
+    GenericChannelId, GuildId, Message, MessageId,
+};
+use moth_core::data::database::snipes::{Snipe, SnipeKind};
+
+// attachments over this size aren't worth downloading and likely can't be uploaded anyway.
+const MAX_REUPLOAD_SIZE: u32 = 10 * 1024 * 1024;
+
+/// Records a deleted or edited message into the snipe ring and persists it.
+pub async fn record(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    message: &Message,
+    kind: SnipeKind,
+    old_content: Option<String>,
+    attachments: &[Attachment],
+) {
+    if message.author.bot() {
+        return;
+    }
+
+    let (archive_message_id, attachment_urls) =
+        match reupload(ctx, data, message.channel_id, message.id, attachments).await {
+            Some((id, urls)) => (Some(id), urls),
+            None => (
+                None,
+                attachments.iter().map(|a| a.url.to_string()).collect(),
+            ),
+        };
+
+    let snipe = Snipe {
+        old_content,
+        attachment_urls,
+        archive_message_id,
+        ..unarchived(message, kind)
+    };
+
+    store(data, message.channel_id, message.guild_id, snipe).await;
+}
+
+/// A snipe of a message that links its attachments on discord instead of the archive.
+#[must_use]
+pub fn unarchived(message: &Message, kind: SnipeKind) -> Snipe {
+    Snipe {
+        message_id: message.id,
+        user_id: message.author.id,
+        username: message.author.name.to_string(),
+        kind,
+        content: message.content.to_string(),
+        old_content: None,
+        attachment_urls: message
+            .attachments
+            .iter()
+            .map(|a| a.url.to_string())
+            .collect(),
+        archive_message_id: None,
+        created_at: chrono::Utc::now(),
+    }
+}
+
+/// Pushes a snipe into the ring of its channel and persists it.
+pub async fn store(
+    data: &Data,
+    channel_id: GenericChannelId,
+    guild_id: Option<GuildId>,
+    snipe: Snipe,
+) {
+    if let Err(e) = data
+        .database
+        .insert_snipe(channel_id, guild_id, &snipe)
+        .await
+    {
+        println!("Failed to persist snipe: {e}");
+    }
+
+    data.anti_delete_cache.snipes.push(channel_id, snipe);
+}
+
+/// Pushes snipes from the same channel into its ring and persists them together.
+pub async fn store_all(
+    data: &Data,
+    channel_id: GenericChannelId,
+    guild_id: Option<GuildId>,
+    snipes: Vec<Snipe>,
+) {
+    if let Err(e) = data
+        .database
+        .insert_snipes(channel_id, guild_id, &snipes)
+        .await
+    {
+        println!("Failed to persist snipes: {e}");
+    }
+
+    for snipe in snipes {
+        data.anti_delete_cache.snipes.push(channel_id, snipe);
+    }
+}
+
+/// Reuploads attachments to the archive channel, returning the archive message and new urls.
+async fn reupload(
+    ctx: &serenity::Context,
+    data: &Data,
+    channel_id: GenericChannelId,
+    message_id: MessageId,
+    attachments: &[Attachment],
+) -> Option<(MessageId, Vec<String>)> {
+    if attachments.is_empty() {
+        return None;
+    }
+
+    let archive_channel = data.config.read().events.snipe_archive_channel?;
+
+    let downloads = attachments
+        .iter()
+        .filter(|a| a.size <= MAX_REUPLOAD_SIZE)
+        .map(|attachment| async move {
+            let bytes = attachment.download().await.ok()?;
+            Some(CreateAttachment::bytes(
+                bytes,
+                attachment.filename.to_string(),
+            ))
+        });
+
+    let files = join_all(downloads)
+        .await
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+    if files.is_empty() {
+        return None;
+    }
+
+    let builder = CreateMessage::new()
+        .content(format!("Attachments of {message_id} in <#{channel_id}>"))
+        .allowed_mentions(CreateAllowedMentions::new())
+        .files(files);
+
+    let archived = archive_channel
+        .send_message(&ctx.http, builder)
+        .await
+        .ok()?;
+
+    Some((
+        archived.id,
+        archived
+            .attachments
+            .iter()
+            .map(|a| a.url.to_string())
+            .collect(),
+    ))
+}
+
+/// Gets the snipes in a channel, loading the persisted ring on first access.
+pub async fn get_snipes(
+    data: &Data,
+    channel_id: GenericChannelId,
+) -> Result<Vec<Arc<Snipe>>, crate::Error> {
+    if let Some(snipes) = data.anti_delete_cache.snipes.get(channel_id) {
+        return Ok(snipes);
+    }
+
+    let snipes = data
+        .database
+        .get_snipes(channel_id)
+        .await?
+        .into_iter()
+        .map(Arc::new)
+        .collect::<Vec<_>>();
+
+    Ok(data.anti_delete_cache.snipes.set_loaded(channel_id, snipes))
+}
+
+/// Fetches fresh attachment urls from the archive, as discord cdn links expire.
+pub async fn fresh_urls(ctx: &serenity::Context, data: &Data, snipe: &Snipe) -> Vec<String> {
+    let archive_channel = data.config.read().events.snipe_archive_channel;
+
+    if let (Some(channel), Some(message_id)) = (archive_channel, snipe.archive_message_id)
+        && let Ok(message) = channel.message(ctx, message_id).await
+    {
+        return message
+            .attachments
+            .iter()
+            .map(|a| a.url.to_string())
+            .collect();
+    }
+
+    snipe.attachment_urls.clone()
+}
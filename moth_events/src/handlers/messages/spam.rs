@@ -0,0 +1,307 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use image::imageops::FilterType;
+use lumi::serenity_prelude::{
+    self as serenity, Attachment, Colour, GenericChannelId, Message, MessageId,
+};
+use moth_core::{
+    config::{CrossChannelSpamGuild, LogEvent},
+    data::structs::{Data, RecentPost, SpamPayload},
+};
+
+use crate::{
+    Error,
+    helper::timeout_member,
+    logging::{field_value, log_embed, mirror},
+};
+
+/// Images larger than this aren't downloaded to be hashed.
+const MAX_IMAGE_SIZE: u32 = 8 * 1024 * 1024;
+
+/// Catches members posting the same content or attachment across many channels, which is
+/// usually a compromised account.
+pub async fn check_cross_channel(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    msg: &Message,
+) -> Result<(), Error> {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(());
+    };
+
+    if msg.author.bot() {
+        return Ok(());
+    }
+
+    let Some(settings) = data
+        .config
+        .read()
+        .cross_channel_spam
+        .guilds
+        .get(&guild_id)
+        .cloned()
+    else {
+        return Ok(());
+    };
+
+    if msg
+        .member
+        .as_ref()
+        .is_some_and(|m| m.roles.iter().any(|r| settings.exempt_roles.contains(r)))
+    {
+        return Ok(());
+    }
+
+    let payloads = payloads(data, msg, &settings).await;
+    if payloads.is_empty() {
+        return Ok(());
+    }
+
+    let copies = {
+        let mut posts = data
+            .spam
+            .posts
+            .entry((guild_id, msg.author.id))
+            .or_default();
+
+        let window = Duration::from_secs(settings.window);
+        while posts.front().is_some_and(|p| p.at.elapsed() > window) {
+            posts.pop_front();
+        }
+
+        posts.push_back(RecentPost {
+            at: Instant::now(),
+            channel_id: msg.channel_id,
+            message_id: msg.id,
+            payloads,
+        });
+
+        // cleared so the same burst is only acted on once.
+        let copies = find_copies(&posts, &settings);
+        if copies.is_some() {
+            posts.clear();
+        }
+
+        copies
+    };
+
+    if let Some(copies) = copies {
+        act(ctx, data, msg, &settings, &copies).await;
+    }
+
+    Ok(())
+}
+
+async fn payloads(
+    data: &Data,
+    msg: &Message,
+    settings: &CrossChannelSpamGuild,
+) -> Vec<SpamPayload> {
+    let mut payloads = Vec::new();
+
+    let content = msg
+        .content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    if content.chars().count() >= settings.min_length {
+        payloads.push(SpamPayload::Content(hash(&content)));
+    }
+
+    for attachment in &msg.attachments {
+        payloads.push(SpamPayload::Attachment {
+            size: attachment.size,
+            name: hash(&*attachment.filename),
+            image: image_hash(data, attachment).await,
+        });
+    }
+
+    payloads
+}
+
+fn hash(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A difference hash of an image, copies that were resized or recompressed only differ by a few
+/// bits.
+async fn image_hash(data: &Data, attachment: &Attachment) -> Option<u64> {
+    if attachment.size > MAX_IMAGE_SIZE
+        || !attachment
+            .content_type
+            .as_deref()
+            .is_some_and(|t| t.starts_with("image/"))
+    {
+        return None;
+    }
+
+    let bytes = data
+        .reqwest
+        .get(&*attachment.proxy_url)
+        .send()
+        .await
+        .ok()?
+        .bytes()
+        .await
+        .ok()?;
+
+    tokio::task::spawn_blocking(move || {
+        let image = image::load_from_memory(&bytes).ok()?;
+        let pixels = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+        let mut hash = 0u64;
+        for y in 0..8 {
+            for x in 0..8 {
+                let brighter = pixels.get_pixel(x, y)[0] < pixels.get_pixel(x + 1, y)[0];
+                hash = (hash << 1) | u64::from(brighter);
+            }
+        }
+
+        Some(hash)
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// Finds the posts sharing something with the latest one, if they span enough channels.
+fn find_copies(
+    posts: &VecDeque<RecentPost>,
+    settings: &CrossChannelSpamGuild,
+) -> Option<Vec<(GenericChannelId, MessageId)>> {
+    let latest = posts.back()?;
+
+    latest.payloads.iter().find_map(|payload| {
+        let copies = posts
+            .iter()
+            .filter(|post| {
+                post.payloads
+                    .iter()
+                    .any(|other| same_payload(payload, other, settings.image_distance))
+            })
+            .map(|post| (post.channel_id, post.message_id))
+            .collect::<Vec<_>>();
+
+        let channels = copies.iter().map(|(c, _)| c).collect::<HashSet<_>>();
+        // a single channel is normal spam, which slowmode and automod already deal with.
+        (channels.len() >= settings.channels.max(2)).then_some(copies)
+    })
+}
+
+fn same_payload(a: &SpamPayload, b: &SpamPayload, image_distance: u32) -> bool {
+    match (a, b) {
+        (SpamPayload::Content(a), SpamPayload::Content(b)) => a == b,
+        (
+            SpamPayload::Attachment { image: Some(a), .. },
+            SpamPayload::Attachment { image: Some(b), .. },
+        ) => (a ^ b).count_ones() <= image_distance,
+        (
+            SpamPayload::Attachment {
+                size: a_size,
+                name: a_name,
+                image: None,
+            },
+            SpamPayload::Attachment {
+                size: b_size,
+                name: b_name,
+                image: None,
+            },
+        ) => a_size == b_size && a_name == b_name,
+        _ => false,
+    }
+}
+
+/// Deletes every copy, times the member out and reports it once.
+async fn act(
+    ctx: &serenity::Context,
+    data: &Data,
+    msg: &Message,
+    settings: &CrossChannelSpamGuild,
+    copies: &[(GenericChannelId, MessageId)],
+) {
+    let Some(guild_id) = msg.guild_id else {
+        return;
+    };
+    let reason = "Posted the same message in multiple channels.";
+
+    let deleted = delete_messages(ctx, copies, reason).await;
+
+    let timed_out = timeout_member(ctx, guild_id, msg.author.id, settings.timeout, reason).await;
+
+    let channels = copies
+        .iter()
+        .map(|(channel_id, _)| *channel_id)
+        .collect::<HashSet<_>>();
+    let channel_list = channels
+        .iter()
+        .map(|channel_id| format!("<#{channel_id}>"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let attachments = msg
+        .attachments
+        .iter()
+        .map(|a| a.filename.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let timeout = if timed_out {
+        format!("timed out for {} minutes", settings.timeout / 60)
+    } else {
+        String::from("failed to time out")
+    };
+
+    let embed = log_embed("Cross-channel spam", Colour::RED, Some(&msg.author))
+        .description(format!(
+            "<@{}> posted the same thing in {} channels.",
+            msg.author.id,
+            channels.len()
+        ))
+        .field("Channels", field_value(&channel_list), false)
+        .field("Content", field_value(&msg.content), false)
+        .field("Attachments", field_value(&attachments), false)
+        .field(
+            "Action",
+            format!("Deleted {deleted}/{} messages, {timeout}.", copies.len()),
+            false,
+        );
+
+    mirror(ctx, data, guild_id, LogEvent::Spam, embed).await;
+}
+
+/// Deletes messages across channels, in bulk where possible, returning how many were deleted.
+pub(super) async fn delete_messages(
+    ctx: &serenity::Context,
+    messages: &[(GenericChannelId, MessageId)],
+    reason: &str,
+) -> usize {
+    let mut by_channel: HashMap<GenericChannelId, Vec<MessageId>> = HashMap::new();
+    for (channel_id, message_id) in messages {
+        by_channel.entry(*channel_id).or_default().push(*message_id);
+    }
+
+    let mut deleted = 0;
+    for (channel_id, message_ids) in &by_channel {
+        let result = if let [message_id] = message_ids.as_slice() {
+            ctx.http
+                .delete_message(*channel_id, *message_id, Some(reason))
+                .await
+        } else {
+            channel_id
+                .delete_messages(&ctx.http, message_ids, Some(reason))
+                .await
+        };
+
+        if result.is_ok() {
+            deleted += message_ids.len();
+        }
+    }
+
+    deleted
+}
@@ -1,7 +1,8 @@
 use crate::{Data, Error};
-use lumi::serenity_prelude::{self as serenity, Ready};
+use lumi::serenity_prelude::{self as serenity, ActivityType, Ready};
 use moth_core::data::structs::ANTI_DELETE_CACHE_CYCLE_TIME;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
@@ -43,13 +44,74 @@ async fn finalize_start(ctx: &serenity::Context) {
         let mut interval: tokio::time::Interval = tokio::time::interval(ANTI_DELETE_CACHE_CYCLE_TIME);
         loop {
             interval.tick().await;
-            *data_clone.anti_delete_cache.deletes_per_cycle.write().await = 0;
+            data_clone
+                .anti_delete_cache
+                .deletes_per_cycle
+                .store(0, Ordering::Relaxed);
+        }
+    });
+
+    let data_clone = data.clone();
+    tokio::spawn(async move {
+        let mut interval: tokio::time::Interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            // spam windows are seconds long, anything this old is never looked at again.
+            data_clone.spam.prune(Duration::from_secs(600));
+
+            // incidents only matter for their cooldown.
+            let anti_delete = data_clone.config.read().anti_delete.clone();
+            data_clone
+                .anti_delete_cache
+                .incidents
+                .retain(|(guild_id, _), at| {
+                    at.elapsed().as_secs() < anti_delete.guild(*guild_id).incident_cooldown
+                });
+
+            // actions outside of the window are dropped on the next action anyway.
+            let anti_nuke = data_clone.config.read().anti_nuke.clone();
+            data_clone
+                .anti_nuke
+                .actions
+                .retain(|(guild_id, _), actions| {
+                    anti_nuke.guilds.get(guild_id).is_some_and(|settings| {
+                        actions
+                            .back()
+                            .is_some_and(|a| a.at.elapsed().as_secs() < settings.window)
+                    })
+                });
+        }
+    });
+
+    let data_clone = data.clone();
+    tokio::spawn(async move {
+        let mut interval: tokio::time::Interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            // lets sessions that end while offline be closed here instead of when the bot is back.
+            if let Err(e) = data_clone.database.touch_voice_sessions().await {
+                println!("Failed to touch voice sessions: {e}");
+            }
         }
     });
 
     let data_clone = data.clone();
     tokio::spawn(moth_core::verification::run(data_clone));
 
+    // without the presence intent there is nothing to sample.
+    if data.config.read().activity.enabled {
+        let ctx_clone = ctx.clone();
+        tokio::spawn(async move {
+            let sample_interval = activity_sample_interval(&ctx_clone);
+            let mut interval: tokio::time::Interval =
+                tokio::time::interval(Duration::from_secs(sample_interval));
+            loop {
+                interval.tick().await;
+                sample_activities(&ctx_clone).await;
+            }
+        });
+    }
+
     data.web.start_background_task(ctx.clone()).await;
     data.emote_processor
         .start_background_task(Arc::clone(&data.database))
@@ -62,3 +124,50 @@ async fn finalize_start(ctx: &serenity::Context) {
         data.private_vc.start_background_task(ctx.clone()).await;
     }
 }
+
+fn activity_sample_interval(ctx: &serenity::Context) -> u64 {
+    // a zero interval would panic, so clamp to something sensible.
+    ctx.data_ref::<Data>()
+        .config
+        .read()
+        .activity
+        .sample_interval
+        .max(60)
+}
+
+/// Records how many members are playing each game in every guild, then drops expired samples.
+async fn sample_activities(ctx: &serenity::Context) {
+    let data = ctx.data_ref::<Data>();
+
+    for guild_id in ctx.cache.guilds() {
+        let counts = {
+            let Some(guild) = ctx.cache.guild(guild_id) else {
+                continue;
+            };
+
+            let mut counts: HashMap<String, u32> = HashMap::new();
+            for presence in &guild.presences {
+                for activity in &presence.activities {
+                    if activity.kind == ActivityType::Playing {
+                        *counts.entry(activity.name.to_string()).or_insert(0) += 1;
+                    }
+                }
+            }
+            counts.into_iter().collect::<Vec<_>>()
+        };
+
+        if let Err(e) = data
+            .database
+            .insert_activity_samples(guild_id, &counts)
+            .await
+        {
+            println!("Failed to sample activities: {e}");
+        }
+    }
+
+    let retention_days = data.config.read().activity.retention_days;
+    let before = chrono::Utc::now() - chrono::Duration::days(i64::from(retention_days));
+    if let Err(e) = data.database.prune_activity_samples(before).await {
+        println!("Failed to prune activity samples: {e}");
+    }
+}
@@ -1,7 +1,10 @@
+pub mod appearance_roles;
+pub mod appearances;
 pub mod channels;
 pub mod guilds;
 pub mod messages;
 pub mod misc;
+pub mod permission_alerts;
 pub mod reactions;
 pub mod users;
 pub mod voice;
@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+use crate::{helper::get_user, logging::log_embed};
+use lumi::serenity_prelude::{
+    self as serenity, ChannelOverwriteAction, Colour, CreateAllowedMentions, CreateMessage,
+    GuildChannel, GuildId, PermissionOverwriteType, Permissions, Role, RoleAction, RoleId, UserId,
+    audit_log::Action,
+};
+use moth_core::{config::PermissionAlertGuild, data::structs::Data};
+
+/// Permissions that a role gaining is worth an alert.
+const DANGEROUS_ROLE_PERMISSIONS: Permissions = Permissions::ADMINISTRATOR
+    .union(Permissions::MANAGE_GUILD)
+    .union(Permissions::MANAGE_ROLES)
+    .union(Permissions::BAN_MEMBERS)
+    .union(Permissions::MENTION_EVERYONE);
+
+/// Permissions that should never be granted to everyone through a channel overwrite.
+const SENSITIVE_OVERWRITE_PERMISSIONS: Permissions = Permissions::MANAGE_CHANNELS
+    .union(Permissions::MANAGE_ROLES)
+    .union(Permissions::MANAGE_WEBHOOKS)
+    .union(Permissions::MANAGE_MESSAGES)
+    .union(Permissions::MANAGE_THREADS)
+    .union(Permissions::MENTION_EVERYONE)
+    .union(Permissions::MUTE_MEMBERS)
+    .union(Permissions::DEAFEN_MEMBERS)
+    .union(Permissions::MOVE_MEMBERS);
+
+fn guild_config(data: &Data, guild_id: GuildId) -> Option<PermissionAlertGuild> {
+    data.config
+        .read()
+        .permission_alerts
+        .guilds
+        .get(&guild_id)
+        .cloned()
+}
+
+/// Alerts when a role gains a dangerous permission or is moved above a protected role.
+pub(crate) async fn role_update(ctx: &serenity::Context, old: &Role, new: &Role) {
+    let data = ctx.data::<Data>();
+    let Some(config) = guild_config(&data, new.guild_id) else {
+        return;
+    };
+
+    let mut reasons = Vec::new();
+
+    let gained = (new.permissions - old.permissions) & DANGEROUS_ROLE_PERMISSIONS;
+    if !gained.is_empty() {
+        reasons.push(format!("Gained {}", permission_names(gained)));
+    }
+
+    // positions shift for every role below a moved one, so only the moved role crosses over.
+    // without the guild cached the positions can't be compared, but the rest still can.
+    let protected = ctx.cache.guild(new.guild_id).map(|guild| {
+        config
+            .protected_roles
+            .iter()
+            .filter(|id| **id != new.id)
+            .filter_map(|id| guild.roles.get(id))
+            .filter(|role| old.position < role.position && new.position >= role.position)
+            .map(|role| role.id)
+            .collect::<Vec<_>>()
+    });
+    for role_id in protected.unwrap_or_default() {
+        reasons.push(format!("Moved above <@&{role_id}>"));
+    }
+
+    if reasons.is_empty() {
+        return;
+    }
+
+    let moderator = find_moderator(
+        ctx,
+        new.guild_id,
+        &[Action::Role(RoleAction::Update)],
+        new.id.get(),
+    )
+    .await;
+
+    let embed = log_embed("Dangerous role change", Colour::RED, None).description(format!(
+        "<@&{}> ({})\n{}",
+        new.id,
+        new.name,
+        reasons.join("\n")
+    ));
+
+    alert(ctx, new.guild_id, &config, embed, moderator).await;
+}
+
+/// Alerts when a role is created with a dangerous permission.
+pub(crate) async fn role_create(ctx: &serenity::Context, role: &Role) {
+    let data = ctx.data::<Data>();
+    let Some(config) = guild_config(&data, role.guild_id) else {
+        return;
+    };
+
+    let dangerous = role.permissions & DANGEROUS_ROLE_PERMISSIONS;
+    if dangerous.is_empty() {
+        return;
+    }
+
+    let moderator = find_moderator(
+        ctx,
+        role.guild_id,
+        &[Action::Role(RoleAction::Create)],
+        role.id.get(),
+    )
+    .await;
+
+    let embed = log_embed("Dangerous role created", Colour::RED, None).description(format!(
+        "<@&{}> ({})\nCreated with {}",
+        role.id,
+        role.name,
+        permission_names(dangerous)
+    ));
+
+    alert(ctx, role.guild_id, &config, embed, moderator).await;
+}
+
+/// Alerts when an @everyone overwrite in a channel starts granting a sensitive permission.
+pub(crate) async fn channel_update(
+    ctx: &serenity::Context,
+    old: &GuildChannel,
+    new: &GuildChannel,
+) {
+    let guild_id = new.base.guild_id;
+    let data = ctx.data::<Data>();
+    let Some(config) = guild_config(&data, guild_id) else {
+        return;
+    };
+
+    let everyone = PermissionOverwriteType::Role(RoleId::new(guild_id.get()));
+    let allowed = |channel: &GuildChannel| {
+        channel
+            .permission_overwrites
+            .iter()
+            .find(|o| o.kind == everyone)
+            .map_or(Permissions::empty(), |o| o.allow)
+    };
+
+    let gained = (allowed(new) - allowed(old)) & SENSITIVE_OVERWRITE_PERMISSIONS;
+    if gained.is_empty() {
+        return;
+    }
+
+    let moderator = find_moderator(
+        ctx,
+        guild_id,
+        &[
+            Action::ChannelOverwrite(ChannelOverwriteAction::Create),
+            Action::ChannelOverwrite(ChannelOverwriteAction::Update),
+        ],
+        new.id.get(),
+    )
+    .await;
+
+    let embed = log_embed("Dangerous overwrite change", Colour::RED, None).description(format!(
+        "<#{}> ({})\n@everyone was granted {}",
+        new.id,
+        new.base.name,
+        permission_names(gained)
+    ));
+
+    alert(ctx, guild_id, &config, embed, moderator).await;
+}
+
+fn permission_names(permissions: Permissions) -> String {
+    permissions.get_permission_names().join(", ")
+}
+
+/// Finds who made a change from the most recent matching audit log entry.
+async fn find_moderator(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    actions: &[Action],
+    target_id: u64,
+) -> Option<UserId> {
+    // the audit log entry isn't always there by the time the event is.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    let logs = guild_id
+        .audit_logs(
+            &ctx.http,
+            None,
+            None,
+            None,
+            Some(nonmax::NonMaxU8::new(25).unwrap()),
+        )
+        .await
+        .ok()?;
+
+    logs.entries
+        .iter()
+        .find(|entry| {
+            actions.contains(&entry.action) && entry.target_id.map(|t| t.get()) == Some(target_id)
+        })
+        .and_then(|entry| entry.user_id)
+}
+
+async fn alert(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    config: &PermissionAlertGuild,
+    mut embed: serenity::CreateEmbed<'_>,
+    moderator: Option<UserId>,
+) {
+    embed = match moderator {
+        Some(user_id) => {
+            let name = get_user(ctx, guild_id, user_id)
+                .await
+                .map_or_else(|| user_id.to_string(), |user| user.tag());
+            embed.field("Moderator", format!("<@{user_id}> ({name})"), true)
+        }
+        None => embed.field("Moderator", "Unknown, check the audit log.", true),
+    };
+
+    let mut msg = CreateMessage::new().embed(embed);
+    if let Some(role_id) = config.ping_role {
+        msg = msg
+            .content(format!("<@&{role_id}>"))
+            .allowed_mentions(CreateAllowedMentions::new().roles(vec![role_id]));
+    }
+
+    if let Err(e) = config.channel.send_message(&ctx.http, msg).await {
+        println!("Failed to send permission alert: {e}");
+    }
+}
@@ -1,12 +1,15 @@
 use std::sync::Arc;
 
 use crate::helper::{get_channel_name, get_guild_name_override, get_user};
+use crate::logging::{log_embed, mirror};
 use crate::{Data, Error};
 
 use moth_ansi::{HI_MAGENTA, RESET};
 
-use lumi::serenity_prelude::{self as serenity, Reaction};
-use small_fixed_array::FixedString;
+use lumi::serenity_prelude::{
+    self as serenity, Colour, GenericChannelId, GuildId, MessageId, Reaction, ReactionType,
+};
+use moth_core::{config::LogEvent, data::database::reaction_blocks::ReactionBlockTarget};
 
 pub async fn reaction_add(
     ctx: &serenity::Context,
@@ -51,33 +54,56 @@ pub async fn reaction_add(
         moth_starboard::starboard_add_handler(ctx, add_reaction, &data).await?;
     }
 
-    match &add_reaction.emoji {
+    if let Some(guild_id) = guild_id {
+        remove_blocked_reaction(ctx, &data, guild_id, add_reaction).await?;
+    }
+
+    Ok(())
+}
+
+/// Removes reactions blocked on the author of the message or in the channel.
+async fn remove_blocked_reaction(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: serenity::GuildId,
+    reaction: &Reaction,
+) -> Result<(), Error> {
+    let emoji = match &reaction.emoji {
         serenity::ReactionType::Custom {
-            id: _,
-            animated: _,
-            name,
-        } => {
-            if name.as_ref() != Some(&FixedString::from_static_trunc("giga_mpreg")) {
-                return Ok(());
-            }
-        }
+            name: Some(name), ..
+        } => name.as_str(),
+        serenity::ReactionType::Unicode(unicode) => unicode.as_str(),
         _ => return Ok(()),
+    };
+
+    let blocks = data.database.get_reaction_blocks(guild_id).await?;
+    let blocks = blocks
+        .iter()
+        .filter(|b| b.emoji == emoji)
+        .map(|b| b.target)
+        .collect::<Vec<_>>();
+
+    if blocks.is_empty() {
+        return Ok(());
     }
 
-    let is_ruben_author = {
-        let msgs = ctx.cache.channel_messages(add_reaction.channel_id);
-        if let Some(messages) = msgs {
-            messages
-                .iter()
-                .find(|m| m.id == add_reaction.message_id)
-                .is_some_and(|m| m.author.id == serenity::UserId::new(291089948709486593))
-        } else {
-            false
-        }
+    let blocked = if blocks.contains(&ReactionBlockTarget::Channel(reaction.channel_id)) {
+        true
+    } else if blocks
+        .iter()
+        .any(|t| matches!(t, ReactionBlockTarget::User(_)))
+    {
+        // only looked up when needed, the cache is checked before fetching.
+        reaction
+            .message(ctx)
+            .await
+            .is_ok_and(|m| blocks.contains(&ReactionBlockTarget::User(m.author.id)))
+    } else {
+        false
     };
 
-    if is_ruben_author {
-        let _ = add_reaction.delete(&ctx.http).await;
+    if blocked {
+        let _ = reaction.delete(&ctx.http).await;
     }
 
     Ok(())
@@ -126,3 +152,107 @@ pub async fn reaction_remove(
     }
     Ok(())
 }
+
+pub async fn reaction_remove_all(
+    ctx: &serenity::Context,
+    channel_id: GenericChannelId,
+    message_id: MessageId,
+    data: Arc<Data>,
+) -> Result<(), Error> {
+    data.emote_processor
+        .sender
+        .reaction_remove_all(message_id)
+        .await;
+
+    let guild_id = guild_of(ctx, channel_id, message_id);
+    reactions_cleared(ctx, &data, guild_id, channel_id, message_id, None).await
+}
+
+pub async fn reaction_remove_emoji(
+    ctx: &serenity::Context,
+    removed_reactions: &Reaction,
+    data: Arc<Data>,
+) -> Result<(), Error> {
+    data.emote_processor
+        .sender
+        .reaction_remove_emoji(removed_reactions)
+        .await;
+
+    reactions_cleared(
+        ctx,
+        &data,
+        removed_reactions.guild_id,
+        removed_reactions.channel_id,
+        removed_reactions.message_id,
+        Some(&removed_reactions.emoji),
+    )
+    .await
+}
+
+/// The remove all event doesn't include the guild, so it's looked up from the cache.
+fn guild_of(
+    ctx: &serenity::Context,
+    channel_id: GenericChannelId,
+    message_id: MessageId,
+) -> Option<GuildId> {
+    if let Some(message) = ctx.cache.message(channel_id, message_id) {
+        return message.guild_id;
+    }
+
+    ctx.cache.guilds().into_iter().find(|guild_id| {
+        ctx.cache.guild(*guild_id).is_some_and(|guild| {
+            guild.channels.get(&channel_id.expect_channel()).is_some()
+                || guild.threads.iter().any(|t| t.id == channel_id.get())
+        })
+    })
+}
+
+async fn reactions_cleared(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    guild_id: Option<GuildId>,
+    channel_id: GenericChannelId,
+    message_id: MessageId,
+    emoji: Option<&ReactionType>,
+) -> Result<(), Error> {
+    let guild_name = get_guild_name_override(ctx, data, guild_id);
+    let channel_name = get_channel_name(ctx, guild_id, channel_id).await;
+    let cleared = match emoji {
+        Some(emoji) => format!("All {emoji} reactions"),
+        None => String::from("All reactions"),
+    };
+
+    println!(
+        "{HI_MAGENTA}[{guild_name}] [#{channel_name}] {cleared} were cleared from a message \
+         (ID:{message_id}){RESET}"
+    );
+
+    if let Some(guild_id) = guild_id {
+        let author = ctx
+            .cache
+            .message(channel_id, message_id)
+            .map(|m| m.author.id);
+
+        let mut embed = log_embed("Reactions cleared", Colour::ORANGE, None)
+            .description(format!(
+                "{cleared} were cleared from {} in <#{channel_id}>.",
+                message_id.link(channel_id, Some(guild_id))
+            ))
+            // neither the gateway event nor the audit log say who did it.
+            .field(
+                "Moderator",
+                "Unknown, Discord doesn't report who clears reactions.",
+                false,
+            );
+
+        if let Some(author) = author {
+            embed = embed.field("Message by", format!("<@{author}>"), true);
+        }
+
+        mirror(ctx, data, guild_id, LogEvent::ReactionClear, embed).await;
+    }
+
+    moth_starboard::starboard_clear_handler(ctx, data, message_id, emoji).await?;
+
+    Ok(())
+}
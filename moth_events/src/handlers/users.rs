@@ -2,15 +2,22 @@ use std::sync::Arc;
 
 use chrono::Utc;
 use lumi::serenity_prelude::{
-    self as serenity, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, GuildMemberUpdateEvent,
-    Member,
+    self as serenity, Colour, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter,
+    GuildMemberUpdateEvent, Member,
 };
 use moth_ansi::{HI_GREEN, RESET};
+use moth_core::{
+    config::{DmActivityGuild, LogEvent},
+    data::database::names::NameKind,
+};
 
-use ::serenity::all::GenericChannelId;
 use small_fixed_array::FixedString;
 
-use crate::{helper::get_guild_name_override, Data, Error};
+use crate::{
+    Data, Error,
+    helper::get_guild_name_override,
+    logging::{before_after, log_embed, mirror},
+};
 
 pub async fn guild_member_update(
     ctx: &serenity::Context,
@@ -22,6 +29,19 @@ pub async fn guild_member_update(
     let guild_id = event.guild_id;
     let guild_name = get_guild_name_override(ctx, &data, Some(guild_id));
 
+    if let Some(new_member) = new {
+        super::appearances::record_member(&data, new_member).await;
+    }
+
+    if let Err(e) = super::appearance_roles::check_member(ctx, &data, event).await {
+        println!("Failed to apply appearance roles: {e}");
+    }
+
+    // recorded even when the old member isn't cached, unchanged names are skipped by the database.
+    if let Some(new_member) = new {
+        record_names(&data, old_if_available.as_ref(), new_member).await;
+    }
+
     if let Some(old_member) = old_if_available {
         if let Some(new_member) = new {
             let old_nickname = old_member.nick.as_deref().unwrap_or("None");
@@ -36,6 +56,10 @@ pub async fn guild_member_update(
                     new_nickname,
                     new_member.user.id
                 );
+
+                let embed = log_embed("Nickname changed", Colour::TEAL, Some(&new_member.user));
+                let embed = before_after(embed, old_nickname, new_nickname);
+                mirror(ctx, &data, guild_id, LogEvent::MemberUpdate, embed).await;
             }
 
             if old_member.user.tag() != new_member.user.tag() {
@@ -45,31 +69,42 @@ pub async fn guild_member_update(
                     new_member.user.tag(),
                     new_member.user.id
                 );
+
+                let embed = log_embed("Username changed", Colour::TEAL, Some(&new_member.user));
+                let embed = before_after(embed, &old_member.user.tag(), &new_member.user.tag());
+                mirror(ctx, &data, guild_id, LogEvent::MemberUpdate, embed).await;
             }
             if old_member.user.global_name != new_member.user.global_name {
+                let none = FixedString::from_str_trunc("None");
+                let old_global = old_member.user.global_name.as_ref().unwrap_or(&none);
+                let new_global = new_member.user.global_name.as_ref().unwrap_or(&none);
+
                 println!(
                     "{HI_GREEN}Display name change: {}: {} -> {} (ID:{}){RESET}",
                     old_member.user.tag(),
-                    old_member
-                        .user
-                        .global_name
-                        .as_ref()
-                        .unwrap_or(&FixedString::from_str_trunc("None")),
-                    new_member
-                        .user
-                        .global_name
-                        .as_ref()
-                        .unwrap_or(&FixedString::from_str_trunc("None")),
+                    old_global,
+                    new_global,
                     new_member.user.id
                 );
+
+                let embed = log_embed("Display name changed", Colour::TEAL, Some(&new_member.user));
+                let embed = before_after(embed, old_global, new_global);
+                mirror(ctx, &data, guild_id, LogEvent::MemberUpdate, embed).await;
             }
         }
 
         if let Some(timestamp) = event.unusual_dm_activity_until {
             let timestamp = timestamp.timestamp();
-            if guild_id != 98226572468690944 {
+            let Some(settings) = data
+                .config
+                .read()
+                .dm_activity
+                .guilds
+                .get(&guild_id)
+                .cloned()
+            else {
                 return Ok(());
-            }
+            };
 
             let now_utc = Utc::now().timestamp();
 
@@ -82,10 +117,14 @@ pub async fn guild_member_update(
 
             let old_stamp = data.get_activity_check(event.user.id).await;
 
+            // the flag is announced before any escalation, but a failed announcement still
+            // records it.
             let Some(old_stamp) = old_stamp else {
-                dm_activity_new(ctx, event, 0).await?;
                 data.new_or_announced(event.user.id, now_utc, timestamp, Some(1))
                     .await;
+                let announced = dm_activity_new(ctx, event, &settings, 0).await;
+                record_dm_activity_flag(ctx, &data, event, &settings, timestamp, true).await;
+                announced?;
                 return Ok(());
             };
 
@@ -93,7 +132,6 @@ pub async fn guild_member_update(
             if let Some(until) = old_stamp.until {
                 // Display a message if its over an hour since the last one.
                 if timestamp - until >= 3600 {
-                    dm_activity_updated(ctx, event, old_stamp.count).await?;
                     data.new_or_announced(
                         event.user.id,
                         now_utc,
@@ -101,6 +139,10 @@ pub async fn guild_member_update(
                         Some(old_stamp.count + 1),
                     )
                     .await;
+                    let announced =
+                        dm_activity_updated(ctx, event, &settings, old_stamp.count).await;
+                    record_dm_activity_flag(ctx, &data, event, &settings, timestamp, true).await;
+                    announced?;
                     return Ok(()); // its okay to return here to prevent
                 }
 
@@ -113,14 +155,175 @@ pub async fn guild_member_update(
                         old_stamp.count + 1,
                     )
                     .await;
+                    record_dm_activity_flag(ctx, &data, event, &settings, timestamp, false).await;
                 }
             } else {
-                dm_activity_new(ctx, event, old_stamp.count).await?;
                 data.new_or_announced(event.user.id, now_utc, timestamp, Some(old_stamp.count + 1))
                     .await;
+                let announced = dm_activity_new(ctx, event, &settings, old_stamp.count).await;
+                record_dm_activity_flag(ctx, &data, event, &settings, timestamp, true).await;
+                announced?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Records the current names of a member, the old names are included when they changed in case
+/// they were never observed before.
+pub(crate) async fn record_names(data: &Data, old: Option<&Member>, new: &Member) {
+    // most member updates are role or avatar changes, nothing to record for those.
+    if let Some(old) = old
+        && old.user.name == new.user.name
+        && old.user.global_name == new.user.global_name
+        && old.nick == new.nick
+    {
+        return;
+    }
+
+    let names = |member: &Member| {
+        [
+            (None, NameKind::Username, Some(member.user.name.to_string())),
+            (
+                None,
+                NameKind::GlobalName,
+                member.user.global_name.as_ref().map(ToString::to_string),
+            ),
+            (
+                Some(member.guild_id),
+                NameKind::Nickname,
+                member.nick.as_ref().map(ToString::to_string),
+            ),
+        ]
+    };
+
+    let old_names = old.map(names);
+    for (i, (guild_id, kind, name)) in names(new).into_iter().enumerate() {
+        let old_name = old_names
+            .as_ref()
+            .map(|old_names| &old_names[i].2)
+            .filter(|old_name| **old_name != name);
+
+        for name in old_name.into_iter().chain([&name]) {
+            if let Err(e) = data
+                .database
+                .insert_name(new.user.id, guild_id, kind, name.as_deref())
+                .await
+            {
+                println!("Failed to record name history: {e}");
+                return;
             }
         }
     }
+}
+
+/// Logs an unusual dm activity flag, escalating if the member has been flagged too often.
+async fn record_dm_activity_flag(
+    ctx: &serenity::Context,
+    data: &Data,
+    event: &GuildMemberUpdateEvent,
+    settings: &DmActivityGuild,
+    until: i64,
+    announced: bool,
+) {
+    let until = chrono::DateTime::from_timestamp(until, 0).unwrap_or_default();
+    let event_id = match data
+        .database
+        .insert_dm_activity_event(event.guild_id, event.user.id, until, announced)
+        .await
+    {
+        Ok(event_id) => event_id,
+        Err(e) => {
+            println!("Failed to record dm activity flag: {e}");
+            return;
+        }
+    };
+
+    let since = Utc::now() - chrono::Duration::seconds(settings.window as i64);
+    let flags = match data
+        .database
+        .count_dm_activity_flags(event.guild_id, event.user.id, since)
+        .await
+    {
+        Ok((flags, false)) if flags >= settings.threshold as i64 => flags,
+        Ok(_) => return,
+        Err(e) => {
+            println!("Failed to count dm activity flags: {e}");
+            return;
+        }
+    };
+
+    if let Err(e) = escalate_dm_activity(ctx, data, event, settings, event_id, flags, since).await {
+        println!("Failed to escalate dm activity: {e}");
+    }
+}
+
+async fn escalate_dm_activity(
+    ctx: &serenity::Context,
+    data: &Data,
+    event: &GuildMemberUpdateEvent,
+    settings: &DmActivityGuild,
+    event_id: i32,
+    flags: i64,
+    since: chrono::DateTime<Utc>,
+) -> Result<(), Error> {
+    // marked first, so a failed alert doesn't escalate again on the next update.
+    data.database.set_dm_activity_escalated(event_id).await?;
+
+    let timed_out = match settings.timeout {
+        Some(seconds) => {
+            crate::helper::timeout_member(
+                ctx,
+                event.guild_id,
+                event.user.id,
+                seconds,
+                "Repeatedly flagged for unusual dm activity.",
+            )
+            .await
+        }
+        None => false,
+    };
+
+    let mut embed = CreateEmbed::new()
+        .author(
+            CreateEmbedAuthor::new(format!(
+                "{} keeps getting flagged for unusual dm activity",
+                event.user.tag()
+            ))
+            .icon_url(event.user.face()),
+        )
+        .description(format!(
+            "<@{}> has been flagged {flags} times since <t:{}:R>.",
+            event.user.id,
+            since.timestamp()
+        ))
+        .colour(Colour::RED)
+        .footer(CreateEmbedFooter::new(format!(
+            "User ID: {}",
+            event.user.id
+        )));
+
+    if let Some(seconds) = settings.timeout {
+        let action = if timed_out {
+            format!(
+                "Timed out until <t:{}:R>",
+                Utc::now().timestamp() + seconds as i64
+            )
+        } else {
+            String::from("Failed to time out")
+        };
+        embed = embed.field("Action", action, false);
+    }
+
+    let mut msg = serenity::CreateMessage::new().embed(embed);
+    if let Some(role_id) = settings.ping_role {
+        msg = msg
+            .content(format!("<@&{role_id}>"))
+            .allowed_mentions(serenity::CreateAllowedMentions::new().roles(vec![role_id]));
+    }
+
+    settings.channel.send_message(&ctx.http, msg).await?;
 
     Ok(())
 }
@@ -128,6 +331,7 @@ pub async fn guild_member_update(
 async fn dm_activity_new(
     ctx: &serenity::Context,
     event: &GuildMemberUpdateEvent,
+    settings: &DmActivityGuild,
     count: i16,
 ) -> Result<(), Error> {
     let user_ping = format!("<@{}>", event.user.id);
@@ -183,7 +387,8 @@ async fn dm_activity_new(
         embed = embed.description(format!("**Online on**:\n{stats}"));
     }
 
-    GenericChannelId::new(158484765136125952)
+    settings
+        .channel
         .send_message(&ctx.http, serenity::CreateMessage::default().embed(embed))
         .await?;
 
@@ -193,6 +398,7 @@ async fn dm_activity_new(
 async fn dm_activity_updated(
     ctx: &serenity::Context,
     event: &GuildMemberUpdateEvent,
+    settings: &DmActivityGuild,
     count: i16,
 ) -> Result<(), Error> {
     let user_ping = format!("<@{}>", event.user.id);
@@ -246,7 +452,8 @@ async fn dm_activity_updated(
         embed = embed.description(format!("**Online on**:\n{stats}"));
     }
 
-    GenericChannelId::new(158484765136125952)
+    settings
+        .channel
         .send_message(&ctx.http, serenity::CreateMessage::default().embed(embed))
         .await?;
 
@@ -3,16 +3,21 @@ use std::borrow::Cow;
 use crate::{
     Error,
     helper::{get_guild_name_override, get_user},
+    logging::{log_embed, mirror},
 };
-use lumi::serenity_prelude::{self as serenity, VoiceState};
+use lumi::serenity_prelude::{self as serenity, Colour, VoiceState};
 use moth_ansi::{GREEN, RESET};
+use moth_core::{config::LogEvent, data::structs::Data};
 pub mod private;
+pub mod sessions;
 
 pub async fn voice_state_update(
     ctx: &serenity::Context,
     old: &Option<VoiceState>,
     new: &VoiceState,
 ) -> Result<(), Error> {
+    sessions::update(ctx, old.as_ref(), new).await;
+
     if let Some(old) = old {
         if old.channel_id != new.channel_id && new.channel_id.is_some() {
             handle_switch(ctx, old, new).await?;
@@ -20,7 +25,7 @@ pub async fn voice_state_update(
             handle_leave(ctx, old, new).await?;
         }
 
-        // third case where mutes and other changes happen.
+        // third case where mutes and other changes happen, only sessions track these.
     } else {
         handle_joins(ctx, new).await?;
     }
@@ -82,6 +87,17 @@ async fn handle_switch(
         );
     }
 
+    let embed = log_embed("Voice channel switched", Colour::DARK_TEAL, Some(&user))
+        .description(format!("<#{old_id}> -> <#{new_id}>"));
+    mirror(
+        ctx,
+        ctx.data_ref::<Data>(),
+        new.guild_id.unwrap(),
+        LogEvent::Voice,
+        embed,
+    )
+    .await;
+
     Ok(())
 }
 async fn handle_leave(
@@ -92,27 +108,41 @@ async fn handle_leave(
     // There is no new channel ID.
     let channel_id = old.channel_id.unwrap();
     // they are leaving so old should hold the guild_id, see handle_joins for justification.
-    let user_name = match get_user(ctx, new.guild_id.unwrap(), new.user_id).await {
-        Some(user) => user.tag(),
-        None => return Ok(()),
+    let Some(user) = get_user(ctx, new.guild_id.unwrap(), new.user_id).await else {
+        return Ok(());
     };
+    let user_name = user.tag();
 
     private::check_channel(ctx, Some(old), new, None).await;
 
-    let guild_cache = ctx.cache.guild(new.guild_id.unwrap());
-    // will fire real error in the future.
-    let Some(guild_cache) = guild_cache else {
-        return Ok(());
-    };
+    {
+        let guild_cache = ctx.cache.guild(new.guild_id.unwrap());
+        // will fire real error in the future.
+        let Some(guild_cache) = guild_cache else {
+            return Ok(());
+        };
 
-    let channel_name = guild_cache
-        .channels
-        .get(&channel_id)
-        .map_or_else(|| "None", |c| c.base.name.as_str());
+        let channel_name = guild_cache
+            .channels
+            .get(&channel_id)
+            .map_or_else(|| "None", |c| c.base.name.as_str());
 
-    let guild_name = get_guild_name_override(ctx, &ctx.data(), new.guild_id);
+        let guild_name = get_guild_name_override(ctx, &ctx.data(), new.guild_id);
+
+        println!("{GREEN}[{guild_name}] {user_name} left {channel_name} (ID:{channel_id}){RESET}");
+    }
+
+    let embed = log_embed("Left voice channel", Colour::DARK_TEAL, Some(&user))
+        .description(format!("<#{channel_id}>"));
+    mirror(
+        ctx,
+        ctx.data_ref::<Data>(),
+        new.guild_id.unwrap(),
+        LogEvent::Voice,
+        embed,
+    )
+    .await;
 
-    println!("{GREEN}[{guild_name}] {user_name} left {channel_name} (ID:{channel_id}){RESET}");
     Ok(())
 }
 async fn handle_joins(ctx: &serenity::Context, new: &VoiceState) -> Result<(), Error> {
@@ -148,5 +178,16 @@ async fn handle_joins(ctx: &serenity::Context, new: &VoiceState) -> Result<(), E
         );
     }
 
+    let embed = log_embed("Joined voice channel", Colour::DARK_TEAL, Some(&user))
+        .description(format!("<#{channel_id}>"));
+    mirror(
+        ctx,
+        ctx.data_ref::<Data>(),
+        new.guild_id.unwrap(),
+        LogEvent::Voice,
+        embed,
+    )
+    .await;
+
     Ok(())
 }
@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use lumi::serenity_prelude::{self as serenity, Guild, VoiceState};
+use moth_core::data::structs::Data;
+
+fn muted(state: &VoiceState) -> bool {
+    state.mute() || state.self_mute()
+}
+
+fn deafened(state: &VoiceState) -> bool {
+    state.deaf() || state.self_deaf()
+}
+
+/// Opens, closes and updates voice sessions from a voice state update.
+pub(super) async fn update(ctx: &serenity::Context, old: Option<&VoiceState>, new: &VoiceState) {
+    let Some(guild_id) = new.guild_id else {
+        return;
+    };
+    let database = &ctx.data_ref::<Data>().database;
+
+    let old_channel = old.and_then(|o| o.channel_id);
+
+    let result = if old_channel == new.channel_id {
+        // mutes and other state changes, the session itself continues.
+        match old {
+            Some(old) if muted(old) != muted(new) || deafened(old) != deafened(new) => {
+                database
+                    .update_voice_session_state(guild_id, new.user_id, muted(new), deafened(new))
+                    .await
+            }
+            _ => return,
+        }
+    } else if let Some(channel_id) = new.channel_id {
+        // opening a session closes the one from the old channel.
+        database
+            .open_voice_session(guild_id, channel_id, new.user_id, muted(new), deafened(new))
+            .await
+    } else {
+        database.close_voice_session(guild_id, new.user_id).await
+    };
+
+    if let Err(e) = result {
+        println!("Failed to update voice session: {e}");
+    }
+}
+
+/// Brings the open sessions of a guild in line with its voice states, as updates are missed while
+/// the bot is offline.
+///
+/// Sessions that ended while offline are closed at the last time they were seen, so the downtime
+/// isn't counted.
+pub async fn reconcile(ctx: &serenity::Context, guild: &Guild) {
+    let database = &ctx.data_ref::<Data>().database;
+
+    let open = match database.get_open_voice_sessions(guild.id).await {
+        Ok(open) => open,
+        Err(e) => {
+            println!("Failed to get open voice sessions: {e}");
+            return;
+        }
+    };
+
+    let mut continuing = HashSet::new();
+    for session in open {
+        let state = guild
+            .voice_states
+            .get(&session.user_id)
+            .filter(|state| state.channel_id == Some(session.channel_id));
+
+        let result = if let Some(state) = state {
+            // they may have muted or deafened while offline.
+            continuing.insert(session.user_id);
+            database
+                .update_voice_session_state(guild.id, state.user_id, muted(state), deafened(state))
+                .await
+        } else {
+            database
+                .close_stale_voice_session(guild.id, session.user_id)
+                .await
+        };
+
+        if let Err(e) = result {
+            println!("Failed to reconcile voice session: {e}");
+        }
+    }
+
+    for state in &guild.voice_states {
+        let Some(channel_id) = state.channel_id else {
+            continue;
+        };
+
+        if continuing.contains(&state.user_id) {
+            continue;
+        }
+
+        if let Err(e) = database
+            .open_voice_session(
+                guild.id,
+                channel_id,
+                state.user_id,
+                muted(state),
+                deafened(state),
+            )
+            .await
+        {
+            println!("Failed to open voice session: {e}");
+        }
+    }
+}
@@ -9,6 +9,7 @@ use lumi::serenity_prelude::{
     PermissionOverwrite, PermissionOverwriteType, Permissions, SortOrder, User, UserId,
 };
 use moth_ansi::{HI_GREEN, RED, RESET};
+use moth_core::config::MAX_TIMEOUT_SECONDS;
 
 // this function serves to help reduce the magic usage of to_user, serenity no longer
 // iterates through all caches to get the information, and that was poor anyway,
@@ -29,6 +30,33 @@ pub async fn get_user(ctx: &serenity::Context, guild_id: GuildId, user_id: UserI
     }
 }
 
+/// Times out a member for the given amount of seconds, capped at [`MAX_TIMEOUT_SECONDS`],
+/// returning if it succeeded.
+pub async fn timeout_member(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    user_id: UserId,
+    seconds: u64,
+    reason: &str,
+) -> bool {
+    let seconds = seconds.min(MAX_TIMEOUT_SECONDS);
+    let until = chrono::Utc::now().timestamp() + seconds as i64;
+    let Ok(until) = serenity::Timestamp::from_unix_timestamp(until) else {
+        return false;
+    };
+
+    guild_id
+        .edit_member(
+            &ctx.http,
+            user_id,
+            serenity::EditMember::new()
+                .disable_communication_until(until)
+                .audit_log_reason(reason),
+        )
+        .await
+        .is_ok()
+}
+
 // Helper function for getting the guild name override or guild name even if None.
 pub fn get_guild_name_override(
     ctx: &serenity::Context,
@@ -16,16 +16,27 @@
 use lumi::serenity_prelude::{self as serenity, FullEvent};
 use moth_core::data::structs::{Data, Error};
 
+pub mod diff;
 pub mod helper;
+pub mod logging;
+pub mod recorder;
 
 pub mod handlers;
 use handlers::*;
 
-pub struct Handler;
+#[derive(Default)]
+pub struct Handler {
+    /// Records every event, only set when `MOTH_RECORD` is.
+    pub recorder: Option<recorder::Recorder>,
+}
 
 #[serenity::async_trait]
 impl serenity::EventHandler for Handler {
     async fn dispatch(&self, ctx: &serenity::Context, event: &serenity::FullEvent) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(event);
+        }
+
         let _ = event_handler(ctx, event).await;
     }
 }
@@ -56,6 +67,21 @@ pub async fn event_handler(
             messages::message_delete(ctx, *channel_id, *deleted_message_id, *guild_id, data)
                 .await?;
         }
+        FullEvent::MessageDeleteBulk {
+            channel_id,
+            multiple_deleted_messages_ids,
+            guild_id,
+            ..
+        } => {
+            messages::message_delete_bulk(
+                ctx,
+                *channel_id,
+                multiple_deleted_messages_ids,
+                *guild_id,
+                data,
+            )
+            .await?;
+        }
         FullEvent::ReactionAdd { add_reaction, .. } => {
             reactions::reaction_add(ctx, add_reaction, data).await?;
         }
@@ -64,9 +90,28 @@ pub async fn event_handler(
         } => {
             reactions::reaction_remove(ctx, removed_reaction, data).await?;
         }
+        FullEvent::ReactionRemoveAll {
+            channel_id,
+            removed_from_message_id,
+            ..
+        } => {
+            reactions::reaction_remove_all(ctx, *channel_id, *removed_from_message_id, data)
+                .await?;
+        }
+        FullEvent::ReactionRemoveEmoji {
+            removed_reactions, ..
+        } => {
+            reactions::reaction_remove_emoji(ctx, removed_reactions, data).await?;
+        }
         FullEvent::GuildCreate { guild, is_new, .. } => {
             guilds::guild_create(ctx, guild, is_new).await?;
         }
+        FullEvent::InviteCreate { data: event, .. } => {
+            guilds::invites::invite_create(&data, event).await;
+        }
+        FullEvent::InviteDelete { data: event, .. } => {
+            guilds::invites::invite_delete(&data, event).await;
+        }
         FullEvent::GuildMemberAddition { new_member, .. } => {
             guilds::guild_member_addition(ctx, data, new_member).await?;
         }
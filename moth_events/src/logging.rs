@@ -0,0 +1,144 @@
+use crate::Data;
+
+use lumi::serenity_prelude::{
+    self as serenity, Colour, CreateAllowedMentions, CreateAttachment, CreateEmbed,
+    CreateEmbedFooter, CreateMessage, GuildId, User,
+};
+use moth_core::config::{LogEvent, LogTarget};
+
+/// Mirrors an embed to the channel or webhook configured for this event in the guild.
+///
+/// Does nothing if no route is configured, failures are ignored as logging should never
+/// interrupt the handler that called it.
+pub async fn mirror(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    event: LogEvent,
+    embed: CreateEmbed<'_>,
+) {
+    mirror_with_files(ctx, data, guild_id, event, embed, vec![]).await;
+}
+
+/// Same as [`mirror`], but with files attached to the message.
+pub async fn mirror_with_files(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    event: LogEvent,
+    embed: CreateEmbed<'_>,
+    files: Vec<CreateAttachment<'_>>,
+) {
+    let Some(target) = ({
+        let config = data.config.read();
+        config.logging.target(guild_id, event).cloned()
+    }) else {
+        return;
+    };
+
+    let mentions = CreateAllowedMentions::new()
+        .all_users(false)
+        .everyone(false)
+        .all_roles(false);
+
+    let msg = CreateMessage::new().embed(embed).allowed_mentions(mentions);
+
+    match target {
+        LogTarget::Channel(channel_id) => {
+            let _ = channel_id.send_message(&ctx.http, msg.files(files)).await;
+        }
+        LogTarget::Webhook(id, thread_id, token) => {
+            // ditto with vcstatus, i want to reuse the message.
+            let _ = ctx
+                .http
+                .execute_webhook(id, thread_id, &token, false, files, &msg)
+                .await;
+        }
+    }
+}
+
+/// Returns true if the guild has a route for this event, useful for skipping expensive work.
+#[must_use]
+pub fn is_routed(data: &Data, guild_id: GuildId, event: LogEvent) -> bool {
+    data.config.read().logging.target(guild_id, event).is_some()
+}
+
+/// A base embed for a log event, with the user as the author if provided.
+#[must_use]
+pub fn log_embed<'a>(
+    title: impl Into<String>,
+    colour: Colour,
+    user: Option<&User>,
+) -> CreateEmbed<'a> {
+    let mut embed = CreateEmbed::new()
+        .title(title.into())
+        .colour(colour)
+        .timestamp(serenity::Timestamp::now());
+
+    if let Some(user) = user {
+        embed = embed
+            .author(serenity::CreateEmbedAuthor::new(user.tag()).icon_url(user.face()))
+            .footer(CreateEmbedFooter::new(format!("User ID: {}", user.id)));
+    }
+
+    embed
+}
+
+/// Formats a before and after field pair, truncating to the embed limits.
+#[must_use]
+pub fn before_after<'a>(embed: CreateEmbed<'a>, before: &str, after: &str) -> CreateEmbed<'a> {
+    embed
+        .field("Before", field_value(before), false)
+        .field("After", field_value(after), false)
+}
+
+/// Converts console output into a `diff` code block, stripping colours.
+#[must_use]
+pub fn diff_block(diff: &str) -> String {
+    let stripped = moth_ansi::strip(diff);
+    let stripped = stripped.trim_end_matches('\n');
+
+    // leave room for the code block.
+    format!("```diff\n{}\n```", truncate(stripped, 4000))
+}
+
+/// Fits the content into an embed field, substituting empty content.
+#[must_use]
+pub fn field_value(content: &str) -> String {
+    if content.is_empty() {
+        return String::from("None");
+    }
+
+    truncate(content, 1024)
+}
+
+/// Truncates a string to a maximum amount of characters, appending an ellipsis if cut.
+#[must_use]
+pub fn truncate(content: &str, max: usize) -> String {
+    if content.chars().count() <= max {
+        return content.to_string();
+    }
+
+    let mut truncated: String = content.chars().take(max.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_value_substitutes_empty_content() {
+        assert_eq!(field_value(""), "None");
+        assert_eq!(field_value("content"), "content");
+    }
+
+    #[test]
+    fn truncate_counts_characters() {
+        assert_eq!(truncate("abcdef", 6), "abcdef");
+        assert_eq!(truncate("abcdef", 4), "abc…");
+        assert_eq!(truncate("ééééé", 3), "éé…");
+        assert_eq!(field_value(&"a".repeat(2000)).chars().count(), 1024);
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use lumi::serenity_prelude::FullEvent;
+use serde::Serialize;
+use serde_json::{Value, json};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+/// Records incoming events to a file as JSON lines, for replaying handler bugs without live
+/// traffic.
+///
+/// Each line is the gateway dispatch the event was built from, `{"t": name, "d": payload}`.
+/// `FullEvent` can't be built outside of serenity, so replaying goes through a local gateway
+/// instead, letting serenity rebuild its cache and the events the same way it does live.
+pub struct Recorder {
+    sender: UnboundedSender<Value>,
+}
+
+impl Recorder {
+    /// Opens the file in `MOTH_RECORD` for appending, recording is off if it isn't set.
+    #[must_use]
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var("MOTH_RECORD").ok()?;
+
+        match File::options().create(true).append(true).open(&path) {
+            Ok(file) => {
+                tracing::info!("Recording events to {path}");
+                let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+                tokio::task::spawn_blocking(move || write_lines(BufWriter::new(file), receiver));
+                Some(Self { sender })
+            }
+            Err(e) => {
+                tracing::error!("Unable to open {path} for recording events: {e}");
+                None
+            }
+        }
+    }
+
+    pub fn record(&self, event: &FullEvent) {
+        let Some((name, payload)) = dispatch_of(event) else {
+            return;
+        };
+
+        let _ = self.sender.send(json!({
+            "t": name,
+            "d": payload,
+            "at": lumi::serenity_prelude::Timestamp::now(),
+        }));
+    }
+}
+
+/// Writes recorded lines until the recorder is dropped, off the runtime so events aren't held up.
+fn write_lines(mut file: BufWriter<File>, mut receiver: UnboundedReceiver<Value>) {
+    while let Some(line) = receiver.blocking_recv() {
+        if let Err(e) = writeln!(file, "{line}") {
+            tracing::error!("Unable to record an event: {e}");
+            continue;
+        }
+
+        // flushed once caught up so a crash doesn't lose the events leading up to it.
+        if receiver.is_empty()
+            && let Err(e) = file.flush()
+        {
+            tracing::error!("Unable to flush recorded events: {e}");
+        }
+    }
+}
+
+fn value(payload: &impl Serialize) -> Option<Value> {
+    serde_json::to_value(payload).ok()
+}
+
+/// Turns an event back into the name and payload of the dispatch it came from.
+///
+/// Only events the handlers or the cache care about are covered, data serenity fills in from its
+/// cache (like the old value of an update) is left out as it's rebuilt on replay.
+fn dispatch_of(event: &FullEvent) -> Option<(&'static str, Value)> {
+    let dispatch = match event {
+        FullEvent::Ready { data_about_bot, .. } => ("READY", value(data_about_bot)?),
+        FullEvent::GuildCreate { guild, .. } => ("GUILD_CREATE", value(guild)?),
+        FullEvent::GuildMembersChunk { chunk, .. } => ("GUILD_MEMBERS_CHUNK", value(chunk)?),
+        FullEvent::Message { new_message, .. } => ("MESSAGE_CREATE", value(new_message)?),
+        FullEvent::MessageUpdate { event, .. } => ("MESSAGE_UPDATE", value(event)?),
+        FullEvent::MessageDelete {
+            channel_id,
+            deleted_message_id,
+            guild_id,
+            ..
+        } => (
+            "MESSAGE_DELETE",
+            json!({ "id": deleted_message_id, "channel_id": channel_id, "guild_id": guild_id }),
+        ),
+        FullEvent::MessageDeleteBulk {
+            channel_id,
+            multiple_deleted_messages_ids,
+            guild_id,
+            ..
+        } => (
+            "MESSAGE_DELETE_BULK",
+            json!({
+                "ids": multiple_deleted_messages_ids,
+                "channel_id": channel_id,
+                "guild_id": guild_id,
+            }),
+        ),
+        FullEvent::ReactionAdd { add_reaction, .. } => {
+            ("MESSAGE_REACTION_ADD", value(add_reaction)?)
+        }
+        FullEvent::ReactionRemove {
+            removed_reaction, ..
+        } => ("MESSAGE_REACTION_REMOVE", value(removed_reaction)?),
+        FullEvent::ReactionRemoveAll {
+            channel_id,
+            removed_from_message_id,
+            ..
+        } => (
+            "MESSAGE_REACTION_REMOVE_ALL",
+            json!({ "channel_id": channel_id, "message_id": removed_from_message_id }),
+        ),
+        FullEvent::ReactionRemoveEmoji {
+            removed_reactions, ..
+        } => ("MESSAGE_REACTION_REMOVE_EMOJI", value(removed_reactions)?),
+        FullEvent::InviteCreate { data, .. } => ("INVITE_CREATE", value(data)?),
+        FullEvent::InviteDelete { data, .. } => ("INVITE_DELETE", value(data)?),
+        FullEvent::GuildMemberAddition { new_member, .. } => {
+            ("GUILD_MEMBER_ADD", value(new_member)?)
+        }
+        FullEvent::GuildMemberRemoval { guild_id, user, .. } => (
+            "GUILD_MEMBER_REMOVE",
+            json!({ "guild_id": guild_id, "user": user }),
+        ),
+        FullEvent::GuildMemberUpdate { event, .. } => ("GUILD_MEMBER_UPDATE", value(event)?),
+        FullEvent::GuildAuditLogEntryCreate {
+            entry, guild_id, ..
+        } => {
+            let mut entry = value(entry)?;
+            entry
+                .as_object_mut()?
+                .insert(String::from("guild_id"), value(guild_id)?);
+            ("GUILD_AUDIT_LOG_ENTRY_CREATE", entry)
+        }
+        FullEvent::GuildRoleCreate { new, .. } => (
+            "GUILD_ROLE_CREATE",
+            json!({ "guild_id": new.guild_id, "role": new }),
+        ),
+        FullEvent::GuildRoleDelete {
+            guild_id,
+            removed_role_id,
+            ..
+        } => (
+            "GUILD_ROLE_DELETE",
+            json!({ "guild_id": guild_id, "role_id": removed_role_id }),
+        ),
+        FullEvent::GuildRoleUpdate { new, .. } => (
+            "GUILD_ROLE_UPDATE",
+            json!({ "guild_id": new.guild_id, "role": new }),
+        ),
+        FullEvent::ChannelCreate { channel, .. } => ("CHANNEL_CREATE", value(channel)?),
+        FullEvent::ChannelDelete { channel, .. } => ("CHANNEL_DELETE", value(channel)?),
+        FullEvent::ChannelUpdate { new, .. } => ("CHANNEL_UPDATE", value(new)?),
+        FullEvent::ThreadCreate {
+            thread,
+            newly_created,
+            ..
+        } => {
+            let mut thread = value(thread)?;
+            if let Some(newly_created) = newly_created {
+                thread
+                    .as_object_mut()?
+                    .insert(String::from("newly_created"), Value::Bool(*newly_created));
+            }
+            ("THREAD_CREATE", thread)
+        }
+        FullEvent::ThreadDelete { thread, .. } => ("THREAD_DELETE", value(thread)?),
+        FullEvent::ThreadUpdate { new, .. } => ("THREAD_UPDATE", value(new)?),
+        FullEvent::VoiceChannelStatusUpdate {
+            status,
+            id,
+            guild_id,
+            ..
+        } => (
+            "VOICE_CHANNEL_STATUS_UPDATE",
+            json!({ "id": id, "guild_id": guild_id, "status": status }),
+        ),
+        FullEvent::VoiceStateUpdate { new, .. } => ("VOICE_STATE_UPDATE", value(new)?),
+        FullEvent::InteractionCreate { interaction, .. } => {
+            ("INTERACTION_CREATE", value(interaction)?)
+        }
+        _ => return None,
+    };
+
+    Some(dispatch)
+}
@@ -7,6 +7,8 @@ use rustrict::{Censor, Trie, Type};
 
 use moth_ansi::{BOLD, RED, RESET};
 
+pub mod links;
+
 pub static WHITESPACE: std::sync::LazyLock<regex::Regex> =
     std::sync::LazyLock::new(|| regex::Regex::new(r"(\s*)(\S+)").unwrap());
 
@@ -136,3 +138,24 @@ fn colour_string(content: &str, changed_words: &[&str]) -> String {
 
     result
 }
+
+/// The amount of single character edits needed to turn one string into the other.
+#[must_use]
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
@@ -0,0 +1,332 @@
+use std::{collections::HashSet, sync::LazyLock};
+
+use crate::{LINKS, levenshtein};
+
+/// Domains that are never flagged as look-alikes, along with their subdomains.
+static OFFICIAL_DOMAINS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+    HashSet::from([
+        "discord.com",
+        "discord.gg",
+        "discord.gift",
+        "discord.new",
+        "discord.dev",
+        "discord.co",
+        "discord.media",
+        "discordapp.com",
+        "discordapp.net",
+        "discordstatus.com",
+        "dis.gd",
+        "steamcommunity.com",
+        "steampowered.com",
+        "steamstatic.com",
+        "steamusercontent.com",
+        "steamchina.com",
+    ])
+});
+
+/// Legitimate sites that are close enough to an impersonated name to look like one.
+static KNOWN_LOOKALIKES: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+    HashSet::from([
+        "discords.com",
+        "discord-bots.gg",
+        "discordbots.org",
+        "discord.me",
+        "discord.io",
+        "discord.st",
+    ])
+});
+
+/// Names that scam domains imitate, and the domain they pretend to be.
+const IMPERSONATED_NAMES: &[(&str, &str)] = &[
+    ("discord", "discord.com"),
+    ("discordapp", "discord.com"),
+    ("steamcommunity", "steamcommunity.com"),
+    ("steampowered", "steampowered.com"),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LinkReason {
+    /// The domain, or a parent of it, is on the blocklist.
+    Blocklisted(String),
+    /// The domain imitates this official domain.
+    Lookalike(&'static str),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkMatch {
+    /// The domain as it was written in the message.
+    pub domain: String,
+    pub reason: LinkReason,
+}
+
+/// Checks the domains of every link in a message against the blocklist and for look-alikes of
+/// official domains.
+///
+/// Domains on the allowlist, or whose parent is on it, are skipped entirely.
+#[must_use]
+pub fn check_links(
+    content: &str,
+    blocklist: &HashSet<String>,
+    allowlist: &HashSet<String>,
+) -> Vec<LinkMatch> {
+    let mut matches = Vec::new();
+
+    for domain in extract_domains(content) {
+        let normalised = normalise_domain(&domain);
+
+        if listed(&domain, allowlist).is_some() || listed(&normalised, allowlist).is_some() {
+            continue;
+        }
+
+        let reason = listed(&domain, blocklist)
+            .or_else(|| listed(&normalised, blocklist))
+            .map(|entry| LinkReason::Blocklisted(entry.to_string()))
+            .or_else(|| lookalike(&domain, &normalised).map(LinkReason::Lookalike));
+
+        if let Some(reason) = reason {
+            matches.push(LinkMatch { domain, reason });
+        }
+    }
+
+    matches
+}
+
+/// Extracts the lowercased domain of every link, without duplicates.
+///
+/// Only links with a scheme or `www.` are considered, so things like `discord.py` in normal
+/// conversation aren't treated as links.
+#[must_use]
+pub fn extract_domains(content: &str) -> Vec<String> {
+    let mut domains = Vec::new();
+
+    for link in LINKS.find_iter(content) {
+        let link = link.as_str();
+        let rest = link.split_once("://").map_or(link, |(_, rest)| rest);
+        let authority = rest.split(['/', '?', '#', '\\']).next().unwrap_or_default();
+        // anything before an @ is userinfo, `https://discord.com@scam.example` goes to the latter.
+        let host = authority.rsplit('@').next().unwrap_or_default();
+        let host = host.split(':').next().unwrap_or_default();
+        let host = host
+            .trim_end_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+
+        if host.contains('.') && !domains.contains(&host) {
+            domains.push(host);
+        }
+    }
+
+    domains
+}
+
+/// Decodes punycode labels and folds look-alike characters, so `xn--dscord-pvf` and `dіscord`
+/// with a cyrillic i compare equal to what they imitate.
+#[must_use]
+pub fn normalise_domain(domain: &str) -> String {
+    let domain = domain.trim_end_matches('.').to_lowercase();
+    let domain = domain.strip_prefix("www.").unwrap_or(&domain);
+    // invalid labels are left as they were, which is as good as it gets for comparing.
+    let (domain, _) = idna::domain_to_unicode(domain);
+
+    domain
+        .split('.')
+        .map(|label| {
+            label
+                .chars()
+                .filter(|c| !matches!(c, '\u{200B}'..='\u{200D}' | '\u{FEFF}' | '\u{00AD}'))
+                .map(fold_char)
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Returns the entry of the list that matches the domain or one of its parents.
+fn listed<'a>(domain: &str, list: &'a HashSet<String>) -> Option<&'a str> {
+    let mut candidate = domain;
+    loop {
+        if let Some(entry) = list.get(candidate) {
+            return Some(entry);
+        }
+
+        candidate = candidate.split_once('.')?.1;
+    }
+}
+
+/// Checks if a domain imitates an official one, the official check is done on the domain as
+/// written so look-alike characters can't sneak past it.
+fn lookalike(domain: &str, normalised: &str) -> Option<&'static str> {
+    let mut candidate = domain.strip_prefix("www.").unwrap_or(domain);
+    loop {
+        if OFFICIAL_DOMAINS.contains(candidate) || KNOWN_LOOKALIKES.contains(candidate) {
+            return None;
+        }
+
+        match candidate.split_once('.') {
+            Some((_, parent)) => candidate = parent,
+            None => break,
+        }
+    }
+
+    let skeleton = skeleton(normalised);
+
+    // `discord.com.scam.example` and `steamcommunity.com-trade.example`.
+    for official in OFFICIAL_DOMAINS.iter() {
+        let official_skeleton = self::skeleton(official);
+        if skeleton.starts_with(&official_skeleton)
+            && matches!(
+                skeleton.as_bytes().get(official_skeleton.len()),
+                Some(b'.' | b'-')
+            )
+        {
+            return Some(official);
+        }
+    }
+
+    let labels = skeleton.split('.').collect::<Vec<_>>();
+    if labels.len() < 2 {
+        return None;
+    }
+
+    // the name right before the tld, so `dlscord.gift` and `discord.gifts` both get caught, along
+    // with its parts for `discord-nitro.gift`.
+    let name = labels[labels.len() - 2];
+    IMPERSONATED_NAMES
+        .iter()
+        .find(|(impersonated, _)| {
+            let impersonated = self::skeleton(impersonated);
+            std::iter::once(name)
+                .chain(name.split('-'))
+                .any(|part| levenshtein(part, &impersonated) <= impersonated.len() / 5)
+        })
+        .map(|(_, official)| *official)
+}
+
+/// Collapses characters and sequences that look alike, only used for comparisons.
+fn skeleton(domain: &str) -> String {
+    domain
+        .replace("rn", "m")
+        .replace("vv", "w")
+        .replace("cl", "d")
+        .chars()
+        .map(|c| match c {
+            '0' => 'o',
+            '1' | 'l' => 'i',
+            '3' => 'e',
+            '4' => 'a',
+            '5' => 's',
+            '7' => 't',
+            c => c,
+        })
+        .collect()
+}
+
+/// Maps characters from other scripts that look like latin letters to those letters.
+fn fold_char(c: char) -> char {
+    match c {
+        // fullwidth forms.
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(u32::from(c) - 0xFEE0).unwrap_or(c),
+        'а' | 'α' | 'ɑ' | 'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+        'Ь' | 'ь' => 'b',
+        'с' | 'ϲ' | 'ç' => 'c',
+        'ԁ' | 'ɗ' => 'd',
+        'е' | 'ε' | 'è' | 'é' | 'ê' | 'ë' => 'e',
+        'ɡ' => 'g',
+        'һ' => 'h',
+        'і' | 'ι' | 'ı' | 'ɩ' | 'ì' | 'í' | 'î' | 'ï' => 'i',
+        'ј' => 'j',
+        'κ' | 'к' => 'k',
+        'ӏ' | 'ʟ' => 'l',
+        'м' => 'm',
+        'п' | 'ñ' => 'n',
+        'о' | 'ο' | 'σ' | 'ò' | 'ó' | 'ô' | 'õ' | 'ö' => 'o',
+        'р' | 'ρ' => 'p',
+        'ԛ' => 'q',
+        'ѕ' => 's',
+        'т' | 'τ' => 't',
+        'υ' | 'ù' | 'ú' | 'û' | 'ü' => 'u',
+        'ν' | 'ѵ' => 'v',
+        'ԝ' | 'ω' => 'w',
+        'х' | 'χ' => 'x',
+        'у' | 'γ' | 'ý' | 'ÿ' => 'y',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookalike_of(domain: &str) -> Option<&'static str> {
+        lookalike(domain, &normalise_domain(domain))
+    }
+
+    #[test]
+    fn decodes_punycode() {
+        assert_eq!(normalise_domain("xn--dscord-pvf.com"), "discord.com");
+        assert_eq!(normalise_domain("www.DISCORD.com."), "discord.com");
+    }
+
+    #[test]
+    fn official_domains_pass() {
+        assert_eq!(lookalike_of("discord.com"), None);
+        assert_eq!(lookalike_of("cdn.discordapp.com"), None);
+        assert_eq!(lookalike_of("store.steampowered.com"), None);
+    }
+
+    #[test]
+    fn known_lookalikes_pass() {
+        assert_eq!(lookalike_of("discords.com"), None);
+        assert_eq!(lookalike_of("discord-bots.gg"), None);
+        assert_eq!(lookalike_of("www.discord.me"), None);
+    }
+
+    #[test]
+    fn unrelated_domains_pass() {
+        assert_eq!(lookalike_of("github.com"), None);
+        assert_eq!(lookalike_of("example.com"), None);
+        assert_eq!(lookalike_of("discordjs.guide"), None);
+    }
+
+    #[test]
+    fn catches_lookalikes() {
+        assert_eq!(lookalike_of("dlscord.gift"), Some("discord.com"));
+        assert_eq!(lookalike_of("discord-nitro.gift"), Some("discord.com"));
+        assert_eq!(lookalike_of("xn--dscord-pvf.com"), Some("discord.com"));
+        assert_eq!(
+            lookalike_of("steamcornmunity.com"),
+            Some("steamcommunity.com")
+        );
+        assert_eq!(
+            lookalike_of("discord.com.scam.example"),
+            Some("discord.com")
+        );
+    }
+
+    #[test]
+    fn extracts_the_host() {
+        assert_eq!(
+            extract_domains("https://discord.com@scam.example/login"),
+            ["scam.example"]
+        );
+        assert_eq!(
+            extract_domains("see https://Example.com:443/, or https://example.com"),
+            ["example.com"]
+        );
+        assert!(extract_domains("written with discord.py").is_empty());
+    }
+
+    #[test]
+    fn checks_the_lists() {
+        let blocklist = HashSet::from([String::from("scam.example")]);
+        let allowlist = HashSet::from([String::from("dlscord.gift")]);
+
+        assert_eq!(
+            check_links("https://login.scam.example/", &blocklist, &allowlist),
+            [LinkMatch {
+                domain: String::from("login.scam.example"),
+                reason: LinkReason::Blocklisted(String::from("scam.example")),
+            }]
+        );
+        assert!(check_links("https://dlscord.gift/nitro", &blocklist, &allowlist).is_empty());
+    }
+}
@@ -3,6 +3,6 @@ mod reactions;
 pub mod starboard;
 
 pub use components::handle_component;
-pub use starboard::{starboard_add_handler, starboard_remove_handler};
+pub use starboard::{starboard_add_handler, starboard_clear_handler, starboard_remove_handler};
 
 pub(crate) use moth_core::data::structs::{Data, Error};
@@ -16,13 +16,27 @@ pub(crate) async fn get_unique_reaction_count(
     starboard_msg: &StarboardMessage,
     reaction: &Reaction,
     state: Option<bool>,
+) -> Result<i16, Error> {
+    maybe_mutate(
+        &mut data.database.starboard.lock().reactions_cache,
+        reaction,
+        state,
+    );
+
+    count_unique_reactions(ctx, data, starboard_msg).await
+}
+
+/// Get the unique reaction count of the original and the starboard message, fetching whichever
+/// isn't cached.
+pub(crate) async fn count_unique_reactions(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    starboard_msg: &StarboardMessage,
 ) -> Result<i16, Error> {
     let author_id = *starboard_msg.user_id;
 
     let (origin_reactions, starboard_reactions) = {
-        let mut guard = data.database.starboard.lock();
-
-        maybe_mutate(&mut guard.reactions_cache, reaction, state);
+        let guard = data.database.starboard.lock();
 
         // Always get the map entries for both message IDs
         let origin_reactions = guard
@@ -1,6 +1,6 @@
 use crate::{
     Data, Error,
-    reactions::{get_reaction_count, get_unique_reaction_count},
+    reactions::{count_unique_reactions, get_reaction_count, get_unique_reaction_count},
 };
 use lumi::serenity_prelude as serenity;
 use moth_core::data::database::{
@@ -92,6 +92,70 @@ pub async fn starboard_remove_handler(
     Ok(())
 }
 
+/// Recalculates the star count after a moderator cleared the reactions of a message, either all
+/// of them or only one emoji.
+pub async fn starboard_clear_handler(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    message_id: serenity::MessageId,
+    emoji: Option<&serenity::ReactionType>,
+) -> Result<(), Error> {
+    if !data.starboard_config.active {
+        return Ok(());
+    }
+
+    if emoji.is_some_and(|emoji| !is_star(data, emoji)) {
+        return Ok(());
+    }
+
+    let mut starboard = if let Ok(starboard) = data.database.get_starboard_msg(message_id).await {
+        starboard
+    } else if let Ok(starboard) = data
+        .database
+        .get_starboard_msg_by_starboard_id(message_id)
+        .await
+    {
+        starboard
+    } else {
+        return Ok(());
+    };
+
+    data.database
+        .starboard
+        .lock()
+        .reactions_cache
+        .insert(message_id, (*starboard.user_id, Vec::new()));
+
+    let new_count = count_unique_reactions(ctx, data, &starboard).await?;
+
+    if new_count == starboard.star_count {
+        return Ok(());
+    }
+
+    starboard.star_count = new_count;
+
+    let message = starboard_edit_message(ctx, data, &starboard);
+
+    starboard
+        .starboard_message_channel
+        .edit_message(&ctx.http, *starboard.starboard_message_id, message)
+        .await?;
+
+    data.database
+        .update_star_count(starboard.id, starboard.star_count)
+        .await?;
+
+    Ok(())
+}
+
+fn is_star(data: &Data, emoji: &serenity::ReactionType) -> bool {
+    if let serenity::ReactionType::Unicode(unicode) = emoji {
+        unicode == &data.starboard_config.star_emoji
+    } else {
+        false
+    }
+}
+
 async fn remove_reaction(ctx: &serenity::Context, reaction: &serenity::Reaction) {
     let has_permissions = has_permissions(ctx, reaction);
 
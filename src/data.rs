@@ -3,42 +3,35 @@ use moth_core::data::{
     database::reactions::EmoteProcessor,
     structs::{Data, StarboardConfig, WebServer},
 };
-use parking_lot::lock_api::Mutex;
 use serenity::all::{GenericChannelId, GuildId, RoleId};
-use std::{
-    collections::VecDeque,
-    sync::{Arc, atomic::AtomicBool},
-};
+use std::sync::{Arc, atomic::AtomicBool};
 
-pub async fn setup() -> Arc<Data> {
+pub async fn setup(reqwest: reqwest::Client) -> Arc<Data> {
     let handler = moth_core::data::database::init_data().await;
 
     let config = moth_core::config::MothConfig::load_config();
     let starboard_config = starboard_config();
 
-    let auto_poop_users = sqlx::query!("SELECT user_id FROM auto_bad_role")
-        .fetch_all(&handler.db)
-        .await
-        .unwrap();
-
-    let auto_pooped = dashmap::DashSet::new();
-    for record in auto_poop_users {
-        #[expect(clippy::cast_sign_loss)]
-        auto_pooped.insert(serenity::all::UserId::new(record.user_id as u64));
+    let appearance_roles = dashmap::DashSet::new();
+    for assignment in handler.get_appearance_role_assignments().await.unwrap() {
+        appearance_roles.insert(assignment);
     }
 
     Arc::new(Data {
         has_started: AtomicBool::new(false),
         database: Arc::new(handler),
         time_started: std::time::Instant::now(),
-        reqwest: reqwest::Client::new(),
+        reqwest,
         config: parking_lot::RwLock::new(config),
         anti_delete_cache: moth_core::data::structs::AntiDeleteCache::default(),
         starboard_config,
         new_join_vc: DashMap::default(),
-        osu_game_joins: Mutex::new(VecDeque::new()),
+        raid: moth_core::data::structs::RaidTracker::default(),
+        anti_nuke: moth_core::data::structs::AntiNukeTracker::default(),
+        spam: moth_core::data::structs::SpamTracker::default(),
+        invite_lookups: moth_core::data::structs::InviteLookups::default(),
         web: WebServer::new().await,
-        auto_pooped,
+        appearance_roles,
         emote_processor: EmoteProcessor::default(),
         private_vc: moth_core::data::database::private_vcs::PrivateVcHandler::default(),
     })
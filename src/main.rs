@@ -3,6 +3,7 @@
 
 mod data;
 mod error;
+mod replay;
 
 use ::serenity::all::GuildId;
 use lumi::serenity_prelude::{self as serenity};
@@ -14,6 +15,16 @@ async fn main() {
     tracing_subscriber::fmt::init();
     dotenvy::dotenv().unwrap();
 
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some("replay") {
+        let path = args.next().expect("Usage: moth replay <recording.jsonl>");
+        if let Err(e) = replay::run(&path).await {
+            tracing::error!("Replay failed: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let options = lumi::FrameworkOptions {
         commands: moth_commands::commands(),
         prefix_options: lumi::PrefixFrameworkOptions {
@@ -36,34 +47,17 @@ async fn main() {
 
     let token = serenity::Token::from_env("MOTH_TOKEN")
         .expect("Missing `MOTH_TOKEN` environment variable.");
-    let intents = serenity::GatewayIntents::non_privileged()
-        | serenity::GatewayIntents::MESSAGE_CONTENT
-        | serenity::GatewayIntents::GUILD_MEMBERS;
 
-    let mut settings = serenity::Settings::default();
-    settings.max_messages = 1000;
+    let data = data::setup(reqwest::Client::new()).await;
 
-    let data = data::setup().await;
-
-    /*     {
-        let mut joins = data.osu_game_joins.lock();
-        joins.push_back(UserId::new(111111111111111111));
-        joins.push_back(UserId::new(111111111111111112));
-        joins.push_back(UserId::new(111111111111111113));
-        joins.push_back(UserId::new(111111111111111114));
-        joins.push_back(UserId::new(111111111111111115));
-        joins.push_back(UserId::new(222222222222222221));
-        joins.push_back(UserId::new(222222222222222222));
-        joins.push_back(UserId::new(222222222222222223));
-        joins.push_back(UserId::new(222222222222222224));
-        joins.push_back(UserId::new(222222222222222225));
-    } */
-
-    let mut client = serenity::Client::builder(token, intents)
+    let presences = data.config.read().activity.enabled;
+    let mut client = serenity::Client::builder(token, intents(presences))
         .framework(framework)
         .data(data.clone())
-        .cache_settings(settings)
-        .event_handler(moth_events::Handler)
+        .cache_settings(cache_settings())
+        .event_handler(moth_events::Handler {
+            recorder: moth_events::recorder::Recorder::from_env(),
+        })
         .await
         .unwrap();
 
@@ -85,6 +79,25 @@ async fn main() {
     client.start().await.unwrap();
 }
 
+fn intents(presences: bool) -> serenity::GatewayIntents {
+    let intents = serenity::GatewayIntents::non_privileged()
+        | serenity::GatewayIntents::MESSAGE_CONTENT
+        | serenity::GatewayIntents::GUILD_MEMBERS;
+
+    // privileged and needs approval, only requested when activity sampling is enabled.
+    if presences {
+        intents | serenity::GatewayIntents::GUILD_PRESENCES
+    } else {
+        intents
+    }
+}
+
+fn cache_settings() -> serenity::Settings {
+    let mut settings = serenity::Settings::default();
+    settings.max_messages = 1000;
+    settings
+}
+
 // i don't want Accelas commands using my norm prefix, this is jank.
 #[expect(clippy::unused_async)]
 async fn try_strip_prefix(msg: &serenity::Message) -> Result<Option<(&str, &str)>, Error> {
@@ -0,0 +1,408 @@
+use std::{
+    future::IntoFuture,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ::serenity::futures::{SinkExt, StreamExt};
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::{
+        State, WebSocketUpgrade,
+        ws::{Message, WebSocket},
+    },
+    http::{Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use lumi::serenity_prelude as serenity;
+use moth_core::data::structs::Error;
+use serde_json::{Value, json};
+use tokio::sync::Notify;
+
+/// Not a real token, nothing leaves this machine during a replay.
+const TOKEN: &str = "MTAwMDAwMDAwMDAwMDAwMDAw.replay.replay";
+/// Milliseconds between the unix and discord epochs.
+const DISCORD_EPOCH: u64 = 1_420_070_400_000;
+
+struct Replay {
+    /// Where the gateway and http stub are listening.
+    address: String,
+    ready: Value,
+    events: Vec<(String, Value)>,
+    delay: Duration,
+    started: AtomicBool,
+    finished: Notify,
+    ids: AtomicU64,
+}
+
+impl Replay {
+    fn next_id(&self) -> u64 {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+
+        (millis.saturating_sub(DISCORD_EPOCH) << 22)
+            | (self.ids.fetch_add(1, Ordering::Relaxed) & 0xFFF)
+    }
+
+    /// A message from the bot, echoing what was sent like discord does.
+    fn message(&self, channel_id: &str, message_id: Option<&str>, body: &Value) -> Value {
+        json!({
+            "id": message_id.map_or_else(|| self.next_id().to_string(), str::to_owned),
+            "channel_id": channel_id,
+            "author": self.ready["user"],
+            "content": body.get("content").cloned().unwrap_or_else(|| json!("")),
+            "embeds": body.get("embeds").cloned().unwrap_or_else(|| json!([])),
+            "timestamp": serenity::Timestamp::now(),
+            "edited_timestamp": null,
+            "tts": false,
+            "mention_everyone": false,
+            "mentions": [],
+            "mention_roles": [],
+            "attachments": [],
+            "components": [],
+            "pinned": false,
+            "type": 0,
+            "flags": 0,
+        })
+    }
+}
+
+/// Feeds a recording made with `MOTH_RECORD` through the event handler against a local database.
+///
+/// Serenity connects to a local gateway that sends the recorded dispatches, so its cache and
+/// events are built the same way they are live. Every http request goes to a stub that logs it
+/// and answers with just enough for the handlers to carry on, requests made with `Data.reqwest`
+/// are proxied through it too and anything over https is refused.
+pub async fn run(path: &str) -> Result<(), Error> {
+    if std::env::var_os("MOTH_TOKEN").is_some() {
+        return Err(
+            "Replays refuse to run with MOTH_TOKEN set, unset it or remove it from the .env file."
+                .into(),
+        );
+    }
+
+    let database_url = std::env::var("DATABASE_URL")
+        .map_err(|_| "No database url found in environment variables!")?;
+    if !is_local(&database_url) {
+        return Err("Replays only run against a local database, point DATABASE_URL at one.".into());
+    }
+
+    let recording = std::fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read the recording {path}: {e}"))?;
+    let (ready, events) = parse_recording(&recording);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| format!("Unable to bind the replay server: {e}"))?;
+    let address = listener.local_addr()?.to_string();
+
+    let replay = Arc::new(Replay {
+        ready: ready_for(ready, &address),
+        address,
+        events,
+        delay: Duration::from_millis(
+            std::env::var("MOTH_REPLAY_DELAY_MS")
+                .ok()
+                .and_then(|delay| delay.parse().ok())
+                .unwrap_or(100),
+        ),
+        started: AtomicBool::new(false),
+        finished: Notify::new(),
+        ids: AtomicU64::new(0),
+    });
+
+    let app = Router::new()
+        .route("/gateway", get(gateway))
+        .fallback(http_stub)
+        .with_state(replay.clone());
+
+    let server = axum::serve(listener, app).into_future();
+
+    tracing::info!("Replaying {} events from {path}", replay.events.len());
+
+    let token: serenity::Token = TOKEN.parse()?;
+    let http = serenity::HttpBuilder::new(token.clone())
+        .proxy(format!("http://{}", replay.address))
+        .build();
+
+    let reqwest = reqwest::Client::builder()
+        .proxy(reqwest::Proxy::all(format!("http://{}", replay.address))?)
+        .build()?;
+    let data = crate::data::setup(reqwest).await;
+
+    let presences = data.config.read().activity.enabled;
+    let mut client =
+        serenity::ClientBuilder::new_with_http(token, Arc::new(http), crate::intents(presences))
+            .data(data.clone())
+            .cache_settings(crate::cache_settings())
+            .event_handler(moth_events::Handler::default())
+            .await?;
+
+    let shutdown_trigger = client.shard_manager.get_shutdown_trigger();
+    tokio::spawn(async move {
+        replay.finished.notified().await;
+
+        // handlers run in their own tasks and emote usage is batched, let them finish.
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        tokio::join!(
+            data.web.task_sender.shutdown(),
+            data.private_vc.sender.shutdown(),
+            data.emote_processor.sender.shutdown(),
+        );
+
+        tracing::info!("Finished replaying {}", replay.events.len());
+        shutdown_trigger();
+    });
+
+    tokio::select! {
+        result = server => result.map_err(|e| format!("The replay server stopped: {e}"))?,
+        result = client.start() => result?,
+    }
+
+    Ok(())
+}
+
+/// Hosts on this machine or a unix socket, so a replay can't write to a real database.
+fn is_local(database_url: &str) -> bool {
+    let rest = database_url
+        .split_once("://")
+        .map_or(database_url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?']).next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    let host = if host.starts_with('[') {
+        host.split_inclusive(']').next().unwrap_or_default()
+    } else {
+        host.split(':').next().unwrap_or_default()
+    };
+
+    matches!(host, "" | "localhost" | "127.0.0.1" | "[::1]")
+}
+
+/// Splits a recording into its first ready and every other dispatch, in order.
+fn parse_recording(recording: &str) -> (Option<Value>, Vec<(String, Value)>) {
+    let mut ready = None;
+    let mut events = Vec::new();
+
+    for (i, line) in recording.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let Ok(mut dispatch) = serde_json::from_str::<Value>(line) else {
+            tracing::warn!(
+                "Skipping line {} of the recording, it isn't valid JSON.",
+                i + 1
+            );
+            continue;
+        };
+
+        let (Some(name), Some(payload)) = (
+            dispatch["t"].as_str().map(str::to_owned),
+            dispatch.get_mut("d").map(Value::take),
+        ) else {
+            tracing::warn!(
+                "Skipping line {} of the recording, it isn't a dispatch.",
+                i + 1
+            );
+            continue;
+        };
+
+        // later readys are from reconnects, the cache is already filled by then.
+        if name == "READY" {
+            ready.get_or_insert(payload);
+        } else {
+            events.push((name, payload));
+        }
+    }
+
+    (ready, events)
+}
+
+/// Points the ready at the local gateway, making one up if the recording didn't have it.
+fn ready_for(ready: Option<Value>, address: &str) -> Value {
+    let mut ready = ready.unwrap_or_else(|| {
+        json!({
+            "v": 10,
+            "user": {
+                "id": "1",
+                "username": "moth",
+                "discriminator": "0",
+                "global_name": null,
+                "avatar": null,
+                "bot": true,
+            },
+            "guilds": [],
+            "session_id": "replay",
+            "application": { "id": "1", "flags": 0 },
+        })
+    });
+
+    ready["resume_gateway_url"] = json!(format!("ws://{address}/gateway"));
+    ready["shard"] = json!([0, 1]);
+    ready
+}
+
+async fn gateway(ws: WebSocketUpgrade, State(replay): State<Arc<Replay>>) -> Response {
+    ws.on_upgrade(move |socket| serve_gateway(socket, replay))
+}
+
+/// Speaks just enough of the gateway protocol to identify the shard and send it the recording.
+async fn serve_gateway(socket: WebSocket, replay: Arc<Replay>) {
+    let (mut sender, mut receiver) = socket.split();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Value>();
+
+    tokio::spawn(async move {
+        while let Some(payload) = rx.recv().await {
+            if sender
+                .send(Message::Text(payload.to_string().into()))
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    let _ = tx.send(json!({ "op": 10, "d": { "heartbeat_interval": 41250 } }));
+
+    // heartbeats are acknowledged for as long as the shard stays connected.
+    let (identified_tx, identified_rx) = tokio::sync::oneshot::channel();
+    let acks = tx.clone();
+    tokio::spawn(async move {
+        let mut identified_tx = Some(identified_tx);
+
+        while let Some(Ok(message)) = receiver.next().await {
+            let Message::Text(text) = message else {
+                continue;
+            };
+
+            match serde_json::from_str::<Value>(text.as_str())
+                .ok()
+                .and_then(|payload| payload["op"].as_u64())
+            {
+                Some(1) => {
+                    let _ = acks.send(json!({ "op": 11 }));
+                }
+                Some(2) => {
+                    if let Some(identified_tx) = identified_tx.take() {
+                        let _ = identified_tx.send(());
+                    }
+                }
+                _ => {}
+            }
+        }
+    });
+
+    if identified_rx.await.is_err() {
+        return;
+    }
+
+    let _ = tx.send(json!({ "op": 0, "t": "READY", "s": 1, "d": replay.ready }));
+
+    if replay.started.swap(true, Ordering::SeqCst) {
+        tracing::warn!("The shard reconnected during the replay, the recording isn't sent again.");
+        return;
+    }
+
+    for (seq, (name, payload)) in (2..).zip(&replay.events) {
+        let _ = tx.send(json!({ "op": 0, "t": name, "s": seq, "d": payload }));
+        tokio::time::sleep(replay.delay).await;
+    }
+
+    replay.finished.notify_one();
+}
+
+/// Logs every request the handlers make and answers with the least that lets them carry on.
+async fn http_stub(
+    State(replay): State<Arc<Replay>>,
+    method: Method,
+    uri: Uri,
+    body: Bytes,
+) -> Response {
+    // https through the proxy would reach the real host, so nothing is tunnelled.
+    if method == Method::CONNECT {
+        tracing::warn!("Refused {method} {uri}");
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let path = uri.path().strip_prefix("/api/v10").unwrap_or(uri.path());
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or(Value::Null);
+
+    if body.is_null() {
+        tracing::info!("{method} {path}");
+    } else {
+        tracing::info!("{method} {path} {body}");
+    }
+
+    let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+    let user = &replay.ready["user"];
+
+    match (&method, segments.as_slice()) {
+        (&Method::GET, ["gateway", "bot"]) => Json(json!({
+            "url": format!("ws://{}/gateway", replay.address),
+            "shards": 1,
+            "session_start_limit": {
+                "total": 1000,
+                "remaining": 1000,
+                "reset_after": 0,
+                "max_concurrency": 1,
+            },
+        }))
+        .into_response(),
+        (&Method::GET, ["users", "@me"]) => Json(user.clone()).into_response(),
+        (&Method::GET, ["oauth2", "applications", "@me"]) => Json(json!({
+            "id": replay.ready["application"]["id"],
+            "name": user["username"],
+            "icon": null,
+            "description": "",
+            "bot_public": false,
+            "bot_require_code_grant": false,
+            "verify_key": "",
+            "flags": 0,
+            "owner": user,
+        }))
+        .into_response(),
+        (&Method::GET, ["guilds", _, "audit-logs"]) => Json(json!({
+            "audit_log_entries": [],
+            "users": [],
+            "integrations": [],
+            "webhooks": [],
+            "threads": [],
+            "application_commands": [],
+            "auto_moderation_rules": [],
+            "guild_scheduled_events": [],
+        }))
+        .into_response(),
+        (&Method::GET, [.., "reactions", _]) => Json(json!([])).into_response(),
+        (&Method::POST, ["channels", channel_id, "messages"]) => {
+            Json(replay.message(channel_id, None, &body)).into_response()
+        }
+        (&Method::PATCH, ["channels", channel_id, "messages", message_id]) => {
+            Json(replay.message(channel_id, Some(message_id), &body)).into_response()
+        }
+        (&Method::POST, ["users", "@me", "channels"]) => Json(json!({
+            "id": replay.next_id().to_string(),
+            "type": 1,
+            "recipients": [{
+                "id": body["recipient_id"],
+                "username": "replay",
+                "discriminator": "0",
+                "avatar": null,
+            }],
+        }))
+        .into_response(),
+        (&Method::GET, _) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "message": "Not stubbed by the replay.", "code": 0 })),
+        )
+            .into_response(),
+        _ => StatusCode::NO_CONTENT.into_response(),
+    }
+}